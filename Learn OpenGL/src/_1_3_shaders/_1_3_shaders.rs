@@ -12,7 +12,7 @@ use std::ffi::CString;
 
 use cgmath::Vector3;
 
-use common::shader::Shader;
+use common::reloadable_shader::ReloadableShader;
 
 // settings
 const SCREEN_WIDTH: u32 = 1024;
@@ -39,11 +39,17 @@ pub fn main() {
     // load OpenGL function pointers
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let (shader_program, vao) = unsafe {
-        let shader_program = Shader::new(
+    let (mut shader_program, vao) = unsafe {
+        let shader_program = match ReloadableShader::new(
             "_1_3_shaders/vertex_shader.glsl",
             "_1_3_shaders/fragment_shader.glsl",
-        );
+        ) {
+            Ok(shader_program) => shader_program,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            },
+        };
 
         let vertices: [f32; 18] = [
             // positions        // colors
@@ -93,6 +99,7 @@ pub fn main() {
     let mut offset = Vector3::new(0.0, 0.0, 0.0);
     while !window.should_close() {
         process_events(&mut window, &events);
+        shader_program.reload_if_changed();
 
         unsafe {
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
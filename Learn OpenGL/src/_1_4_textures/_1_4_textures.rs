@@ -12,9 +12,9 @@ use std::ptr;
 use std::mem;
 use std::os::raw::c_void;
 use std::ffi::CStr;
-use std::path::Path;
 
-use common::shader::Shader;
+use common::assets;
+use common::reloadable_shader::ReloadableShader;
 
 // settings
 const SCREEN_WIDTH: u32 = 1024;
@@ -41,11 +41,17 @@ pub fn main() {
     // load OpenGL function pointers
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let (shader_program, vao, vbo, ebo, texture1, texture2) = unsafe {
-        let shader_program = Shader::new(
+    let (mut shader_program, vao, vbo, ebo, texture1, texture2) = unsafe {
+        let shader_program = match ReloadableShader::new(
             "_1_4_textures/vertex_shader.glsl",
             "_1_4_textures/fragment_shader.glsl",
-        );
+        ) {
+            Ok(shader_program) => shader_program,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            },
+        };
 
         let vertices: [f32; 32] = [
             // positions       // colors        // texture coords
@@ -107,8 +113,8 @@ pub fn main() {
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
         // load image, create texture and generate mipmaps
-        let img = image::open(&Path::new("_1_4_textures/texture_container.jpg"))
-                    .expect("Failed to texture from file");
+        let img = assets::open_image("_1_4_textures/texture_container.jpg")
+                    .unwrap_or_else(|error| panic!("Failed to texture from file: {}", error));
         let data = img.raw_pixels();
         gl::TexImage2D(gl::TEXTURE_2D,
                        0,
@@ -132,8 +138,8 @@ pub fn main() {
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
         gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
         // load image, create texture and generate mipmaps
-        let img = image::open(&Path::new("_1_4_textures/texture_awesome.png"))
-                    .expect("Failed to load texture from file");
+        let img = assets::open_image("_1_4_textures/texture_awesome.png")
+                    .unwrap_or_else(|error| panic!("Failed to load texture from file: {}", error));
         let img = img.flipv(); // flip loaded texture on the y-axis.
         let data = img.raw_pixels();
         gl::TexImage2D(gl::TEXTURE_2D,
@@ -147,10 +153,6 @@ pub fn main() {
                        &data[0] as *const u8 as *const c_void);
         gl::GenerateMipmap(gl::TEXTURE_2D);
 
-        shader_program.use_program();
-        shader_program.set_int(c_str!("texture1"), 0);
-        shader_program.set_int(c_str!("texture2"), 1);
-
         gl::BindVertexArray(0);
 
         (shader_program, vao, vbo, ebo, texture1, texture2)
@@ -159,12 +161,16 @@ pub fn main() {
     let mix_ratio = 0.2;
     while !window.should_close() {
         process_events(&mut window, &events);
+        shader_program.reload_if_changed();
 
         unsafe {
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
             shader_program.use_program();
+            // set every frame, since a reloaded program starts over with default uniforms
+            shader_program.set_int(c_str!("texture1"), 0);
+            shader_program.set_int(c_str!("texture2"), 1);
             shader_program.set_float(c_str!("mixRatio"), mix_ratio);
 
             gl::ActiveTexture(gl::TEXTURE0);
@@ -7,6 +7,8 @@ use cgmath::{Point3, Vector3};
 
 use std::sync::mpsc::Receiver;
 
+use common::load_error::LoadError;
+
 use super::scene_graph::SceneGraph;
 use super::cube_cloud::CubeCloud;
 
@@ -16,6 +18,31 @@ const WINDOW_NAME: &'static str = "Learn OpenGL Lesson 1.6 Coordinate Systems";
 const SCREEN_WIDTH: u32 = 1024;
 const SCREEN_HEIGHT: u32 = 768;
 
+fn build_scene_graph() -> Result<SceneGraph, LoadError> {
+    let mut scene_graph = SceneGraph::new(SCREEN_WIDTH, SCREEN_HEIGHT);
+    scene_graph.update_camera(
+        Point3::new(0.0, 0.0, 3.0),
+        Point3::new(0.0, 0.0, -1000.0),
+        Vector3::new(0.0, 1.0, 0.0),
+    );
+
+    let cube_positions: Vec<Vector3<f32>> = vec![
+        Vector3::new( 0.0,  0.0,  0.0),
+        Vector3::new( 2.0,  5.0, -15.0),
+        Vector3::new(-1.5, -2.2, -2.5),
+        Vector3::new(-3.8, -2.0, -12.3),
+        Vector3::new( 2.4, -0.4, -3.5),
+        Vector3::new(-1.7,  3.0, -7.5),
+        Vector3::new( 1.3, -2.0, -2.5),
+        Vector3::new( 1.5,  2.0, -2.5),
+        Vector3::new( 1.5,  0.2, -1.5),
+        Vector3::new(-1.3,  1.0, -1.5),
+    ];
+    scene_graph.add_element(Box::new(CubeCloud::new(SRC_FOLDER, cube_positions, 0.2)?));
+
+    Ok(scene_graph)
+}
+
 pub fn main() {
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -39,29 +66,16 @@ pub fn main() {
     // initialize OpenGL function pointers
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let mut scene_graph = SceneGraph::new(SCREEN_WIDTH, SCREEN_HEIGHT);
-    scene_graph.update_camera(
-        Point3::new(0.0, 0.0, 3.0),
-        Point3::new(0.0, 0.0, -1000.0),
-        Vector3::new(0.0, 1.0, 0.0),
-    );
-
-    let cube_positions: Vec<Vector3<f32>> = vec![
-        Vector3::new( 0.0,  0.0,  0.0),
-        Vector3::new( 2.0,  5.0, -15.0),
-        Vector3::new(-1.5, -2.2, -2.5),
-        Vector3::new(-3.8, -2.0, -12.3),
-        Vector3::new( 2.4, -0.4, -3.5),
-        Vector3::new(-1.7,  3.0, -7.5),
-        Vector3::new( 1.3, -2.0, -2.5),
-        Vector3::new( 1.5,  2.0, -2.5),
-        Vector3::new( 1.5,  0.2, -1.5),
-        Vector3::new(-1.3,  1.0, -1.5),
-    ];
-    scene_graph.add_element(Box::new(CubeCloud::new(SRC_FOLDER, cube_positions, 0.2)));
+    let scene_graph = match build_scene_graph() {
+        Ok(scene_graph) => scene_graph,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
+    };
 
     let mut t = 0;
-    let scene_graph = scene_graph;
+    let mut scene_graph = scene_graph;
 
     while !window.should_close() {
         process_events(&mut window, &events);
@@ -71,6 +85,7 @@ pub fn main() {
         // counter
         t += 1;
 
+        scene_graph.reload_shaders();
         scene_graph.render_frame(t);
 
         window.swap_buffers();
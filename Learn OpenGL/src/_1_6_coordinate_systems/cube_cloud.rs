@@ -3,34 +3,44 @@ extern crate glfw;
 extern crate gl;
 use self::gl::types::*;
 
-extern crate image;
-use image::GenericImage;
-
 use std::ptr;
 use std::mem;
 use std::os::raw::c_void;
 use std::ffi::CStr;
-use std::path::Path;
 
 use cgmath::{Deg, Matrix4, Vector3};
 
-use common::shader::Shader;
+use common::load_error::LoadError;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::texture::{Filter, Texture, TextureOptions};
+use common::uniform::TextureUnit;
 use super::scene_element::SceneElement;
 
 pub struct CubeCloud {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
-    box_texture: GLuint,
-    smiley_texture: GLuint,
+    box_texture: Texture,
+    smiley_texture: Texture,
     cube_model_matrices: Vec<Matrix4<f32>>,
+    mix_ratio: f32,
 }
 
 impl CubeCloud {
-    pub fn new(src_folder: &str, cube_positions: Vec<Vector3<f32>>, mix_ratio: f32) -> Self {
+    pub fn new(src_folder: &str, cube_positions: Vec<Vector3<f32>>, mix_ratio: f32) -> Result<Self, LoadError> {
+
+        // the box is sampled without filtering, so its texels stay sharp up close
+        let box_texture = Texture::load(
+            &format!("{}/texture_container.jpg", src_folder),
+            &TextureOptions::new().flip_y(false).filter(Filter::Nearest, Filter::Nearest).mipmaps(false),
+        )?;
+        let smiley_texture = Texture::load(
+            &format!("{}/texture_awesome.png", src_folder),
+            &TextureOptions::new().mipmaps(false),
+        )?;
 
-        let (shader_program, vao, vbo, box_texture, smiley_texture) =
-            CubeCloud::init_opengl(src_folder, mix_ratio);
+        let (shader_program, vao, vbo) = CubeCloud::init_opengl(src_folder)?;
 
         let rotation_axis: Vector3<f32> = Vector3::new(1.0, 0.3, 0.5);
         let model_matrices =
@@ -46,25 +56,24 @@ impl CubeCloud {
             })
             .collect();
 
-        CubeCloud {
+        Ok(CubeCloud {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             box_texture: box_texture,
             smiley_texture: smiley_texture,
             cube_model_matrices: model_matrices,
-        }
+            mix_ratio: mix_ratio,
+        })
     }
 
-    fn init_opengl(src_folder: &str, mix_ratio: f32) -> (Shader, GLuint, GLuint, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
-        let box_texture_path = &format!("{}/texture_container.jpg", src_folder);
-        let smiley_texture_path = &format!("{}/texture_awesome.png", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 180] = [
                 // positions       // texture coordinates
@@ -135,56 +144,11 @@ impl CubeCloud {
 
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
 
-            let box_texture = CubeCloud::init_texture(box_texture_path, gl::NEAREST, false, gl::RGB);
-            let smiley_texture = CubeCloud::init_texture(smiley_texture_path, gl::LINEAR, true, gl::RGBA);
-
-            shader_program.use_program();
-            shader_program.set_int(c_str!("texture1"), 0);
-            shader_program.set_int(c_str!("texture2"), 1);
-
-            shader_program.set_float(c_str!("mixRatio"), mix_ratio);
-
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo, box_texture, smiley_texture)
+            Ok((shader_program, vao, vbo))
         }
     }
-
-    fn init_texture(texture_path: &str, texture_filter: GLenum, flipv: bool, rgb_format: GLuint) -> GLuint {
-        let mut texture = 0;
-        unsafe {
-            gl::GenTextures(1, &mut texture);
-            gl::BindTexture(gl::TEXTURE_2D, texture);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, texture_filter as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, texture_filter as i32);
-            let img = image::open(&Path::new(texture_path))
-                        .expect("Failed to read texture from file");
-
-            // TODO: take a file type enum and match accordingly
-
-            // needed for png
-            let img = if flipv {
-                img.flipv() // flip loaded texture on the y-axis.
-            } else {
-                img
-            };
-            let data = img.raw_pixels();
-            gl::TexImage2D(gl::TEXTURE_2D,
-                           0,
-                           gl::RGB as i32,
-                           img.width() as i32,
-                           img.height() as i32,
-                           0,
-                           rgb_format,
-                           gl::UNSIGNED_BYTE,
-                           &data[0] as *const u8 as *const c_void);
-            gl::GenerateMipmap(gl::TEXTURE_2D);
-        }
-
-        texture
-    }
 }
 
 impl SceneElement for CubeCloud {
@@ -196,11 +160,13 @@ impl SceneElement for CubeCloud {
             self.shader_program.use_program();
             self.shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
             self.shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
+            // set every frame, since a reloaded program starts over with default uniforms
+            self.shader_program.set_int(c_str!("texture1"), 0);
+            self.shader_program.set_int(c_str!("texture2"), 1);
+            self.shader_program.set_float(c_str!("mixRatio"), self.mix_ratio);
 
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.box_texture);
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, self.smiley_texture);
+            self.box_texture.bind(TextureUnit(0));
+            self.smiley_texture.bind(TextureUnit(1));
             gl::BindVertexArray(self.vao);
 
             for (index, &model_matrix) in self.cube_model_matrices.iter().enumerate() {
@@ -215,6 +181,10 @@ impl SceneElement for CubeCloud {
             }
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for CubeCloud {
@@ -2,4 +2,7 @@ use cgmath::Matrix4;
 
 pub trait SceneElement {
     fn render_frame(&self, t: u32, view_matrix: &Matrix4<f32>, projection_matrix: &Matrix4<f32>);
+
+    /// Rebuild any shader programs whose source files have changed. Only called between frames.
+    fn reload_shaders(&mut self) {}
 }
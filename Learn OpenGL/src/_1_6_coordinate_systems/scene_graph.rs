@@ -33,6 +33,13 @@ impl SceneGraph {
         self.elements.push(element);
     }
 
+    /// Pick up edits to the elements' shader source files; call between frames
+    pub fn reload_shaders(&mut self) {
+        for element in self.elements.iter_mut() {
+            element.reload_shaders();
+        }
+    }
+
     pub fn render_frame(&self, t: u32) {
 
         unsafe {
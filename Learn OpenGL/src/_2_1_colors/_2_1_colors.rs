@@ -7,6 +7,8 @@ use cgmath::{Point3, Vector3};
 
 use std::sync::mpsc::Receiver;
 
+use common::shader::ShaderError;
+
 use super::scene_graph::SceneGraph;
 use super::container_cube::ContainerCube;
 use super::lamp_cube::LampCube;
@@ -17,6 +19,30 @@ const WINDOW_NAME: &'static str = "Learn OpenGL Lesson 2.1 Colors";
 const SCREEN_WIDTH: u32 = 1024;
 const SCREEN_HEIGHT: u32 = 768;
 
+fn build_scene_graph() -> Result<SceneGraph, ShaderError> {
+    let mut scene_graph = SceneGraph::new(SCREEN_WIDTH, SCREEN_HEIGHT);
+    scene_graph.update_camera(
+        Point3::new(0.0, 0.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+    );
+
+    let light_color = Vector3::new(1.0, 1.0, 1.0);
+
+    let cube_positions: Vec<Vector3<f32>> = vec![
+        Vector3::new(-0.8,  -0.5, -0.5),
+    ];
+    let object_color = Vector3::new(1.0, 0.5, 0.31);
+    scene_graph.add_element(Box::new(
+        ContainerCube::new(SRC_FOLDER, cube_positions, object_color, light_color)?
+    ));
+
+    let lamp_position: Vector3<f32> = Vector3::new(1.2, 1.0, -2.0);
+    scene_graph.add_element(Box::new(LampCube::new(SRC_FOLDER, lamp_position)?));
+
+    Ok(scene_graph)
+}
+
 pub fn main() {
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -40,28 +66,16 @@ pub fn main() {
     // initialize OpenGL function pointers
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-    let mut scene_graph = SceneGraph::new(SCREEN_WIDTH, SCREEN_HEIGHT);
-    scene_graph.update_camera(
-        Point3::new(0.0, 0.0, 3.0),
-        Point3::new(0.0, 0.0, 0.0),
-        Vector3::new(0.0, 1.0, 0.0),
-    );
-
-    let light_color = Vector3::new(1.0, 1.0, 1.0);
-
-    let cube_positions: Vec<Vector3<f32>> = vec![
-        Vector3::new(-0.8,  -0.5, -0.5),
-    ];
-    let object_color = Vector3::new(1.0, 0.5, 0.31);
-    scene_graph.add_element(Box::new(
-        ContainerCube::new(SRC_FOLDER, cube_positions, object_color, light_color)
-    ));
-
-    let lamp_position: Vector3<f32> = Vector3::new(1.2, 1.0, -2.0);
-    scene_graph.add_element(Box::new(LampCube::new(SRC_FOLDER, lamp_position)));
+    let scene_graph = match build_scene_graph() {
+        Ok(scene_graph) => scene_graph,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
+    };
 
     let mut t = 0;
-    let scene_graph = scene_graph;
+    let mut scene_graph = scene_graph;
 
     while !window.should_close() {
         process_events(&mut window, &events);
@@ -70,6 +84,7 @@ pub fn main() {
         // counter
         t += 1;
 
+        scene_graph.reload_shaders();
         scene_graph.render_frame(t);
 
         window.swap_buffers();
@@ -10,40 +10,41 @@ use std::ffi::CStr;
 
 use cgmath::{Matrix4, Vector3};
 
-use common::shader::Shader;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
 use super::scene_element::SceneElement;
 
 pub struct LampCube {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
     model_matrix: Matrix4<f32>,
 }
 
 impl LampCube {
-    pub fn new(src_folder: &str, lamp_position: Vector3<f32>) -> Self {
+    pub fn new(src_folder: &str, lamp_position: Vector3<f32>) -> Result<Self, ShaderError> {
 
         let (shader_program, vao, vbo) =
-            LampCube::init_opengl(src_folder);
+            LampCube::init_opengl(src_folder)?;
 
         let model_matrix =
             Matrix4::from_translation(lamp_position) * Matrix4::from_scale(0.2);
 
-        LampCube {
+        Ok(LampCube {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             model_matrix: model_matrix,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader_lamp.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 180] = [
                 // positions       // texture coordinates
@@ -111,7 +112,7 @@ impl LampCube {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -132,6 +133,10 @@ impl SceneElement for LampCube {
             gl::DrawArrays(gl::TRIANGLES, 0, 36);
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for LampCube {
@@ -11,12 +11,13 @@ use std::ffi::CStr;
 use cgmath::prelude::*;
 use cgmath::{Matrix3, Matrix4, Vector3};
 
-use common::shader::Shader;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
 use super::scene_element::SceneElement;
 use super::light::Light;
 
 pub struct ContainerCube {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
     model_matrices: Vec<Matrix4<f32>>,
@@ -28,10 +29,10 @@ impl ContainerCube {
         src_folder: &str,
         cube_positions: Vec<Vector3<f32>>,
         color: Vector3<f32>,
-    ) -> Self {
+    ) -> Result<Self, ShaderError> {
 
         let (shader_program, vao, vbo) =
-            ContainerCube::init_opengl(src_folder);
+            ContainerCube::init_opengl(src_folder)?;
 
         let model_matrices =
             cube_positions
@@ -39,22 +40,22 @@ impl ContainerCube {
             .map(|position| Matrix4::from_translation(*position))
             .collect();
 
-        ContainerCube {
+        Ok(ContainerCube {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             model_matrices: model_matrices,
             color: color,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 216] = [
                 // positions       // surface normals
@@ -127,7 +128,7 @@ impl ContainerCube {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -179,6 +180,10 @@ impl SceneElement for ContainerCube {
             }
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for ContainerCube {
@@ -10,36 +10,37 @@ use std::ffi::CStr;
 
 use cgmath::Matrix4;
 
-use common::shader::Shader;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
 use super::scene_element::SceneElement;
 use super::light::Light;
 
 pub struct LampCube {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
 }
 
 impl LampCube {
-    pub fn new(src_folder: &str) -> Self {
+    pub fn new(src_folder: &str) -> Result<Self, ShaderError> {
 
         let (shader_program, vao, vbo) =
-            LampCube::init_opengl(src_folder);
+            LampCube::init_opengl(src_folder)?;
 
-        LampCube {
+        Ok(LampCube {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader_lamp.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 180] = [
                 // positions       // texture coordinates
@@ -107,7 +108,7 @@ impl LampCube {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -135,6 +136,10 @@ impl SceneElement for LampCube {
             gl::DrawArrays(gl::TRIANGLES, 0, 36);
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for LampCube {
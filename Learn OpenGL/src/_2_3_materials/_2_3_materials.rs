@@ -6,7 +6,9 @@ extern crate gl;
 use cgmath::{Point3, Vector3};
 
 use common::camera::{Camera, Pitch, Yaw};
+use common::camera_controller::FpsController;
 use common::input::*;
+use common::shader::ShaderError;
 
 use super::coordinate_axes::CoordinateAxes;
 use super::scene_graph::SceneGraph;
@@ -21,6 +23,36 @@ const WINDOW_NAME: &'static str = "Learn OpenGL Lesson 2.3 Materials";
 const SCREEN_WIDTH: u32 = 1024;
 const SCREEN_HEIGHT: u32 = 768;
 
+fn build_scene_graph(camera: Camera, light: Light) -> Result<SceneGraph, ShaderError> {
+    let mut scene_graph = SceneGraph::new(SCREEN_WIDTH, SCREEN_HEIGHT, camera, light);
+
+    scene_graph.add_element(Box::new(
+        CoordinateAxes::new(
+            SRC_FOLDER,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        )?
+    ));
+
+    let cube_positions: Vec<Vector3<f32>> = vec![
+        Vector3::new(-1.5, 0.5, 0.0),
+    ];
+    let cube_material = Material {
+        ambient_color: Vector3::new(1.0, 0.5, 0.31),
+        diffuse_color: Vector3::new(1.0, 0.5, 0.31),
+        specular_color: Vector3::new(0.5, 0.5, 0.5),
+        shininess: 32.0,
+    };
+    scene_graph.add_element(Box::new(
+        ContainerCube::new(SRC_FOLDER, cube_positions, cube_material)?
+    ));
+
+    scene_graph.add_element(Box::new(LampCube::new(SRC_FOLDER)?));
+
+    Ok(scene_graph)
+}
+
 pub fn main() {
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -29,9 +61,7 @@ pub fn main() {
     #[cfg(target_os = "macos")]
     glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
 
-    let mut first_mouse = true;
-    let mut last_x: f32 = SCREEN_WIDTH as f32 / 2.0;
-    let mut last_y: f32 = SCREEN_HEIGHT as f32 / 2.0;
+    let mut camera_controller = FpsController::new(SCREEN_WIDTH, SCREEN_HEIGHT);
 
     // timing
     let mut delta_time: f32; // time between current frame and last frame
@@ -70,31 +100,13 @@ pub fn main() {
         Yaw::new(-100.0),
         Pitch::new(0.0),
     );
-    let mut scene_graph = SceneGraph::new(SCREEN_WIDTH, SCREEN_HEIGHT, camera, light);
-
-    scene_graph.add_element(Box::new(
-        CoordinateAxes::new(
-            SRC_FOLDER,
-            Vector3::new(1.0, 0.0, 0.0),
-            Vector3::new(0.0, 1.0, 0.0),
-            Vector3::new(0.0, 0.0, 1.0),
-        )
-    ));
-
-    let cube_positions: Vec<Vector3<f32>> = vec![
-        Vector3::new(-1.5, 0.5, 0.0),
-    ];
-    let cube_material = Material {
-        ambient_color: Vector3::new(1.0, 0.5, 0.31),
-        diffuse_color: Vector3::new(1.0, 0.5, 0.31),
-        specular_color: Vector3::new(0.5, 0.5, 0.5),
-        shininess: 32.0,
+    let scene_graph = match build_scene_graph(camera, light) {
+        Ok(scene_graph) => scene_graph,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
     };
-    scene_graph.add_element(Box::new(
-        ContainerCube::new(SRC_FOLDER, cube_positions, cube_material)
-    ));
-
-    scene_graph.add_element(Box::new(LampCube::new(SRC_FOLDER)));
 
     let mut scene_graph = scene_graph;
 
@@ -103,9 +115,10 @@ pub fn main() {
         delta_time = current_frame - last_frame;
         last_frame = current_frame;
 
-        process_events(&events, &mut first_mouse, &mut last_x, &mut last_y, &mut scene_graph.camera);
-        process_input(&mut window, delta_time, &mut scene_graph.camera);
+        process_events(&events, &mut camera_controller, &mut scene_graph.camera);
+        process_input(&mut window, delta_time, &mut camera_controller, &mut scene_graph.camera);
 
+        scene_graph.reload_shaders();
         scene_graph.render_frame(current_frame);
 
         window.swap_buffers();
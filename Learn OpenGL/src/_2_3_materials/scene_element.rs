@@ -10,4 +10,7 @@ pub trait SceneElement {
         view_matrix: &Matrix4<f32>,
         projection_matrix: &Matrix4<f32>,
     );
+
+    /// Rebuild any shader programs whose source files have changed. Only called between frames.
+    fn reload_shaders(&mut self) {}
 }
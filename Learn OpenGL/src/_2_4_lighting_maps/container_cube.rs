@@ -9,17 +9,20 @@ use std::os::raw::c_void;
 use std::ffi::CStr;
 
 use cgmath::prelude::*;
-use cgmath::{Matrix3, Matrix4, Vector3};
+use cgmath::{Matrix3, Matrix4, Vector3, Vector4};
 
-use common::shader::Shader;
-use common::texture::load_texture;
+use common::load_error::LoadError;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::texture::{Texture, TextureOptions, Wrap};
+use common::uniform::TextureUnit;
 
 use super::scene_element::SceneElement;
 use super::light::Light;
 use super::material::Material;
 
 pub struct ContainerCube {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
     model_matrices: Vec<Matrix4<f32>>,
@@ -27,10 +30,7 @@ pub struct ContainerCube {
 }
 
 impl ContainerCube {
-    pub fn new(src_folder: &str, cube_positions: Vec<Vector3<f32>>) -> Self {
-
-        let (shader_program, vao, vbo) =
-            ContainerCube::init_opengl(src_folder);
+    pub fn new(src_folder: &str, cube_positions: Vec<Vector3<f32>>) -> Result<Self, LoadError> {
 
         let model_matrices =
             cube_positions
@@ -44,28 +44,41 @@ impl ContainerCube {
         let material = Material {
             ambient_color: Vector3::new(1.0, 0.5, 0.31),
             // DEBUG
-            diffuse_map_texture_id: load_texture(&format!("{}/texture_container.png", src_folder), true),
-            specular_map_texture_id: load_texture(&format!("{}/texture_container_specular_map.png", src_folder), true),
-            emission_map_texture_id: Some(load_texture(&format!("{}/texture_emission_map.jpg", src_folder), false)),
+            diffuse_map: Texture::load(&format!("{}/texture_container.png", src_folder), &TextureOptions::new())?,
+            specular_map: Texture::load(&format!("{}/texture_container_specular_map.png", src_folder), &TextureOptions::new())?,
+            // the shader stretches the emission map over the wood inside the steel frame, and the
+            // black border keeps the frame itself from glowing
+            emission_map: Some(
+                Texture::load(
+                    &format!("{}/texture_emission_map.jpg", src_folder),
+                    &TextureOptions::new()
+                        .flip_y(false)
+                        .wrap(Wrap::ClampToBorder)
+                        .border_color(Vector4::new(0.0, 0.0, 0.0, 1.0)),
+                )?
+            ),
             shininess: 32.0,
         };
 
-        ContainerCube {
+        let (shader_program, vao, vbo) =
+            ContainerCube::init_opengl(src_folder)?;
+
+        Ok(ContainerCube {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             model_matrices: model_matrices,
             material: material,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 288] = [
                 // positions       // surface normals // texture coords
@@ -143,7 +156,7 @@ impl ContainerCube {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -200,18 +213,15 @@ impl SceneElement for ContainerCube {
                 );
 
                 // diffuse map
-                gl::ActiveTexture(gl::TEXTURE0);
-                gl::BindTexture(gl::TEXTURE_2D, self.material.diffuse_map_texture_id);
+                self.material.diffuse_map.bind(TextureUnit(0));
                 self.shader_program.set_int(c_str!("material.diffuseColor"), 0);
 
                 // specular map
-                gl::ActiveTexture(gl::TEXTURE1);
-                gl::BindTexture(gl::TEXTURE_2D, self.material.specular_map_texture_id);
+                self.material.specular_map.bind(TextureUnit(1));
                 self.shader_program.set_int(c_str!("material.specularColor"), 1);
 
-                if let Some(emission_map_id) = self.material.emission_map_texture_id {
-                    gl::ActiveTexture(gl::TEXTURE2);
-                    gl::BindTexture(gl::TEXTURE_2D, emission_map_id);
+                if let Some(ref emission_map) = self.material.emission_map {
+                    emission_map.bind(TextureUnit(2));
                     self.shader_program.set_int(c_str!("material.emissionColor"), 2);
                 }
 
@@ -219,6 +229,10 @@ impl SceneElement for ContainerCube {
             }
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for ContainerCube {
@@ -14,6 +14,9 @@ struct Light {
 	vec3 specularColor;
 };
 
+// how much of each side of a face the container's steel frame covers
+const float FRAME_WIDTH = 0.1;
+
 uniform vec3 lightPositionView;
 uniform Material material;
 uniform Light light;
@@ -43,8 +46,9 @@ void main() {
 	float specularFraction = pow(max(dot(-viewDirection, reflectionDirection), 0.0), material.shininess);
 	vec3 specularColor = light.specularColor * specularFraction * vec3(texture(material.specularColor, texCoords));
 
-	// emission
-	vec3 emissionColor = vec3(texture(material.emissionColor, texCoords));
+	// emission, only inside the frame; the map's border color is black
+	vec2 emissionCoords = (texCoords - FRAME_WIDTH) / (1.0 - 2.0 * FRAME_WIDTH);
+	vec3 emissionColor = vec3(texture(material.emissionColor, emissionCoords));
 
 	vec3 finalColor = ambientColor + diffuseColor + specularColor + emissionColor;
 	fragColor = vec4(finalColor, 1.0);
@@ -1,4 +1,4 @@
-use cgmath::{Deg, Matrix4, perspective};
+use cgmath::{Deg, Matrix4};
 
 extern crate gl;
 
@@ -10,7 +10,6 @@ use super::light::Light;
 use super::scene_element::SceneElement;
 
 pub struct SceneGraph {
-    projection_matrix: Matrix4<f32>,
     pub camera: Camera,
 
     elements: Vec<Box<SceneElement>>,
@@ -19,12 +18,10 @@ pub struct SceneGraph {
 
 impl SceneGraph {
     pub fn new(screen_width: u32, screen_height: u32, camera: Camera, light: Light) -> Self {
-        // TODO: shouldn't this update when the window's resized?
-        let projection_matrix: Matrix4<f32> =
-            perspective(Deg(45.0), screen_width as f32 / screen_height as f32, 0.1, 100.0);
+        let mut camera = camera;
+        camera.resize_viewport(screen_width, screen_height);
 
         SceneGraph {
-            projection_matrix: projection_matrix,
             camera: camera,
             elements: Vec::new(),
             light: light,
@@ -35,6 +32,13 @@ impl SceneGraph {
         self.elements.push(element);
     }
 
+    /// Pick up edits to the elements' shader source files; call between frames
+    pub fn reload_shaders(&mut self) {
+        for element in self.elements.iter_mut() {
+            element.reload_shaders();
+        }
+    }
+
     pub fn render_frame(&self, t: f32) {
 
         unsafe {
@@ -67,7 +71,7 @@ impl SceneGraph {
                 t,
                 &current_light,
                 &self.camera.view_matrix(),
-                &self.projection_matrix,
+                &self.camera.projection_matrix(),
             );
         }
     }
@@ -11,15 +11,18 @@ use std::ffi::CStr;
 use cgmath::prelude::*;
 use cgmath::{Deg, Matrix3, Matrix4, Vector3};
 
-use common::shader::Shader;
-use common::texture::load_texture;
+use common::load_error::LoadError;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::texture::{Texture, TextureOptions};
+use common::uniform::TextureUnit;
 
 use super::scene_element::SceneElement;
 use super::light::{Light, LightType};
 use super::material::Material;
 
 pub struct ContainerCube {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
     model_matrices: Vec<Matrix4<f32>>,
@@ -27,9 +30,8 @@ pub struct ContainerCube {
 }
 
 impl ContainerCube {
-    pub fn new(src_folder: &str) -> Self {
+    pub fn new(src_folder: &str) -> Result<Self, LoadError> {
 
-        let (shader_program, vao, vbo) = ContainerCube::init_opengl(src_folder);
 
         let cube_positions: Vec<Vector3<f32>> = vec![
             Vector3::new( 0.0,  0.0,  0.0),
@@ -56,28 +58,30 @@ impl ContainerCube {
 
         let material = Material {
             ambient_color: Vector3::new(1.0, 0.5, 0.31),
-            diffuse_map_texture_id: load_texture(&format!("{}/texture_container.png", src_folder), true),
-            specular_map_texture_id: load_texture(&format!("{}/texture_container_specular_map.png", src_folder), true),
-            emission_map_texture_id: None,
+            diffuse_map: Texture::load(&format!("{}/texture_container.png", src_folder), &TextureOptions::new())?,
+            specular_map: Texture::load(&format!("{}/texture_container_specular_map.png", src_folder), &TextureOptions::new())?,
+            emission_map: None,
             shininess: 32.0,
         };
 
-        ContainerCube {
+        let (shader_program, vao, vbo) = ContainerCube::init_opengl(src_folder)?;
+
+        Ok(ContainerCube {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             model_matrices: model_matrices,
             material: material,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader_spotlight.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 288] = [
                 // positions       // surface normals // texture coords
@@ -155,7 +159,7 @@ impl ContainerCube {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -235,19 +239,16 @@ impl SceneElement for ContainerCube {
                 );
 
                 // diffuse map
-                gl::ActiveTexture(gl::TEXTURE0);
-                gl::BindTexture(gl::TEXTURE_2D, self.material.diffuse_map_texture_id);
+                self.material.diffuse_map.bind(TextureUnit(0));
                 self.shader_program.set_int(c_str!("material.diffuseColor"), 0);
 
                 // specular map
-                gl::ActiveTexture(gl::TEXTURE1);
-                gl::BindTexture(gl::TEXTURE_2D, self.material.specular_map_texture_id);
+                self.material.specular_map.bind(TextureUnit(1));
                 self.shader_program.set_int(c_str!("material.specularColor"), 1);
 
-                match self.material.emission_map_texture_id {
-                    Some(emission_map_id) => {
-                        gl::ActiveTexture(gl::TEXTURE2);
-                        gl::BindTexture(gl::TEXTURE_2D, emission_map_id);
+                match self.material.emission_map {
+                    Some(ref emission_map) => {
+                        emission_map.bind(TextureUnit(2));
                         self.shader_program.set_bool(c_str!("material.emissionPresent"), true);
                         self.shader_program.set_int(c_str!("material.emissionColor"), 2);
                     },
@@ -260,6 +261,10 @@ impl SceneElement for ContainerCube {
             }
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for ContainerCube {
@@ -10,12 +10,13 @@ use std::ffi::CStr;
 
 use cgmath::{Matrix4, Vector3};
 
-use common::shader::Shader;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
 use super::scene_element::SceneElement;
 use super::light::Light;
 
 pub struct CoordinateAxes {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
 
@@ -30,28 +31,28 @@ impl CoordinateAxes {
         x_color: Vector3<f32>,
         y_color: Vector3<f32>,
         z_color: Vector3<f32>
-    ) -> Self {
+    ) -> Result<Self, ShaderError> {
 
         let (shader_program, vao, vbo) =
-            CoordinateAxes::init_opengl(src_folder);
+            CoordinateAxes::init_opengl(src_folder)?;
 
-        CoordinateAxes {
+        Ok(CoordinateAxes {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             x_color: x_color,
             y_color: y_color,
             z_color: z_color,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/coordinate_axes_vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/coordinate_axes_fragment_shader.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
 
             let vertices: [f32; 18] = [
                 -10000.0,  0.0,  0.0,
@@ -83,7 +84,7 @@ impl CoordinateAxes {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -114,6 +115,10 @@ impl SceneElement for CoordinateAxes {
             gl::DrawArrays(gl::LINES, 4, 2);
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for CoordinateAxes {
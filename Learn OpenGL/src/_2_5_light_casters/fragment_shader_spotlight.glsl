@@ -1,13 +1,10 @@
 #version 330 core
 
-struct Material {
-	vec3 ambientColor;
-	sampler2D diffuseColor;
-	sampler2D specularColor;
-	bool emissionPresent;
-	sampler2D emissionColor;
-	float shininess;
-};
+in vec3 vertexPositionView;
+in vec3 normal;
+in vec2 texCoords;
+
+#include "lighting.glsl"
 
 struct Light {
 	vec3 ambientColor;
@@ -22,10 +19,6 @@ uniform vec3 lightPositionView;
 uniform Material material;
 uniform Light light;
 
-in vec3 vertexPositionView;
-in vec3 normal;
-in vec2 texCoords;
-
 out vec4 fragColor;
 
 void main() {
@@ -6,12 +6,14 @@ extern crate gl;
 use cgmath::{Deg, Point3, Vector3, Vector4};
 
 use common::camera::{Camera, Pitch, Yaw};
+use common::camera_controller::FpsController;
 use common::input::*;
+use common::load_error::LoadError;
 
 use super::coordinate_axes::CoordinateAxes;
 use super::scene_graph::SceneGraph;
 use super::cubes::Cubes;
-use super::light::{Light, LightType};
+use super::light::{point_light_count, Light, LightType};
 
 const SRC_FOLDER: &'static str = "_2_6_multiple_lights";
 const WINDOW_NAME: &'static str = "Learn OpenGL Lesson 2.6 Multiple Lights";
@@ -19,6 +21,25 @@ const WINDOW_NAME: &'static str = "Learn OpenGL Lesson 2.6 Multiple Lights";
 const SCREEN_WIDTH: u32 = 1024;
 const SCREEN_HEIGHT: u32 = 768;
 
+fn build_scene_graph(camera: Camera, lights: Vec<Light>, point_light_count: usize) -> Result<SceneGraph, LoadError> {
+    let mut scene_graph = SceneGraph::new(
+        SCREEN_WIDTH, SCREEN_HEIGHT, SRC_FOLDER, camera, lights
+    )?;
+
+    scene_graph.add_element(Box::new(
+        CoordinateAxes::new(
+            SRC_FOLDER,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        )?
+    ));
+
+    scene_graph.add_element(Box::new(Cubes::new(SRC_FOLDER, point_light_count)?));
+
+    Ok(scene_graph)
+}
+
 pub fn main() {
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -27,9 +48,7 @@ pub fn main() {
     #[cfg(target_os = "macos")]
     glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
 
-    let mut first_mouse = true;
-    let mut last_x: f32 = SCREEN_WIDTH as f32 / 2.0;
-    let mut last_y: f32 = SCREEN_HEIGHT as f32 / 2.0;
+    let mut camera_controller = FpsController::new(SCREEN_WIDTH, SCREEN_HEIGHT);
 
     // timing
     let mut delta_time: f32; // time between current frame and last frame
@@ -126,20 +145,14 @@ pub fn main() {
         Yaw::new(-90.0),
         Pitch::new(0.0),
     );
-    let mut scene_graph = SceneGraph::new(
-        SCREEN_WIDTH, SCREEN_HEIGHT, SRC_FOLDER, camera, lights
-    );
-
-    scene_graph.add_element(Box::new(
-        CoordinateAxes::new(
-            SRC_FOLDER,
-            Vector3::new(1.0, 0.0, 0.0),
-            Vector3::new(0.0, 1.0, 0.0),
-            Vector3::new(0.0, 0.0, 1.0),
-        )
-    ));
-
-    scene_graph.add_element(Box::new(Cubes::new(SRC_FOLDER)));
+    let point_light_count = point_light_count(&lights);
+    let scene_graph = match build_scene_graph(camera, lights, point_light_count) {
+        Ok(scene_graph) => scene_graph,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
+    };
 
     let mut scene_graph = scene_graph;
 
@@ -148,9 +161,10 @@ pub fn main() {
         delta_time = current_frame - last_frame;
         last_frame = current_frame;
 
-        process_events(&events, &mut first_mouse, &mut last_x, &mut last_y, &mut scene_graph.camera);
-        process_input(&mut window, delta_time, &mut scene_graph.camera);
+        process_events(&events, &mut camera_controller, &mut scene_graph.camera);
+        process_input(&mut window, delta_time, &mut camera_controller, &mut scene_graph.camera);
 
+        scene_graph.reload_shaders();
         scene_graph.render_frame(current_frame);
 
         window.swap_buffers();
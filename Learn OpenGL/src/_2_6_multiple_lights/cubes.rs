@@ -6,20 +6,25 @@ use self::gl::types::*;
 use std::ptr;
 use std::mem;
 use std::os::raw::c_void;
-use std::ffi::{CStr, CString};
+use std::ffi::CStr;
 
 use cgmath::prelude::*;
 use cgmath::{Deg, Matrix3, Matrix4, Vector3};
 
-use common::shader::Shader;
-use common::texture::load_texture;
+use common::load_error::LoadError;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::shader::ShaderStage;
+use common::shader_preprocessor::Preprocessor;
+use common::texture::{Texture, TextureOptions};
+use common::uniform::TextureUnit;
 
 use super::scene_element::SceneElement;
 use super::light::{Light, LightType};
 use super::material::Material;
 
 pub struct Cubes {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
     model_matrices: Vec<Matrix4<f32>>,
@@ -27,9 +32,9 @@ pub struct Cubes {
 }
 
 impl Cubes {
-    pub fn new(src_folder: &str) -> Self {
+    /// `point_light_count` sizes the shader's array of point lights
+    pub fn new(src_folder: &str, point_light_count: usize) -> Result<Self, LoadError> {
 
-        let (shader_program, vao, vbo) = Cubes::init_opengl(src_folder);
 
         let cube_positions: Vec<Vector3<f32>> = vec![
             Vector3::new( 0.0,  0.0,  0.0),
@@ -56,28 +61,34 @@ impl Cubes {
 
         let material = Material {
             ambient_color: Vector3::new(1.0, 0.5, 0.31),
-            diffuse_map_texture_id: load_texture(&format!("{}/texture_crate.png", src_folder), true),
-            specular_map_texture_id: load_texture(&format!("{}/texture_crate_specular_map.png", src_folder), true),
-            emission_map_texture_id: None,
+            diffuse_map: Texture::load(&format!("{}/texture_crate.png", src_folder), &TextureOptions::new())?,
+            specular_map: Texture::load(&format!("{}/texture_crate_specular_map.png", src_folder), &TextureOptions::new())?,
+            emission_map: None,
             shininess: 32.0,
         };
 
-        Cubes {
+        let (shader_program, vao, vbo) = Cubes::init_opengl(src_folder, point_light_count)?;
+
+        Ok(Cubes {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             model_matrices: model_matrices,
             material: material,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str, point_light_count: usize) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
+        let preprocessor = Preprocessor::new().define("NUM_POINT_LIGHTS", point_light_count);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::from_files_with(
+                &[(ShaderStage::Vertex, vertex_shader_path), (ShaderStage::Fragment, fragment_shader_path)],
+                preprocessor,
+            )?;
 
             let vertices: [f32; 288] = [
                 // positions       // surface normals // texture coords
@@ -155,7 +166,7 @@ impl Cubes {
 
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 
@@ -168,28 +179,18 @@ impl Cubes {
                     linear,
                     quadratic,
                 } => {
-                    let shader_light_var = format!("pointLights[{}]", point_light_index);
-                    self.set_common_light_uniforms(light, &*shader_light_var, view_matrix);
+                    self.set_common_light_uniforms(light, "pointLights", Some(point_light_index), view_matrix);
 
                     unsafe {
-                        self.shader_program.set_float(
-                            &CString::new(format!("{}.constant", shader_light_var)).unwrap(),
-                            constant,
-                        );
-                        self.shader_program.set_float(
-                            &CString::new(format!("{}.linear", shader_light_var)).unwrap(),
-                            linear,
-                        );
-                        self.shader_program.set_float(
-                            &CString::new(format!("{}.quadratic", shader_light_var)).unwrap(),
-                            quadratic,
-                        );
+                        self.shader_program.set_element_field("pointLights", point_light_index, "constant", &constant);
+                        self.shader_program.set_element_field("pointLights", point_light_index, "linear", &linear);
+                        self.shader_program.set_element_field("pointLights", point_light_index, "quadratic", &quadratic);
                     }
 
                     point_light_index += 1;
                 },
                 LightType::Directional => {
-                    self.set_common_light_uniforms(light, "directionalLight", view_matrix);
+                    self.set_common_light_uniforms(light, "directionalLight", None, view_matrix);
                 },
                 LightType::Spotlight {
                     direction,
@@ -197,47 +198,41 @@ impl Cubes {
                     outer_angle,
                     flashlight: _,
                 } => {
-                    let shader_light_var = "spotlight";
-                    self.set_common_light_uniforms(light, shader_light_var, view_matrix);
+                    self.set_common_light_uniforms(light, "spotlight", None, view_matrix);
 
                     unsafe {
-                        self.shader_program.set_3fv(
-                            &CString::new(format!("{}.directionView", shader_light_var)).unwrap(),
-                            &(view_matrix * direction).truncate()
-                        );
-                        self.shader_program.set_float(
-                            &CString::new(format!("{}.cutOffInner", shader_light_var)).unwrap(),
-                            inner_angle.cos()
-                        );
-                        self.shader_program.set_float(
-                            &CString::new(format!("{}.cutOffOuter", shader_light_var)).unwrap(),
-                            outer_angle.cos()
-                        );
+                        self.shader_program.set_field("spotlight", "directionView", &(view_matrix * direction).truncate());
+                        self.shader_program.set_field("spotlight", "cutOffInner", &inner_angle.cos());
+                        self.shader_program.set_field("spotlight", "cutOffOuter", &outer_angle.cos());
                     }
                 },
             }
         }
     }
 
-    fn set_common_light_uniforms(&self, light: &Light, shader_var_name: &str, view_matrix: &Matrix4<f32>) {
-        let light_position_view_space = view_matrix * light.position;
+    /// Set the uniforms every kind of light has. `point_light_index` is the light's index in the
+    /// `pointLights` array, or None if `shader_var_name` is a single struct.
+    fn set_common_light_uniforms(
+        &self,
+        light: &Light,
+        shader_var_name: &str,
+        point_light_index: Option<usize>,
+        view_matrix: &Matrix4<f32>,
+    ) {
+        let light_position_view_space = (view_matrix * light.position).truncate();
+        let fields: [(&str, &Vector3<f32>); 4] = [
+            ("positionView", &light_position_view_space),
+            ("ambientColor", &light.ambient_color),
+            ("diffuseColor", &light.diffuse_color),
+            ("specularColor", &light.specular_color),
+        ];
         unsafe {
-            self.shader_program.set_3fv(
-                &CString::new(format!("{}.positionView", shader_var_name)).unwrap(),
-                &light_position_view_space.truncate(),
-            );
-            self.shader_program.set_3fv(
-                &CString::new(format!("{}.ambientColor", shader_var_name)).unwrap(),
-                &light.ambient_color
-            );
-            self.shader_program.set_3fv(
-                &CString::new(format!("{}.diffuseColor", shader_var_name)).unwrap(),
-                &light.diffuse_color
-            );
-            self.shader_program.set_3fv(
-                &CString::new(format!("{}.specularColor", shader_var_name)).unwrap(),
-                &light.specular_color
-            );
+            for &(field, value) in fields.iter() {
+                match point_light_index {
+                    Some(index) => self.shader_program.set_element_field(shader_var_name, index, field, value),
+                    None => self.shader_program.set_field(shader_var_name, field, value),
+                }
+            }
         }
     }
 }
@@ -287,19 +282,16 @@ impl SceneElement for Cubes {
                 );
 
                 // diffuse map
-                gl::ActiveTexture(gl::TEXTURE0);
-                gl::BindTexture(gl::TEXTURE_2D, self.material.diffuse_map_texture_id);
+                self.material.diffuse_map.bind(TextureUnit(0));
                 self.shader_program.set_int(c_str!("material.diffuseColor"), 0);
 
                 // specular map
-                gl::ActiveTexture(gl::TEXTURE1);
-                gl::BindTexture(gl::TEXTURE_2D, self.material.specular_map_texture_id);
+                self.material.specular_map.bind(TextureUnit(1));
                 self.shader_program.set_int(c_str!("material.specularColor"), 1);
 
-                match self.material.emission_map_texture_id {
-                    Some(emission_map_id) => {
-                        gl::ActiveTexture(gl::TEXTURE2);
-                        gl::BindTexture(gl::TEXTURE_2D, emission_map_id);
+                match self.material.emission_map {
+                    Some(ref emission_map) => {
+                        emission_map.bind(TextureUnit(2));
                         self.shader_program.set_bool(c_str!("material.emissionPresent"), true);
                         self.shader_program.set_int(c_str!("material.emissionColor"), 2);
                     },
@@ -312,6 +304,10 @@ impl SceneElement for Cubes {
             }
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for Cubes {
@@ -29,3 +29,13 @@ pub enum LightType {
         flashlight: bool,   
     },
 }
+
+/// How many of the lights are point lights, i.e. the size of the shaders' `pointLights` array
+pub fn point_light_count(lights: &[Light]) -> usize {
+    lights.iter()
+        .filter(|light| match light.light_type {
+            LightType::Point { .. } => true,
+            _ => false,
+        })
+        .count()
+}
@@ -1,10 +1,12 @@
 use cgmath::Vector3;
 
+use common::texture::Texture;
+
 #[derive(Debug)]
 pub struct Material {
     pub ambient_color: Vector3<f32>,
-    pub diffuse_map_texture_id: u32,
-    pub specular_map_texture_id: u32,
-    pub emission_map_texture_id: Option<u32>,
+    pub diffuse_map: Texture,
+    pub specular_map: Texture,
+    pub emission_map: Option<Texture>,
     pub shininess: f32,
 }
@@ -1,15 +1,13 @@
-use cgmath::{Deg, Matrix4, perspective};
-
 extern crate gl;
 
 use common::camera::Camera;
+use common::shader::ShaderError;
 
 use super::lamps::Lamps;
 use super::light::{Light, LightType};
 use super::scene_element::SceneElement;
 
 pub struct SceneGraph {
-    projection_matrix: Matrix4<f32>,
     pub camera: Camera,
 
     elements: Vec<Box<SceneElement>>,
@@ -23,23 +21,28 @@ impl SceneGraph {
         src_folder: &str,
         camera: Camera,
         lights: Vec<Light>,
-    ) -> Self {
-        // TODO: shouldn't this update when the window's resized?
-        let projection_matrix: Matrix4<f32> =
-            perspective(Deg(45.0), screen_width as f32 / screen_height as f32, 0.1, 100.0);
+    ) -> Result<Self, ShaderError> {
+        let mut camera = camera;
+        camera.resize_viewport(screen_width, screen_height);
 
-        SceneGraph {
-            projection_matrix: projection_matrix,
+        Ok(SceneGraph {
             camera: camera,
-            elements: vec![Box::new(Lamps::new(src_folder))],
+            elements: vec![Box::new(Lamps::new(src_folder)?)],
             lights: lights,
-        }
+        })
     }
 
     pub fn add_element(&mut self, element: Box<SceneElement>) {
         self.elements.push(element);
     }
 
+    /// Pick up edits to the elements' shader source files; call between frames
+    pub fn reload_shaders(&mut self) {
+        for element in self.elements.iter_mut() {
+            element.reload_shaders();
+        }
+    }
+
     pub fn render_frame(&mut self, t: f32) {
 
         self.update_flashlights();
@@ -54,7 +57,7 @@ impl SceneGraph {
                 t,
                 &self.lights,
                 &self.camera.view_matrix(),
-                &self.projection_matrix,
+                &self.camera.projection_matrix(),
             );
         }
     }
@@ -3,19 +3,21 @@ extern crate glfw;
 extern crate gl;
 use self::gl::types::*;
 
-use std::ptr;
 use std::mem;
 use std::os::raw::c_void;
 use std::ffi::CStr;
 
 use cgmath::{Matrix4, Vector3};
 
-use common::shader::Shader;
+use common::frustum::{CullingStats, Frustum};
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::vertex_layout::VertexLayout;
 use super::scene_element::SceneElement;
 use super::light::Light;
 
 pub struct CoordinateAxes {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
     vao: GLuint,
     vbo: GLuint,
 
@@ -30,28 +32,30 @@ impl CoordinateAxes {
         x_color: Vector3<f32>,
         y_color: Vector3<f32>,
         z_color: Vector3<f32>
-    ) -> Self {
+    ) -> Result<Self, ShaderError> {
 
         let (shader_program, vao, vbo) =
-            CoordinateAxes::init_opengl(src_folder);
+            CoordinateAxes::init_opengl(src_folder)?;
 
-        CoordinateAxes {
+        Ok(CoordinateAxes {
             shader_program: shader_program,
             vao: vao,
             vbo: vbo,
             x_color: x_color,
             y_color: y_color,
             z_color: z_color,
-        }
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str) -> Result<(ReloadableShader, GLuint, GLuint), ShaderError> {
 
         let vertex_shader_path = &format!("{}/coordinate_axes_vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/coordinate_axes_fragment_shader.glsl", src_folder);
 
         unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
+            let shader_program = ReloadableShader::new(vertex_shader_path, fragment_shader_path)?;
+            let layout = VertexLayout::new(&[("aPos", 0, 3)]);
+            layout.validate(&shader_program)?;
 
             let vertices: [f32; 18] = [
                 -10000.0,  0.0,  0.0,
@@ -73,16 +77,12 @@ impl CoordinateAxes {
                            &vertices[0] as *const f32 as *const c_void,
                            gl::STATIC_DRAW);
 
-            let stride = 3 * mem::size_of::<GLfloat>() as GLsizei;
-
-            // positions
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
+            layout.apply();
 
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
             gl::BindVertexArray(0);
 
-            (shader_program, vao, vbo)
+            Ok((shader_program, vao, vbo))
         }
     }
 }
@@ -93,15 +93,15 @@ impl SceneElement for CoordinateAxes {
         &self,
         _t: f32,
         _lights: &Vec<Light>,
-        view_matrix: &Matrix4<f32>,
-        projection_matrix: &Matrix4<f32>,
+        _view_matrix: &Matrix4<f32>,
+        _projection_matrix: &Matrix4<f32>,
+        _frustum: &Frustum,
+        _culling_stats: &mut CullingStats,
     ) {
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
 
             self.shader_program.use_program();
-            self.shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
-            self.shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
 
             gl::BindVertexArray(self.vao);
 
@@ -113,6 +113,10 @@ impl SceneElement for CoordinateAxes {
             gl::DrawArrays(gl::LINES, 4, 2);
         }
     }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
 }
 
 impl Drop for CoordinateAxes {
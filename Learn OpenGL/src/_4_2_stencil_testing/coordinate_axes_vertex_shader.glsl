@@ -1,7 +1,6 @@
 #version 330 core
 
-uniform mat4 viewMatrix;
-uniform mat4 projectionMatrix;
+#include "camera_block.glsl"
 
 layout (location = 0) in vec3 aPos;
 
@@ -1,37 +1,51 @@
 extern crate glfw;
 
 extern crate gl;
-use self::gl::types::*;
 
-use std::ptr;
-use std::mem;
-use std::os::raw::c_void;
-use std::ffi::{CStr, CString};
+use std::f32::{INFINITY, NEG_INFINITY};
+use std::rc::Rc;
+use std::ffi::CStr;
 
 use cgmath::prelude::*;
-use cgmath::{Deg, Matrix3, Matrix4, Vector3};
-
-use common::shader::Shader;
-use common::texture::load_texture;
+use cgmath::{Deg, Matrix3, Matrix4, Point3, Vector3};
+
+use common::asset_cache::AssetCache;
+use common::frustum::{BoundingVolume, CullingStats, Frustum};
+use common::load_error::LoadError;
+use common::mesh::Mesh;
+use common::ray::{Ray, RayHit};
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::shader_builder::ShaderBuilder;
+use common::texture::{ColorSpace, TextureOptions};
+use common::uniform::TextureUnit;
+use common::vertex_layout::VertexLayout;
 
 use super::scene_element::SceneElement;
-use super::light::{Light, LightType};
+use super::light::Light;
 use super::material::Material;
 
+/// scale applied to each cube to draw its highlight outline
+const HIGHLIGHT_SCALE: f32 = 1.1;
+
 pub struct Cubes {
-    standard_shader_program: Shader,
-    highlight_shader_program: Shader,
-    vao: GLuint,
-    vbo: GLuint,
+    standard_shader_program: ReloadableShader,
+    highlight_shader_program: ReloadableShader,
+    mesh: Rc<Mesh>,
     model_matrices: Vec<Matrix4<f32>>,
+    // world-space bounds of each cube, including its highlight
+    instance_bounds: Vec<BoundingVolume>,
+    bounds: BoundingVolume,
+    // the cube outlined with the highlight, chosen by clicking on it
+    selected_instance: Option<usize>,
     material: Material,
 }
 
 impl Cubes {
-    pub fn new(src_folder: &str) -> Self {
+    pub fn new(src_folder: &str, assets: &mut AssetCache) -> Result<Self, LoadError> {
 
-        let (standard_shader_program, highlight_shader_program, vao, vbo) =
-            Cubes::init_opengl(src_folder);
+        let (standard_shader_program, highlight_shader_program, mesh) =
+            Cubes::init_opengl(src_folder, assets)?;
 
         let cube_positions: Vec<Vector3<f32>> = vec![
             Vector3::new( 0.0,  3.0,  0.0),
@@ -53,202 +67,92 @@ impl Cubes {
             let axis = Vector3::new(1.0, 0.3, 0.5).normalize();
             model_matrix = model_matrix * Matrix4::from_axis_angle(axis, Deg(angle));
             model_matrix
-        }).collect();
+        }).collect::<Vec<Matrix4<f32>>>();
+
+        // a sphere around the unit cube's corners
+        let cube_bounds = BoundingVolume::Sphere {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 0.5 * 3.0f32.sqrt() * HIGHLIGHT_SCALE,
+        };
+        let instance_bounds: Vec<BoundingVolume> = model_matrices.iter()
+            .map(|model_matrix| cube_bounds.transformed(model_matrix))
+            .collect();
+        let bounds = Cubes::enclosing_bounds(&instance_bounds);
 
         let material = Material {
             ambient_color: Vector3::new(1.0, 0.5, 0.31),
-            diffuse_map_texture_id: load_texture(&format!("{}/texture_marble.jpg", src_folder), true),
-            specular_map_texture_id: load_texture(&format!("{}/texture_crate_specular_map.png", src_folder), true),
-            emission_map_texture_id: None,
+            diffuse_map: assets.texture(&format!("{}/texture_marble.jpg", src_folder), &TextureOptions::new().color_space(ColorSpace::Srgb))?,
+            specular_map: assets.texture(&format!("{}/texture_crate_specular_map.png", src_folder), &TextureOptions::new())?,
+            emission_map: None,
             shininess: 32.0,
         };
 
-        Cubes {
+        Ok(Cubes {
             standard_shader_program: standard_shader_program,
             highlight_shader_program: highlight_shader_program,
-            vao: vao,
-            vbo: vbo,
+            mesh: mesh,
             model_matrices: model_matrices,
+            instance_bounds: instance_bounds,
+            bounds: bounds,
+            selected_instance: None,
             material: material,
+        })
+    }
+
+    /// An axis-aligned box around all of the given bounding spheres
+    fn enclosing_bounds(instance_bounds: &[BoundingVolume]) -> BoundingVolume {
+        let mut min = Point3::new(INFINITY, INFINITY, INFINITY);
+        let mut max = Point3::new(NEG_INFINITY, NEG_INFINITY, NEG_INFINITY);
+        for bounds in instance_bounds.iter() {
+            if let BoundingVolume::Sphere { center, radius } = *bounds {
+                min = Point3::new(
+                    min.x.min(center.x - radius),
+                    min.y.min(center.y - radius),
+                    min.z.min(center.z - radius),
+                );
+                max = Point3::new(
+                    max.x.max(center.x + radius),
+                    max.y.max(center.y + radius),
+                    max.z.max(center.z + radius),
+                );
+            }
         }
+        BoundingVolume::Aabb { min: min, max: max }
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, Shader, GLuint, GLuint) {
+    fn init_opengl(
+        src_folder: &str,
+        assets: &mut AssetCache,
+    ) -> Result<(ReloadableShader, ReloadableShader, Rc<Mesh>), ShaderError> {
 
         let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
         let standard_fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
         let highlight_fragment_shader_path = &format!("{}/fragment_shader_highlight.glsl", src_folder);
 
-        unsafe {
-            let standard_shader_program = Shader::new(vertex_shader_path, standard_fragment_shader_path);
-            let highlight_shader_program = Shader::new(vertex_shader_path, highlight_fragment_shader_path);
-
-            let vertices: [f32; 288] = [
-                // positions       // surface normals // texture coords
-                -0.5, -0.5, -0.5,  0.0,  0.0, -1.0,  0.0, 0.0,
-                 0.5, -0.5, -0.5,  0.0,  0.0, -1.0,  1.0, 0.0,
-                 0.5,  0.5, -0.5,  0.0,  0.0, -1.0,  1.0, 1.0,
-                 0.5,  0.5, -0.5,  0.0,  0.0, -1.0,  1.0, 1.0,
-                -0.5,  0.5, -0.5,  0.0,  0.0, -1.0,  0.0, 1.0,
-                -0.5, -0.5, -0.5,  0.0,  0.0, -1.0,  0.0, 0.0,
-
-                -0.5, -0.5,  0.5,  0.0,  0.0, 1.0,   0.0, 0.0,
-                 0.5, -0.5,  0.5,  0.0,  0.0, 1.0,   1.0, 0.0,
-                 0.5,  0.5,  0.5,  0.0,  0.0, 1.0,   1.0, 1.0,
-                 0.5,  0.5,  0.5,  0.0,  0.0, 1.0,   1.0, 1.0,
-                -0.5,  0.5,  0.5,  0.0,  0.0, 1.0,   0.0, 1.0,
-                -0.5, -0.5,  0.5,  0.0,  0.0, 1.0,   0.0, 0.0,
-
-                -0.5,  0.5,  0.5, -1.0,  0.0,  0.0,  1.0, 0.0,
-                -0.5,  0.5, -0.5, -1.0,  0.0,  0.0,  1.0, 1.0,
-                -0.5, -0.5, -0.5, -1.0,  0.0,  0.0,  0.0, 1.0,
-                -0.5, -0.5, -0.5, -1.0,  0.0,  0.0,  0.0, 1.0,
-                -0.5, -0.5,  0.5, -1.0,  0.0,  0.0,  0.0, 0.0,
-                -0.5,  0.5,  0.5, -1.0,  0.0,  0.0,  1.0, 0.0,
-
-                 0.5,  0.5,  0.5,  1.0,  0.0,  0.0,  1.0, 0.0,
-                 0.5,  0.5, -0.5,  1.0,  0.0,  0.0,  1.0, 1.0,
-                 0.5, -0.5, -0.5,  1.0,  0.0,  0.0,  0.0, 1.0,
-                 0.5, -0.5, -0.5,  1.0,  0.0,  0.0,  0.0, 1.0,
-                 0.5, -0.5,  0.5,  1.0,  0.0,  0.0,  0.0, 0.0,
-                 0.5,  0.5,  0.5,  1.0,  0.0,  0.0,  1.0, 0.0,
-
-                -0.5, -0.5, -0.5,  0.0, -1.0,  0.0,  0.0, 1.0,
-                 0.5, -0.5, -0.5,  0.0, -1.0,  0.0,  1.0, 1.0,
-                 0.5, -0.5,  0.5,  0.0, -1.0,  0.0,  1.0, 0.0,
-                 0.5, -0.5,  0.5,  0.0, -1.0,  0.0,  1.0, 0.0,
-                -0.5, -0.5,  0.5,  0.0, -1.0,  0.0,  0.0, 0.0,
-                -0.5, -0.5, -0.5,  0.0, -1.0,  0.0,  0.0, 1.0,
-
-                -0.5,  0.5, -0.5,  0.0,  1.0,  0.0,  0.0, 1.0,
-                 0.5,  0.5, -0.5,  0.0,  1.0,  0.0,  1.0, 1.0,
-                 0.5,  0.5,  0.5,  0.0,  1.0,  0.0,  1.0, 0.0,
-                 0.5,  0.5,  0.5,  0.0,  1.0,  0.0,  1.0, 0.0,
-                -0.5,  0.5,  0.5,  0.0,  1.0,  0.0,  0.0, 0.0,
-                -0.5,  0.5, -0.5,  0.0,  1.0,  0.0,  0.0, 1.0
-            ];
-            let (mut vao, mut vbo) = (0, 0);
-            gl::GenVertexArrays(1, &mut vao);
-            gl::GenBuffers(1, &mut vbo);
-
-            gl::BindVertexArray(vao);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(gl::ARRAY_BUFFER,
-                           (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                           &vertices[0] as *const f32 as *const c_void,
-                           gl::STATIC_DRAW);
-
-            let stride = 8 * mem::size_of::<GLfloat>() as GLsizei;
-
-            // positions
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
-
-            // surface normals
-            let surface_normals_offset = (3 * mem::size_of::<GLfloat>()) as *const c_void;
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, surface_normals_offset);
-            gl::EnableVertexAttribArray(1);
-
-            // texture coordinates
-            let texture_coords_offset = (6 * mem::size_of::<GLfloat>()) as *const c_void;
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, stride, texture_coords_offset);
-            gl::EnableVertexAttribArray(2);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-
-            (standard_shader_program, highlight_shader_program, vao, vbo)
-        }
-    }
-
-    fn set_light_uniforms(&self, lights: &Vec<Light>, view_matrix: &Matrix4<f32>) {
-        let mut point_light_index = 0;
-        for light in lights.iter() {
-            match light.light_type {
-                LightType::Point {
-                    constant,
-                    linear,
-                    quadratic,
-                } => {
-                    let shader_light_var = format!("pointLights[{}]", point_light_index);
-                    self.set_common_light_uniforms(light, &*shader_light_var, view_matrix);
-
-                    unsafe {
-                        self.standard_shader_program.set_float(
-                            &CString::new(format!("{}.constant", shader_light_var)).unwrap(),
-                            constant,
-                        );
-                        self.standard_shader_program.set_float(
-                            &CString::new(format!("{}.linear", shader_light_var)).unwrap(),
-                            linear,
-                        );
-                        self.standard_shader_program.set_float(
-                            &CString::new(format!("{}.quadratic", shader_light_var)).unwrap(),
-                            quadratic,
-                        );
-                    }
-
-                    point_light_index += 1;
-                },
-                LightType::Directional => {
-                    self.set_common_light_uniforms(light, "directionalLight", view_matrix);
-                },
-                LightType::Spotlight {
-                    direction,
-                    inner_angle,
-                    outer_angle,
-                    flashlight: _,
-                } => {
-                    let shader_light_var = "spotlight";
-                    self.set_common_light_uniforms(light, shader_light_var, view_matrix);
-
-                    unsafe {
-                        self.standard_shader_program.set_3fv(
-                            &CString::new(format!("{}.directionView", shader_light_var)).unwrap(),
-                            &(view_matrix * direction).truncate()
-                        );
-                        self.standard_shader_program.set_float(
-                            &CString::new(format!("{}.cutOffInner", shader_light_var)).unwrap(),
-                            inner_angle.cos()
-                        );
-                        self.standard_shader_program.set_float(
-                            &CString::new(format!("{}.cutOffOuter", shader_light_var)).unwrap(),
-                            outer_angle.cos()
-                        );
-                    }
-                },
-            }
-        }
-    }
-
-    fn set_common_light_uniforms(&self, light: &Light, shader_var_name: &str, view_matrix: &Matrix4<f32>) {
-        let light_position_view_space = view_matrix * light.position;
-        unsafe {
-            self.standard_shader_program.set_3fv(
-                &CString::new(format!("{}.positionView", shader_var_name)).unwrap(),
-                &light_position_view_space.truncate(),
-            );
-            self.standard_shader_program.set_3fv(
-                &CString::new(format!("{}.ambientColor", shader_var_name)).unwrap(),
-                &light.ambient_color
-            );
-            self.standard_shader_program.set_3fv(
-                &CString::new(format!("{}.diffuseColor", shader_var_name)).unwrap(),
-                &light.diffuse_color
-            );
-            self.standard_shader_program.set_3fv(
-                &CString::new(format!("{}.specularColor", shader_var_name)).unwrap(),
-                &light.specular_color
-            );
-        }
+        // both programs link the same compiled vertex shader
+        let standard_shader_program = assets.shader(
+            ShaderBuilder::new().vertex(vertex_shader_path).fragment(standard_fragment_shader_path)
+        )?;
+        let highlight_shader_program = assets.shader(
+            ShaderBuilder::new().vertex(vertex_shader_path).fragment(highlight_fragment_shader_path)
+        )?;
+        let mesh = cube_mesh(assets);
+        mesh.validate(&standard_shader_program)?;
+        mesh.validate(&highlight_shader_program)?;
+
+        Ok((standard_shader_program, highlight_shader_program, mesh))
     }
 
     /// Executes drawing commands, applying the given transform to the model matrices. This
     /// function doesn't handle GL state like the depth buffer--that should be handled by the
     /// caller before or after invoking this method.
-    unsafe fn execute_draw_commands(&self, view_matrix: &Matrix4<f32>, model_transform: Option<Matrix4<f32>>) {
-        for unmodified_model_matrix in self.model_matrices.iter() {
+    unsafe fn execute_draw_commands(
+        &self,
+        view_matrix: &Matrix4<f32>,
+        model_matrices: &[Matrix4<f32>],
+        model_transform: Option<Matrix4<f32>>,
+    ) {
+        for unmodified_model_matrix in model_matrices.iter() {
             let model_matrix = match model_transform {
                 Some(transform) => unmodified_model_matrix * transform,
                 None => *unmodified_model_matrix,
@@ -272,19 +176,16 @@ impl Cubes {
             );
 
             // diffuse map
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.material.diffuse_map_texture_id);
+            self.material.diffuse_map.bind(TextureUnit(0));
             self.standard_shader_program.set_int(c_str!("material.diffuseColor"), 0);
 
             // specular map
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, self.material.specular_map_texture_id);
+            self.material.specular_map.bind(TextureUnit(1));
             self.standard_shader_program.set_int(c_str!("material.specularColor"), 1);
 
-            match self.material.emission_map_texture_id {
-                Some(emission_map_id) => {
-                    gl::ActiveTexture(gl::TEXTURE2);
-                    gl::BindTexture(gl::TEXTURE_2D, emission_map_id);
+            match self.material.emission_map {
+                Some(ref emission_map) => {
+                    emission_map.bind(TextureUnit(2));
                     self.standard_shader_program.set_bool(c_str!("material.emissionPresent"), true);
                     self.standard_shader_program.set_int(c_str!("material.emissionColor"), 2);
                 },
@@ -293,7 +194,7 @@ impl Cubes {
                 }
             }
 
-            gl::DrawArrays(gl::TRIANGLES, 0, 36);
+            self.mesh.draw();
         }
     }
 }
@@ -303,10 +204,24 @@ impl SceneElement for Cubes {
     fn render_frame(
         &self,
         _t: f32,
-        lights: &Vec<Light>,
+        _lights: &Vec<Light>,
         view_matrix: &Matrix4<f32>,
-        projection_matrix: &Matrix4<f32>,
+        _projection_matrix: &Matrix4<f32>,
+        frustum: &Frustum,
+        culling_stats: &mut CullingStats,
     ) {
+        // decide visibility once so the standard and highlight passes agree on which cubes to draw
+        let visible_instances: Vec<usize> = (0..self.model_matrices.len())
+            .filter(|&instance| culling_stats.record_instance(frustum.intersects(&self.instance_bounds[instance])))
+            .collect();
+        let visible_model_matrices: Vec<Matrix4<f32>> = visible_instances.iter()
+            .map(|&instance| self.model_matrices[instance])
+            .collect();
+        let selected_model_matrices: Vec<Matrix4<f32>> = visible_instances.iter()
+            .filter(|&&instance| Some(instance) == self.selected_instance)
+            .map(|&instance| self.model_matrices[instance])
+            .collect();
+
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::STENCIL_TEST);
@@ -315,19 +230,14 @@ impl SceneElement for Cubes {
             gl::StencilMask(0xFF);  // enable writing to the stencil buffer
 
             self.standard_shader_program.use_program();
-            self.standard_shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
-            self.standard_shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
 
             // material properties
             // TODO: accept these in the constructor, set them on the struct, and pass to the
             // shader in init_opengl()
             self.standard_shader_program.set_3fv(c_str!("material.ambientColor"), &self.material.ambient_color);
             self.standard_shader_program.set_float(c_str!("material.shininess"), self.material.shininess);
-            self.set_light_uniforms(lights, view_matrix);
 
-            gl::BindVertexArray(self.vao);
-
-            self.execute_draw_commands(view_matrix, None);
+            self.execute_draw_commands(view_matrix, &visible_model_matrices, None);
 
             gl::StencilFunc(gl::NOTEQUAL, 1, 0xFF);
             gl::StencilMask(0x00); // disable writing to the stencil buffer
@@ -338,21 +248,87 @@ impl SceneElement for Cubes {
             // with any fragments obscured by the ground
             gl::Enable(gl::DEPTH_TEST);
             self.highlight_shader_program.use_program();
-            self.highlight_shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
-            self.highlight_shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
-            self.execute_draw_commands(view_matrix, Some(Matrix4::from_scale(1.1)));
+            self.execute_draw_commands(view_matrix, &selected_model_matrices, Some(Matrix4::from_scale(HIGHLIGHT_SCALE)));
 
             gl::StencilMask(0xFF); // re-enable writing to the stencil buffer
             gl::Enable(gl::DEPTH_TEST);
         }
     }
-}
 
-impl Drop for Cubes {
-    fn drop(&mut self) {
-        unsafe {
-            gl::DeleteVertexArrays(1, &self.vao);
-            gl::DeleteBuffers(1, &self.vbo);
-        }
+    fn bounds(&self) -> Option<BoundingVolume> {
+        Some(self.bounds)
+    }
+
+    fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        // the unit cube, oriented by each model matrix
+        let (cube_min, cube_max) = (Point3::new(-0.5, -0.5, -0.5), Point3::new(0.5, 0.5, 0.5));
+        self.model_matrices.iter().enumerate()
+            .filter_map(|(instance, model_matrix)| {
+                ray.intersect_obb(model_matrix, cube_min, cube_max)
+                    .map(|distance| RayHit { distance: distance, instance: instance })
+            })
+            .fold(None, |nearest: Option<RayHit>, hit| match nearest {
+                Some(nearest) if nearest.distance <= hit.distance => Some(nearest),
+                _ => Some(hit),
+            })
+    }
+
+    fn select_instance(&mut self, instance: Option<usize>) {
+        self.selected_instance = instance;
+    }
+
+    fn reload_shaders(&mut self) {
+        self.standard_shader_program.reload_if_changed();
+        self.highlight_shader_program.reload_if_changed();
     }
 }
+
+/// The unit cube, with surface normals and texture coordinates, shared by the cubes and the lamps
+pub fn cube_mesh(assets: &mut AssetCache) -> Rc<Mesh> {
+    let layout = VertexLayout::new(&[("aPos", 0, 3), ("aNormal", 1, 3), ("aTexCoords", 2, 2)]);
+    let vertices: [f32; 288] = [
+        // positions       // surface normals // texture coords
+        -0.5, -0.5, -0.5,  0.0,  0.0, -1.0,  0.0, 0.0,
+         0.5, -0.5, -0.5,  0.0,  0.0, -1.0,  1.0, 0.0,
+         0.5,  0.5, -0.5,  0.0,  0.0, -1.0,  1.0, 1.0,
+         0.5,  0.5, -0.5,  0.0,  0.0, -1.0,  1.0, 1.0,
+        -0.5,  0.5, -0.5,  0.0,  0.0, -1.0,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0,  0.0, -1.0,  0.0, 0.0,
+
+        -0.5, -0.5,  0.5,  0.0,  0.0, 1.0,   0.0, 0.0,
+         0.5, -0.5,  0.5,  0.0,  0.0, 1.0,   1.0, 0.0,
+         0.5,  0.5,  0.5,  0.0,  0.0, 1.0,   1.0, 1.0,
+         0.5,  0.5,  0.5,  0.0,  0.0, 1.0,   1.0, 1.0,
+        -0.5,  0.5,  0.5,  0.0,  0.0, 1.0,   0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0,  0.0, 1.0,   0.0, 0.0,
+
+        -0.5,  0.5,  0.5, -1.0,  0.0,  0.0,  1.0, 0.0,
+        -0.5,  0.5, -0.5, -1.0,  0.0,  0.0,  1.0, 1.0,
+        -0.5, -0.5, -0.5, -1.0,  0.0,  0.0,  0.0, 1.0,
+        -0.5, -0.5, -0.5, -1.0,  0.0,  0.0,  0.0, 1.0,
+        -0.5, -0.5,  0.5, -1.0,  0.0,  0.0,  0.0, 0.0,
+        -0.5,  0.5,  0.5, -1.0,  0.0,  0.0,  1.0, 0.0,
+
+         0.5,  0.5,  0.5,  1.0,  0.0,  0.0,  1.0, 0.0,
+         0.5,  0.5, -0.5,  1.0,  0.0,  0.0,  1.0, 1.0,
+         0.5, -0.5, -0.5,  1.0,  0.0,  0.0,  0.0, 1.0,
+         0.5, -0.5, -0.5,  1.0,  0.0,  0.0,  0.0, 1.0,
+         0.5, -0.5,  0.5,  1.0,  0.0,  0.0,  0.0, 0.0,
+         0.5,  0.5,  0.5,  1.0,  0.0,  0.0,  1.0, 0.0,
+
+        -0.5, -0.5, -0.5,  0.0, -1.0,  0.0,  0.0, 1.0,
+         0.5, -0.5, -0.5,  0.0, -1.0,  0.0,  1.0, 1.0,
+         0.5, -0.5,  0.5,  0.0, -1.0,  0.0,  1.0, 0.0,
+         0.5, -0.5,  0.5,  0.0, -1.0,  0.0,  1.0, 0.0,
+        -0.5, -0.5,  0.5,  0.0, -1.0,  0.0,  0.0, 0.0,
+        -0.5, -0.5, -0.5,  0.0, -1.0,  0.0,  0.0, 1.0,
+
+        -0.5,  0.5, -0.5,  0.0,  1.0,  0.0,  0.0, 1.0,
+         0.5,  0.5, -0.5,  0.0,  1.0,  0.0,  1.0, 1.0,
+         0.5,  0.5,  0.5,  0.0,  1.0,  0.0,  1.0, 0.0,
+         0.5,  0.5,  0.5,  0.0,  1.0,  0.0,  1.0, 0.0,
+        -0.5,  0.5,  0.5,  0.0,  1.0,  0.0,  0.0, 0.0,
+        -0.5,  0.5, -0.5,  0.0,  1.0,  0.0,  0.0, 1.0
+    ];
+    assets.mesh("cube", &vertices, &layout)
+}
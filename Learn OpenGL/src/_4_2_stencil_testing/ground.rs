@@ -1,93 +1,78 @@
 extern crate glfw;
 
 extern crate gl;
-use self::gl::types::*;
 
-use std::ptr;
-use std::mem;
-use std::os::raw::c_void;
+use std::rc::Rc;
 use std::ffi::CStr;
 
-use cgmath::{Matrix4, Vector3};
-
-use common::shader::Shader;
-use common::texture::load_texture;
+use cgmath::{Matrix4, Point3, Vector3};
+
+use common::asset_cache::AssetCache;
+use common::frustum::{CullingStats, Frustum};
+use common::load_error::LoadError;
+use common::mesh::Mesh;
+use common::ray::{Ray, RayHit};
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::shader_builder::ShaderBuilder;
+use common::texture::{ColorSpace, Texture, TextureOptions, Wrap};
+use common::uniform::TextureUnit;
+use common::vertex_layout::VertexLayout;
 use super::scene_element::SceneElement;
 use super::light::Light;
 
 pub struct Ground {
-    shader_program: Shader,
-    vao: GLuint,
-    vbo: GLuint,
+    shader_program: ReloadableShader,
+    mesh: Rc<Mesh>,
     model_matrix: Matrix4<f32>,
-    texture_id: u32,
+    texture: Rc<Texture>,
 }
 
 impl Ground {
-    pub fn new(src_folder: &str) -> Self {
+    pub fn new(src_folder: &str, assets: &mut AssetCache) -> Result<Self, LoadError> {
 
-        let (shader_program, vao, vbo) =
-            Ground::init_opengl(src_folder);
+        let (shader_program, mesh) =
+            Ground::init_opengl(src_folder, assets)?;
 
         let model_matrix =
             Matrix4::from_nonuniform_scale(1_000.0, 0.0, 1_000.0) *
             Matrix4::from_translation(Vector3::new(0.0, 0.5, 0.0));
 
-        Ground {
+        Ok(Ground {
             shader_program: shader_program,
-            vao: vao,
-            vbo: vbo,
+            mesh: mesh,
             model_matrix: model_matrix,
-            texture_id: load_texture(&format!("{}/texture_metal.png", src_folder), true),
-        }
+            // mirroring every other tile breaks up the grid that repeating the same tile a thousand
+            // times makes
+            texture: assets.texture(
+                &format!("{}/texture_metal.png", src_folder),
+                &TextureOptions::new().color_space(ColorSpace::Srgb).anisotropy(8.0).wrap(Wrap::MirroredRepeat),
+            )?,
+        })
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
+    fn init_opengl(src_folder: &str, assets: &mut AssetCache) -> Result<(ReloadableShader, Rc<Mesh>), ShaderError> {
 
         let vertex_shader_path = &format!("{}/texturing_vertex_shader.glsl", src_folder);
         let fragment_shader_path = &format!("{}/texturing_fragment_shader.glsl", src_folder);
 
-        unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
-
-            let vertices: [f32; 30] = [
-                // positions         // texture coordinates
-                 5.0, -0.5,  5.0,    1_000.0, 0.0,
-                -5.0, -0.5,  5.0,    0.0, 0.0,
-                -5.0, -0.5, -5.0,    0.0, 1_000.0,
-
-                 5.0, -0.5,  5.0,    1_000.0, 0.0,
-                -5.0, -0.5, -5.0,    0.0, 1_000.0,
-                 5.0, -0.5, -5.0,    1_000.0, 1_000.0,
-            ];
-            let (mut vao, mut vbo) = (0, 0);
-            gl::GenVertexArrays(1, &mut vao);
-            gl::GenBuffers(1, &mut vbo);
-
-            gl::BindVertexArray(vao);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(gl::ARRAY_BUFFER,
-                           (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                           &vertices[0] as *const f32 as *const c_void,
-                           gl::STATIC_DRAW);
-
-            let stride = 5 * mem::size_of::<GLfloat>() as GLsizei;
+        let shader_program = assets.shader(ShaderBuilder::new().vertex(vertex_shader_path).fragment(fragment_shader_path))?;
+        let layout = VertexLayout::new(&[("aPos", 0, 3), ("aTexCoord", 1, 2)]);
 
-            // positions
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
+        let vertices: [f32; 30] = [
+            // positions         // texture coordinates
+             5.0, -0.5,  5.0,    1_000.0, 0.0,
+            -5.0, -0.5,  5.0,    0.0, 0.0,
+            -5.0, -0.5, -5.0,    0.0, 1_000.0,
 
-            // texture coordinates
-            let texture_coords_offset = (3 * mem::size_of::<GLfloat>()) as *const c_void;
-            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, texture_coords_offset);
-            gl::EnableVertexAttribArray(1);
+             5.0, -0.5,  5.0,    1_000.0, 0.0,
+            -5.0, -0.5, -5.0,    0.0, 1_000.0,
+             5.0, -0.5, -5.0,    1_000.0, 1_000.0,
+        ];
+        let mesh = assets.mesh("ground", &vertices, &layout);
+        mesh.validate(&shader_program)?;
 
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-
-            (shader_program, vao, vbo)
-        }
+        Ok((shader_program, mesh))
     }
 }
 
@@ -97,8 +82,10 @@ impl SceneElement for Ground {
         &self,
         _t: f32,
         _lights: &Vec<Light>,
-        view_matrix: &Matrix4<f32>,
-        projection_matrix: &Matrix4<f32>,
+        _view_matrix: &Matrix4<f32>,
+        _projection_matrix: &Matrix4<f32>,
+        _frustum: &Frustum,
+        _culling_stats: &mut CullingStats,
     ) {
         unsafe {
             gl::StencilMask(0x00); // disable writing to the stencil buffer
@@ -106,23 +93,23 @@ impl SceneElement for Ground {
 
             self.shader_program.use_program();
             self.shader_program.set_mat4fv(c_str!("modelMatrix"), &self.model_matrix);
-            self.shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
-            self.shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
 
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
-            gl::BindVertexArray(self.vao);
-
-            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+            self.texture.bind(TextureUnit(0));
+            self.mesh.draw();
         }
     }
-}
 
-impl Drop for Ground {
-    fn drop(&mut self) {
-        unsafe {
-            gl::DeleteVertexArrays(1, &self.vao);
-            gl::DeleteBuffers(1, &self.vbo);
-        }
+    fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        // the model matrix flattens the ground, so it can't be inverted for an oriented box test;
+        // the ground is axis-aligned anyway
+        let corner = |x: f32, z: f32| {
+            Point3::from_homogeneous(self.model_matrix * Point3::new(x, -0.5, z).to_homogeneous())
+        };
+        ray.intersect_aabb(corner(-5.0, -5.0), corner(5.0, 5.0))
+            .map(|distance| RayHit { distance: distance, instance: 0 })
+    }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
     }
 }
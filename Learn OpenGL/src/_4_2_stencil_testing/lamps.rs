@@ -1,114 +1,45 @@
 extern crate glfw;
 
 extern crate gl;
-use self::gl::types::*;
 
-use std::ptr;
-use std::mem;
-use std::os::raw::c_void;
+use std::rc::Rc;
 use std::ffi::CStr;
 
-use cgmath::Matrix4;
+use cgmath::{EuclideanSpace, Matrix4, Point3};
 
-use common::shader::Shader;
+use common::asset_cache::AssetCache;
+use common::frustum::{BoundingVolume, CullingStats, Frustum};
+use common::mesh::Mesh;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::shader_builder::ShaderBuilder;
+use super::cubes::cube_mesh;
 use super::scene_element::SceneElement;
 use super::light::{Light, LightType};
 
+/// the lamps are unit cubes scaled down to this size
+const LAMP_SCALE: f32 = 0.4;
+
 pub struct Lamps {
-    shader_program: Shader,
-    vao: GLuint,
-    vbo: GLuint,
+    shader_program: ReloadableShader,
+    mesh: Rc<Mesh>,
 }
 
 impl Lamps {
-    pub fn new(src_folder: &str) -> Self {
-
-        let (shader_program, vao, vbo) =
-            Lamps::init_opengl(src_folder);
+    pub fn new(src_folder: &str, assets: &mut AssetCache) -> Result<Self, ShaderError> {
 
-        Lamps {
-            shader_program: shader_program,
-            vao: vao,
-            vbo: vbo,
-        }
-    }
-
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint) {
-
-        let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
+        let vertex_shader_path = &format!("{}/vertex_shader_lamp.glsl", src_folder);
         let fragment_shader_path = &format!("{}/fragment_shader_lamp.glsl", src_folder);
 
-        unsafe {
-            let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
-
-            let vertices: [f32; 180] = [
-                // positions       // texture coordinates
-                -0.2, -0.2, -0.2,  0.0, 0.0,
-                 0.2, -0.2, -0.2,  1.0, 0.0,
-                 0.2,  0.2, -0.2,  1.0, 1.0,
-                 0.2,  0.2, -0.2,  1.0, 1.0,
-                -0.2,  0.2, -0.2,  0.0, 1.0,
-                -0.2, -0.2, -0.2,  0.0, 0.0,
-
-                -0.2, -0.2,  0.2,  0.0, 0.0,
-                 0.2, -0.2,  0.2,  1.0, 0.0,
-                 0.2,  0.2,  0.2,  1.0, 1.0,
-                 0.2,  0.2,  0.2,  1.0, 1.0,
-                -0.2,  0.2,  0.2,  0.0, 1.0,
-                -0.2, -0.2,  0.2,  0.0, 0.0,
-
-                -0.2,  0.2,  0.2,  1.0, 0.0,
-                -0.2,  0.2, -0.2,  1.0, 1.0,
-                -0.2, -0.2, -0.2,  0.0, 1.0,
-                -0.2, -0.2, -0.2,  0.0, 1.0,
-                -0.2, -0.2,  0.2,  0.0, 0.0,
-                -0.2,  0.2,  0.2,  1.0, 0.0,
-
-                 0.2,  0.2,  0.2,  1.0, 0.0,
-                 0.2,  0.2, -0.2,  1.0, 1.0,
-                 0.2, -0.2, -0.2,  0.0, 1.0,
-                 0.2, -0.2, -0.2,  0.0, 1.0,
-                 0.2, -0.2,  0.2,  0.0, 0.0,
-                 0.2,  0.2,  0.2,  1.0, 0.0,
-
-                -0.2, -0.2, -0.2,  0.0, 1.0,
-                 0.2, -0.2, -0.2,  1.0, 1.0,
-                 0.2, -0.2,  0.2,  1.0, 0.0,
-                 0.2, -0.2,  0.2,  1.0, 0.0,
-                -0.2, -0.2,  0.2,  0.0, 0.0,
-                -0.2, -0.2, -0.2,  0.0, 1.0,
-
-                -0.2,  0.2, -0.2,  0.0, 1.0,
-                 0.2,  0.2, -0.2,  1.0, 1.0,
-                 0.2,  0.2,  0.2,  1.0, 0.0,
-                 0.2,  0.2,  0.2,  1.0, 0.0,
-                -0.2,  0.2,  0.2,  0.0, 0.0,
-                -0.2,  0.2, -0.2,  0.0, 1.0
-            ];
-            let (mut vao, mut vbo) = (0, 0);
-            gl::GenVertexArrays(1, &mut vao);
-            gl::GenBuffers(1, &mut vbo);
-
-            gl::BindVertexArray(vao);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(gl::ARRAY_BUFFER,
-                           (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                           &vertices[0] as *const f32 as *const c_void,
-                           gl::STATIC_DRAW);
-
-            let stride = 5 * mem::size_of::<GLfloat>() as GLsizei;
-
-            // positions
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-
-            gl::BindVertexArray(0);
-
-            (shader_program, vao, vbo)
-        }
+        let shader_program = assets.shader(ShaderBuilder::new().vertex(vertex_shader_path).fragment(fragment_shader_path))?;
+        // the same cube the cubes are drawn with, scaled down to the lamps' size when drawn
+        let mesh = cube_mesh(assets);
+        mesh.validate(&shader_program)?;
+
+        Ok(Lamps {
+            shader_program: shader_program,
+            mesh: mesh,
+        })
     }
 }
 
@@ -118,17 +49,15 @@ impl SceneElement for Lamps {
         &self,
         _t: f32,
         lights: &Vec<Light>,
-        view_matrix: &Matrix4<f32>,
-        projection_matrix: &Matrix4<f32>,
+        _view_matrix: &Matrix4<f32>,
+        _projection_matrix: &Matrix4<f32>,
+        frustum: &Frustum,
+        culling_stats: &mut CullingStats,
     ) {
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
 
             self.shader_program.use_program();
-            self.shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
-            self.shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
-
-            gl::BindVertexArray(self.vao);
 
             let point_lights = lights.iter().filter(|light| {
                 match light.light_type {
@@ -137,24 +66,28 @@ impl SceneElement for Lamps {
                 }
             });
             for light in point_lights {
+                // a sphere around the lamp cube's corners
+                let lamp_bounds = BoundingVolume::Sphere {
+                    center: Point3::from_vec(light.position.truncate()),
+                    radius: 0.5 * LAMP_SCALE * 3.0f32.sqrt(),
+                };
+                if !culling_stats.record_instance(frustum.intersects(&lamp_bounds)) {
+                    continue;
+                }
+
                 self.shader_program.set_3fv(c_str!("light.ambientColor"), &light.ambient_color);
                 self.shader_program.set_3fv(c_str!("light.diffuseColor"), &light.diffuse_color);
                 self.shader_program.set_3fv(c_str!("light.specularColor"), &light.specular_color);
 
-                let model_matrix = Matrix4::from_translation(light.position.truncate());
+                let model_matrix =
+                    Matrix4::from_translation(light.position.truncate()) * Matrix4::from_scale(LAMP_SCALE);
                 self.shader_program.set_mat4fv(c_str!("modelMatrix"), &model_matrix);
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
+                self.mesh.draw();
             }
         }
     }
-}
 
-impl Drop for Lamps {
-    fn drop(&mut self) {
-        unsafe {
-            gl::DeleteVertexArrays(1, &self.vao);
-            gl::DeleteBuffers(1, &self.vbo);
-        }
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
     }
 }
-
@@ -1,4 +1,7 @@
-use cgmath::{Deg, Vector3, Vector4};
+use cgmath::prelude::*;
+use cgmath::{Deg, Matrix4, Vector3, Vector4};
+
+use common::uniform_buffer::{Std140Writer, MAX_POINT_LIGHTS};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Light {
@@ -29,3 +32,77 @@ pub enum LightType {
         flashlight: bool,   
     },
 }
+
+/// The `Lights` uniform block of `light_block.glsl`, with positions and directions in view space.
+/// Point lights past `MAX_POINT_LIGHTS` are left out, and if there are several directional lights
+/// or spotlights, the last of each is used.
+pub fn light_block_data(lights: &[Light], view_matrix: &Matrix4<f32>) -> Std140Writer {
+    let point_lights: Vec<&Light> = lights.iter()
+        .filter(|light| match light.light_type {
+            LightType::Point { .. } => true,
+            _ => false,
+        })
+        .take(MAX_POINT_LIGHTS)
+        .collect();
+    let directional_light = lights.iter()
+        .filter(|light| match light.light_type {
+            LightType::Directional => true,
+            _ => false,
+        })
+        .last();
+    let spotlight = lights.iter()
+        .filter(|light| match light.light_type {
+            LightType::Spotlight { .. } => true,
+            _ => false,
+        })
+        .last();
+
+    let mut writer = Std140Writer::new();
+    writer.write(&(point_lights.len() as i32));
+    // the whole array is always written so the buffer matches the block's size
+    for index in 0..MAX_POINT_LIGHTS {
+        let light = point_lights.get(index).map(|light| *light);
+        writer.write_struct(|writer| {
+            write_common_light_fields(writer, light, view_matrix);
+            let (constant, linear, quadratic) = match light.map(|light| light.light_type) {
+                Some(LightType::Point { constant, linear, quadratic }) => (constant, linear, quadratic),
+                _ => (0.0, 0.0, 0.0),
+            };
+            writer.write(&constant).write(&linear).write(&quadratic);
+        });
+    }
+
+    writer.write_struct(|writer| write_common_light_fields(writer, directional_light, view_matrix));
+
+    writer.write_struct(|writer| {
+        write_common_light_fields(writer, spotlight, view_matrix);
+        let (direction, inner_angle, outer_angle) = match spotlight.map(|light| light.light_type) {
+            Some(LightType::Spotlight { direction, inner_angle, outer_angle, .. }) => {
+                (direction, inner_angle, outer_angle)
+            },
+            // equal cutoffs make an empty cone, which spotlightColor skips
+            _ => (Vector4::new(0.0, 0.0, 0.0, 0.0), Deg(0.0), Deg(0.0)),
+        };
+        writer.write(&(view_matrix * direction).truncate())
+            .write(&inner_angle.cos())
+            .write(&outer_angle.cos());
+    });
+
+    writer
+}
+
+/// The position and colors that start every light struct; zeros for lights that aren't there
+fn write_common_light_fields(writer: &mut Std140Writer, light: Option<&Light>, view_matrix: &Matrix4<f32>) {
+    let zero = Vector3::new(0.0, 0.0, 0.0);
+    match light {
+        Some(light) => {
+            writer.write(&(view_matrix * light.position).truncate())
+                .write(&light.ambient_color)
+                .write(&light.diffuse_color)
+                .write(&light.specular_color);
+        },
+        None => {
+            writer.write(&zero).write(&zero).write(&zero).write(&zero);
+        },
+    }
+}
@@ -1,12 +1,18 @@
 extern crate glfw;
-use self::glfw::Context;
+use self::glfw::{Context, Key};
 
 extern crate gl;
 
 use cgmath::{Deg, Point3, Vector3, Vector4};
 
+use common::asset_cache::AssetCache;
 use common::camera::{Camera, Pitch, Yaw};
+use common::camera_controller::OrbitController;
 use common::input::*;
+use common::load_error::LoadError;
+use common::ray::Ray;
+use common::skybox::Skybox;
+use common::texture::{ColorSpace, TextureOptions};
 
 use super::coordinate_axes::CoordinateAxes;
 use super::scene_graph::SceneGraph;
@@ -20,6 +26,43 @@ const WINDOW_NAME: &'static str = "Learn OpenGL Lesson 4.2 Stencil Testing";
 const SCREEN_WIDTH: u32 = 1024;
 const SCREEN_HEIGHT: u32 = 768;
 
+fn build_scene_graph(
+    camera: Camera,
+    lights: Vec<Light>,
+    assets: &mut AssetCache,
+) -> Result<SceneGraph, LoadError> {
+    let mut scene_graph = SceneGraph::new(
+        SCREEN_WIDTH, SCREEN_HEIGHT, SRC_FOLDER, camera, lights, assets
+    )?;
+
+    scene_graph.add_element(Box::new(
+        CoordinateAxes::new(
+            SRC_FOLDER,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        )?
+    ));
+
+    scene_graph.add_element(Box::new(Ground::new(SRC_FOLDER, assets)?));
+    scene_graph.add_element(Box::new(Cubes::new(SRC_FOLDER, assets)?));
+
+    let sky = assets.cubemap_from_faces(
+        &[
+            &format!("{}/sky_right.png", SRC_FOLDER),
+            &format!("{}/sky_left.png", SRC_FOLDER),
+            &format!("{}/sky_top.png", SRC_FOLDER),
+            &format!("{}/sky_bottom.png", SRC_FOLDER),
+            &format!("{}/sky_front.png", SRC_FOLDER),
+            &format!("{}/sky_back.png", SRC_FOLDER),
+        ],
+        &TextureOptions::new().color_space(ColorSpace::Srgb),
+    )?;
+    scene_graph.set_skybox(Skybox::new(sky)?);
+
+    Ok(scene_graph)
+}
+
 pub fn main() {
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -28,10 +71,6 @@ pub fn main() {
     #[cfg(target_os = "macos")]
     glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
 
-    let mut first_mouse = true;
-    let mut last_x: f32 = SCREEN_WIDTH as f32 / 2.0;
-    let mut last_y: f32 = SCREEN_HEIGHT as f32 / 2.0;
-
     // timing
     let mut delta_time: f32; // time between current frame and last frame
     let mut last_frame: f32 = 0.0;
@@ -55,6 +94,13 @@ pub fn main() {
     // initialize OpenGL function pointers
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
+    // color maps are sampled as sRGB and lit in linear space, so convert the result back to sRGB
+    unsafe {
+        gl::Enable(gl::FRAMEBUFFER_SRGB);
+        // filter across the edges between cubemap faces
+        gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+    }
+
     let lights = vec![
         Light {
             position: Vector4::new(1.0, -1.0, 0.0, 0.0),
@@ -127,21 +173,23 @@ pub fn main() {
         Yaw::new(-90.0),
         Pitch::new(-15.0),
     );
-    let mut scene_graph = SceneGraph::new(
-        SCREEN_WIDTH, SCREEN_HEIGHT, SRC_FOLDER, camera, lights
-    );
+    let mut assets = AssetCache::new();
+    let mut scene_graph = match build_scene_graph(camera, lights, &mut assets) {
+        Ok(scene) => scene,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
+    };
 
-    scene_graph.add_element(Box::new(
-        CoordinateAxes::new(
-            SRC_FOLDER,
-            Vector3::new(1.0, 0.0, 0.0),
-            Vector3::new(0.0, 1.0, 0.0),
-            Vector3::new(0.0, 0.0, 1.0),
-        )
-    ));
+    // orbit the cube closest to the origin so it can be inspected from every side
+    let mut camera_controller = OrbitController::new(Point3::new(0.0, 3.0, 0.0), &mut scene_graph.camera);
+
+    // click a cube to outline it; click anything else to clear the outline
+    let mut click_detector = ClickDetector::new();
 
-    scene_graph.add_element(Box::new(Ground::new(SRC_FOLDER)));
-    scene_graph.add_element(Box::new(Cubes::new(SRC_FOLDER)));
+    // F3 prints how many elements and instances the last frame culled
+    let mut culling_key = KeyPressDetector::new(Key::F3);
 
     let mut scene_graph = scene_graph;
 
@@ -150,12 +198,37 @@ pub fn main() {
         delta_time = current_frame - last_frame;
         last_frame = current_frame;
 
-        process_events(&events, &mut first_mouse, &mut last_x, &mut last_y, &mut scene_graph.camera);
-        process_input(&mut window, delta_time, &mut scene_graph.camera);
-
+        process_events(&events, &mut camera_controller, &mut scene_graph.camera);
+        process_input(&mut window, delta_time, &mut camera_controller, &mut scene_graph.camera);
+
+        if let Some((cursor_x, cursor_y)) = click_detector.update(&window) {
+            let (window_width, window_height) = window.get_size();
+            let ray = Ray::from_cursor(
+                cursor_x,
+                cursor_y,
+                window_width as u32,
+                window_height as u32,
+                &scene_graph.camera.view_matrix(),
+                &scene_graph.camera.projection_matrix(),
+            );
+            let pick = ray.and_then(|ray| scene_graph.pick(&ray));
+            scene_graph.select(pick.as_ref());
+        }
+
+        if culling_key.update(&window) {
+            println!("culling: {}", scene_graph.culling_stats());
+        }
+
+        scene_graph.reload_shaders();
         scene_graph.render_frame(current_frame);
 
         window.swap_buffers();
         glfw.poll_events();
     }
+
+    // everything the scene loaded is released with it, so anything still alive has leaked
+    drop(scene_graph);
+    if cfg!(debug_assertions) && assets.live_assets() > 0 {
+        println!("{} after the scene was dropped", assets);
+    }
 }
@@ -7,5 +7,6 @@ pub mod light;
 pub mod material;
 pub mod scene_element;
 pub mod scene_graph;
+pub mod skybox;
 
 pub use self::main::main;
@@ -1,5 +1,8 @@
 use cgmath::Matrix4;
 
+use common::frustum::{BoundingVolume, CullingStats, Frustum};
+use common::ray::{Ray, RayHit};
+
 use super::light::Light;
 
 pub trait SceneElement {
@@ -9,5 +12,26 @@ pub trait SceneElement {
         lights: &Vec<Light>,
         view_matrix: &Matrix4<f32>,
         projection_matrix: &Matrix4<f32>,
+        frustum: &Frustum,
+        culling_stats: &mut CullingStats,
     );
+
+    /// World-space bounds used to skip the whole element when it's outside the view frustum.
+    /// Elements without bounds are always drawn.
+    fn bounds(&self) -> Option<BoundingVolume> {
+        None
+    }
+
+    /// The nearest point where the ray hits the element, for picking with the mouse. Elements that
+    /// can't be picked never report a hit.
+    fn intersect_ray(&self, _ray: &Ray) -> Option<RayHit> {
+        None
+    }
+
+    /// Mark one of the element's instances as selected, or clear the selection. Elements without
+    /// a selected look ignore this.
+    fn select_instance(&mut self, _instance: Option<usize>) {}
+
+    /// Rebuild any shader programs whose source files have changed. Only called between frames.
+    fn reload_shaders(&mut self) {}
 }
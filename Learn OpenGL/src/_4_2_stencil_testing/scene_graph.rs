@@ -1,19 +1,40 @@
-use cgmath::{Deg, Matrix4, perspective};
-
 extern crate gl;
 
+use common::asset_cache::AssetCache;
 use common::camera::Camera;
+use common::frustum::{CullingStats, Frustum};
+use common::ray::Ray;
+use common::skybox::Skybox;
+use common::uniform_buffer::{camera_block_data, UniformBuffer, CAMERA_BLOCK_BINDING, LIGHT_BLOCK_BINDING};
+use common::shader::ShaderError;
 
 use super::lamps::Lamps;
-use super::light::{Light, LightType};
+use super::light::{light_block_data, Light, LightType};
 use super::scene_element::SceneElement;
 
+/// The scene element under the cursor
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScenePick {
+    /// index of the element, as returned by `SceneGraph::add_element`
+    pub element: usize,
+    pub instance: usize,
+    pub distance: f32,
+}
+
 pub struct SceneGraph {
-    projection_matrix: Matrix4<f32>,
     pub camera: Camera,
 
     elements: Vec<Box<SceneElement>>,
+    // drawn after every element, behind them
+    skybox: Option<Skybox>,
     lights: Vec<Light>,
+
+    // shared by every element's shaders and refilled each frame
+    camera_block: UniformBuffer,
+    light_block: UniformBuffer,
+
+    // from the last frame drawn
+    culling_stats: CullingStats,
 }
 
 impl SceneGraph {
@@ -23,21 +44,70 @@ impl SceneGraph {
         src_folder: &str,
         camera: Camera,
         lights: Vec<Light>,
-    ) -> Self {
-        // TODO: shouldn't this update when the window's resized?
-        let projection_matrix: Matrix4<f32> =
-            perspective(Deg(45.0), screen_width as f32 / screen_height as f32, 0.1, 100.0);
+        assets: &mut AssetCache,
+    ) -> Result<Self, ShaderError> {
+        let mut camera = camera;
+        camera.resize_viewport(screen_width, screen_height);
 
-        SceneGraph {
-            projection_matrix: projection_matrix,
+        Ok(SceneGraph {
             camera: camera,
-            elements: vec![Box::new(Lamps::new(src_folder))],
+            elements: vec![Box::new(Lamps::new(src_folder, assets)?)],
+            skybox: None,
             lights: lights,
-        }
+            camera_block: UniformBuffer::new(CAMERA_BLOCK_BINDING),
+            light_block: UniformBuffer::new(LIGHT_BLOCK_BINDING),
+            culling_stats: CullingStats::default(),
+        })
     }
 
-    pub fn add_element(&mut self, element: Box<SceneElement>) {
+    /// Returns the element's index, to recognize it in picks
+    pub fn add_element(&mut self, element: Box<SceneElement>) -> usize {
         self.elements.push(element);
+        self.elements.len() - 1
+    }
+
+    /// Draw `skybox` behind the scene instead of the clear color
+    pub fn set_skybox(&mut self, skybox: Skybox) {
+        self.skybox = Some(skybox);
+    }
+
+    /// How many elements and instances the last frame drew and culled
+    pub fn culling_stats(&self) -> CullingStats {
+        self.culling_stats
+    }
+
+    /// The nearest element hit by the ray
+    pub fn pick(&self, ray: &Ray) -> Option<ScenePick> {
+        self.elements.iter().enumerate()
+            .filter_map(|(index, element)| element.intersect_ray(ray).map(|hit| ScenePick {
+                element: index,
+                instance: hit.instance,
+                distance: hit.distance,
+            }))
+            .fold(None, |nearest: Option<ScenePick>, pick| match nearest {
+                Some(nearest) if nearest.distance <= pick.distance => Some(nearest),
+                _ => Some(pick),
+            })
+    }
+
+    /// Select the picked instance and clear the selection of every other element
+    pub fn select(&mut self, pick: Option<&ScenePick>) {
+        for (index, element) in self.elements.iter_mut().enumerate() {
+            element.select_instance(match pick {
+                Some(pick) if pick.element == index => Some(pick.instance),
+                _ => None,
+            });
+        }
+    }
+
+    /// Pick up edits to the elements' shader source files; call between frames
+    pub fn reload_shaders(&mut self) {
+        for element in self.elements.iter_mut() {
+            element.reload_shaders();
+        }
+        if let Some(ref mut skybox) = self.skybox {
+            skybox.reload_shaders();
+        }
     }
 
     pub fn render_frame(&mut self, t: f32) {
@@ -49,14 +119,44 @@ impl SceneGraph {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
         }
 
+        let view_matrix = self.camera.view_matrix();
+        let projection_matrix = self.camera.projection_matrix();
+        let frustum = Frustum::from_matrix(&(projection_matrix * view_matrix));
+        self.camera_block.update(&camera_block_data(&view_matrix, &projection_matrix));
+        self.light_block.update(&light_block_data(&self.lights, &view_matrix));
+
+        let mut culling_stats = CullingStats::default();
         for element in self.elements.iter() {
+            if let Some(bounds) = element.bounds() {
+                if !frustum.intersects(&bounds) {
+                    culling_stats.elements_culled += 1;
+                    continue;
+                }
+            }
+            culling_stats.elements_drawn += 1;
+
             element.render_frame(
                 t,
                 &self.lights,
-                &self.camera.view_matrix(),
-                &self.projection_matrix,
+                &view_matrix,
+                &projection_matrix,
+                &frustum,
+                &mut culling_stats,
             );
         }
+
+        if let Some(ref skybox) = self.skybox {
+            skybox.render_frame(
+                t,
+                &self.lights,
+                &view_matrix,
+                &projection_matrix,
+                &frustum,
+                &mut culling_stats,
+            );
+        }
+
+        self.culling_stats = culling_stats;
     }
 
     /// update all flashlights to point forward from the camera
@@ -1,8 +1,8 @@
 #version 330 core
 
+#include "camera_block.glsl"
+
 uniform mat4 modelMatrix;
-uniform mat4 viewMatrix;
-uniform mat4 projectionMatrix;
 
 layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec2 aTexCoord;
@@ -1,9 +1,9 @@
 #version 330 core
 
+#include "camera_block.glsl"
+
 uniform mat4 modelMatrix;
 uniform mat3 normalMatrixView;
-uniform mat4 viewMatrix;
-uniform mat4 projectionMatrix;
 
 layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec3 aNormal;
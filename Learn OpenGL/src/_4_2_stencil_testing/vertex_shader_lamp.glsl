@@ -0,0 +1,11 @@
+#version 330 core
+
+#include "camera_block.glsl"
+
+uniform mat4 modelMatrix;
+
+layout (location = 0) in vec3 aPos;
+
+void main() {
+    gl_Position = projectionMatrix * viewMatrix * modelMatrix * vec4(aPos, 1.0);
+}
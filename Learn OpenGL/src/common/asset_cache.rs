@@ -0,0 +1,168 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::rc::{Rc, Weak};
+
+use common::assets;
+use common::mesh::Mesh;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::{CompiledStage, ShaderError, ShaderStage};
+use common::shader_builder::ShaderBuilder;
+use common::shader_preprocessor::ShaderSource;
+use common::texture::{Cubemap, Texture, TextureError, TextureOptions};
+use common::vertex_layout::VertexLayout;
+
+/// A cached asset whose type has been erased, so every kind of asset fits in one map
+trait CachedAsset {
+    /// how many handles to the asset are still around
+    fn handles(&self) -> usize;
+    fn as_any(&self) -> &Any;
+}
+
+impl<T: 'static> CachedAsset for Weak<T> {
+    fn handles(&self) -> usize {
+        Rc::strong_count(&match self.upgrade() {
+            Some(asset) => asset,
+            None => return 0,
+        }) - 1
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+struct Entry {
+    kind: &'static str,
+    name: String,
+    asset: Box<CachedAsset>,
+}
+
+/// Loads each texture, cubemap, shader stage, mesh or other asset once and hands out shared
+/// handles to it, so elements that use the same file with the same options share one GPU object.
+///
+/// Assets are told apart by their canonical path, so different relative paths to the same file
+/// match, plus a description of how they were loaded. The cache only keeps weak references: an
+/// asset is deleted as soon as its last handle is dropped, and loaded again if it's asked for
+/// after that. Its `Display` lists the assets that are still alive, to spot leaks.
+pub struct AssetCache {
+    entries: HashMap<(TypeId, String), Entry>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        AssetCache {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The asset of type `T` cached under `key`, or the result of `load` cached under it. `kind`
+    /// and `name` describe the asset in the listing.
+    pub fn get_or_load<T, E, F>(&mut self, kind: &'static str, name: &str, key: String, load: F) -> Result<Rc<T>, E>
+        where T: 'static, F: FnOnce() -> Result<T, E>
+    {
+        self.entries.retain(|_, entry| entry.asset.handles() > 0);
+
+        let id = (TypeId::of::<T>(), key);
+        if let Some(entry) = self.entries.get(&id) {
+            if let Some(asset) = entry.asset.as_any().downcast_ref::<Weak<T>>().and_then(Weak::upgrade) {
+                return Ok(asset);
+            }
+        }
+
+        let asset = Rc::new(load()?);
+        self.entries.insert(id, Entry {
+            kind: kind,
+            name: name.to_string(),
+            asset: Box::new(Rc::downgrade(&asset)),
+        });
+        Ok(asset)
+    }
+
+    pub fn texture(&mut self, path: &str, options: &TextureOptions) -> Result<Rc<Texture>, TextureError> {
+        let name = canonical_name(path);
+        let key = format!("{} {:?}", name, options);
+        self.get_or_load("texture", &name, key, || Texture::load(path, options))
+    }
+
+    pub fn cubemap_from_faces(&mut self, paths: &[&str; 6], options: &TextureOptions) -> Result<Rc<Cubemap>, TextureError> {
+        let names: Vec<String> = paths.iter().map(|path| canonical_name(path)).collect();
+        let key = format!("{:?} {:?}", names, options);
+        self.get_or_load("cubemap", &names.join(", "), key, || Cubemap::from_faces(paths, options))
+    }
+
+    pub fn cubemap_from_equirectangular(
+        &mut self,
+        path: &str,
+        face_size: u32,
+        options: &TextureOptions,
+    ) -> Result<Rc<Cubemap>, TextureError> {
+        let name = canonical_name(path);
+        let key = format!("{} {} {:?}", name, face_size, options);
+        self.get_or_load("cubemap", &name, key, || Cubemap::from_equirectangular(path, face_size, options))
+    }
+
+    /// A program built from `builder` that compiles each of its stages once: programs asking for
+    /// the same file with the same `#define`s link the same compiled stage. The program still
+    /// reloads when its files change, recompiling the changed stages on its own.
+    pub fn shader(&mut self, builder: ShaderBuilder) -> Result<ReloadableShader, ShaderError> {
+        ReloadableShader::from_builder_compiling(builder, |stage, source| self.shader_stage(stage, source))
+    }
+
+    /// The stage compiled from `source`. Stages match by path and expanded source, which holds
+    /// the `#define`s, so the same file included differently or edited in between isn't shared.
+    pub fn shader_stage(&mut self, stage: ShaderStage, source: ShaderSource) -> Result<Rc<CompiledStage>, ShaderError> {
+        let name = canonical_name(source.path());
+        let mut hasher = DefaultHasher::new();
+        source.text.hash(&mut hasher);
+        let key = format!("{} {} {:x}", stage, name, hasher.finish());
+        self.get_or_load("shader", &format!("{} ({})", name, stage), key, || CompiledStage::compile(stage, source))
+    }
+
+    /// A mesh of `vertices`, shared with every other element that asks for the same vertices with
+    /// the same layout. `name` describes it in the listing.
+    pub fn mesh(&mut self, name: &str, vertices: &[f32], layout: &VertexLayout) -> Rc<Mesh> {
+        let mut hasher = DefaultHasher::new();
+        for vertex in vertices.iter() {
+            vertex.to_bits().hash(&mut hasher);
+        }
+        let key = format!("{:?} {:x}", layout, hasher.finish());
+        match self.get_or_load("mesh", name, key, || Ok::<Mesh, ()>(Mesh::new(vertices, layout.clone()))) {
+            Ok(mesh) => mesh,
+            Err(()) => unreachable!(),
+        }
+    }
+
+    /// The number of assets that still have handles
+    pub fn live_assets(&self) -> usize {
+        self.entries.values().filter(|entry| entry.asset.handles() > 0).count()
+    }
+}
+
+impl fmt::Display for AssetCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut live: Vec<(&str, &str, usize)> = self.entries.values()
+            .map(|entry| (entry.kind, &entry.name[..], entry.asset.handles()))
+            .filter(|&(_, _, handles)| handles > 0)
+            .collect();
+        live.sort();
+
+        write!(f, "{} live assets", live.len())?;
+        for (kind, name, handles) in live {
+            write!(f, "\n  {} {} ({} handles)", kind, name, handles)?;
+        }
+        Ok(())
+    }
+}
+
+/// The full path of the file `name` resolves to, so different ways of reaching the same file
+/// match. Embedded and missing assets keep their name.
+fn canonical_name(name: &str) -> String {
+    match assets::file_path(name) {
+        Some(path) => fs::canonicalize(&path).unwrap_or(path).to_string_lossy().into_owned(),
+        None => name.replace('\\', "/"),
+    }
+}
@@ -0,0 +1,180 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use image;
+use image::DynamicImage;
+
+use common::embedded_assets;
+
+/// Folders to look for assets in before any others, separated like `PATH`
+pub const ASSET_FOLDERS_VARIABLE: &'static str = "LEARN_OPENGL_ASSETS";
+
+/// Where an asset was found
+#[derive(Debug, Clone)]
+pub enum Asset {
+    File(PathBuf),
+    /// built into the binary with the `embed-assets` feature
+    Embedded(&'static [u8]),
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    /// `name` isn't in any of the `searched` places
+    NotFound { name: String, searched: Vec<String> },
+    /// the asset was found at `path` but couldn't be read
+    Io { path: String, error: io::Error },
+    /// the asset at `path` isn't an image in a supported format
+    Image { path: String, error: image::ImageError },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssetError::NotFound { ref name, ref searched } => {
+                write!(f, "can't find asset {}, tried:", name)?;
+                for place in searched.iter() {
+                    write!(f, "\n  {}", place)?;
+                }
+                Ok(())
+            },
+            AssetError::Io { ref path, ref error } => write!(f, "failed to read {}: {}", path, error),
+            AssetError::Image { ref path, ref error } => write!(f, "failed to decode image {}: {}", path, error),
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn description(&self) -> &str {
+        match *self {
+            AssetError::NotFound { .. } => "asset not found",
+            AssetError::Io { .. } => "failed to read asset",
+            AssetError::Image { .. } => "failed to decode image asset",
+        }
+    }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            AssetError::NotFound { .. } => None,
+            AssetError::Io { ref error, .. } => Some(error),
+            AssetError::Image { ref error, .. } => Some(error),
+        }
+    }
+}
+
+/// Finds lesson assets by their path relative to the `src` folder, e.g.
+/// `heightmap/vertex_shader.glsl`, so lessons run from any working directory.
+///
+/// Each root folder is tried in order:
+///   1. the folders in `LEARN_OPENGL_ASSETS`
+///   2. the working directory, which is where lessons have always looked
+///   3. the folder the executable is in, for assets copied next to it
+///   4. the crate's `src` folder and the crate folder itself, as of the build
+///
+/// Assets embedded with the `embed-assets` feature come last, so files on disk can still be edited
+/// and hot-reloaded. Absolute paths are only looked for as given.
+#[derive(Debug, Clone)]
+pub struct AssetLocator {
+    roots: Vec<PathBuf>,
+    embedded: &'static [(&'static str, &'static [u8])],
+}
+
+impl AssetLocator {
+    pub fn new() -> Self {
+        let mut roots: Vec<PathBuf> = vec![];
+        if let Some(folders) = env::var_os(ASSET_FOLDERS_VARIABLE) {
+            roots.extend(env::split_paths(&folders).filter(|folder| !folder.as_os_str().is_empty()));
+        }
+        roots.push(PathBuf::from("."));
+        if let Some(executable_folder) = env::current_exe().ok().and_then(|path| path.parent().map(Path::to_path_buf)) {
+            roots.push(executable_folder);
+        }
+        let crate_folder = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        roots.push(crate_folder.join("src"));
+        roots.push(crate_folder);
+
+        AssetLocator {
+            roots: roots,
+            embedded: embedded_assets::ASSETS,
+        }
+    }
+
+    /// Where the asset called `name` is, or every place that was tried
+    pub fn locate(&self, name: &str) -> Result<Asset, AssetError> {
+        let mut searched = vec![];
+        let path = Path::new(name);
+        let candidates: Vec<PathBuf> = if path.is_absolute() {
+            vec![path.to_path_buf()]
+        } else {
+            self.roots.iter().map(|root| root.join(path)).collect()
+        };
+        for candidate in candidates {
+            if candidate.is_file() {
+                return Ok(Asset::File(candidate));
+            }
+            let candidate = candidate.to_string_lossy().into_owned();
+            if !searched.contains(&candidate) {
+                searched.push(candidate);
+            }
+        }
+
+        if !self.embedded.is_empty() {
+            // embedded names always use forward slashes
+            let normalized_name = name.replace('\\', "/");
+            match self.embedded.iter().find(|&&(embedded_name, _)| embedded_name == normalized_name) {
+                Some(&(_, bytes)) => return Ok(Asset::Embedded(bytes)),
+                None => searched.push(format!("{} embedded in the executable", normalized_name)),
+            }
+        }
+
+        Err(AssetError::NotFound { name: name.to_string(), searched: searched })
+    }
+
+    pub fn read(&self, name: &str) -> Result<Vec<u8>, AssetError> {
+        match self.locate(name)? {
+            Asset::File(path) => {
+                let mut bytes = vec![];
+                File::open(&path)
+                    .and_then(|mut file| file.read_to_end(&mut bytes))
+                    .map_err(|error| AssetError::Io { path: path.to_string_lossy().into_owned(), error: error })?;
+                Ok(bytes)
+            },
+            Asset::Embedded(bytes) => Ok(bytes.to_vec()),
+        }
+    }
+}
+
+/// Whether `name` can be found by `AssetLocator::new()`
+pub fn exists(name: &str) -> bool {
+    AssetLocator::new().locate(name).is_ok()
+}
+
+/// The file on disk that `name` resolves to, or None if it's embedded or missing
+pub fn file_path(name: &str) -> Option<PathBuf> {
+    match AssetLocator::new().locate(name) {
+        Ok(Asset::File(path)) => Some(path),
+        _ => None,
+    }
+}
+
+pub fn read(name: &str) -> Result<Vec<u8>, AssetError> {
+    AssetLocator::new().read(name)
+}
+
+pub fn read_to_string(name: &str) -> Result<String, AssetError> {
+    let bytes = read(name)?;
+    String::from_utf8(bytes).map_err(|error| AssetError::Io {
+        path: name.to_string(),
+        error: io::Error::new(io::ErrorKind::InvalidData, error),
+    })
+}
+
+/// Load an image asset, with the format worked out from its contents
+pub fn open_image(name: &str) -> Result<DynamicImage, AssetError> {
+    let bytes = read(name)?;
+    image::load_from_memory(&bytes).map_err(|error| AssetError::Image { path: name.to_string(), error: error })
+}
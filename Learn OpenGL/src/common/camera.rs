@@ -1,10 +1,33 @@
-use std::f32::consts::PI;
+use cgmath::{Deg, InnerSpace, Matrix3, Matrix4, Point3, Quaternion, Rotation3, Vector3, Zero};
+use cgmath::{ortho, perspective};
 
-use cgmath::{InnerSpace, Matrix4, Point3, Vector3, Zero};
-
-pub enum CameraMovement { Forward, Backward, TurnLeft, TurnRight, StrafeLeft, StrafeRight }
+#[derive(Debug, Copy, Clone)]
+pub enum CameraMovement {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+    StrafeLeft,
+    StrafeRight,
+    Up,
+    Down,
+    RollLeft,
+    RollRight,
+}
 use self::CameraMovement::*;
 
+/// How the camera turns in response to input.
+///
+/// `Fps` keeps the camera upright: orientation is built from Euler yaw and pitch, pitch is clamped
+/// to +/-89 degrees, and rolling is ignored. `FreeFlight` stores the orientation as a quaternion
+/// and applies every rotation around the camera's own axes, so it can pitch through vertical and
+/// roll without gimbal lock.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CameraMode {
+    Fps,
+    FreeFlight,
+}
+
 #[derive(Debug)]
 pub struct Pitch(f32);
 impl Pitch {
@@ -44,6 +67,33 @@ impl Default for Speed {
     }
 }
 
+/// how quickly velocity builds up towards full speed while a movement key is held, per second
+#[derive(Debug)]
+pub struct Acceleration(f32);
+impl Default for Acceleration {
+    fn default() -> Self {
+        Acceleration(10.0)
+    }
+}
+
+/// how quickly velocity dies away once movement keys are released, per second
+#[derive(Debug)]
+pub struct Damping(f32);
+impl Default for Damping {
+    fn default() -> Self {
+        Damping(8.0)
+    }
+}
+
+/// full turning speed in degrees per second
+#[derive(Debug)]
+pub struct TurnSpeed(f32);
+impl Default for TurnSpeed {
+    fn default() -> Self {
+        TurnSpeed(60.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct MouseSensitivity(f32);
 impl Default for MouseSensitivity {
@@ -60,6 +110,33 @@ impl Default for Zoom {
     }
 }
 
+#[derive(Debug)]
+pub struct ClipPlanes(f32, f32);
+impl ClipPlanes {
+    pub fn new(near: f32, far: f32) -> Self {
+        ClipPlanes(near, far)
+    }
+}
+impl Default for ClipPlanes {
+    fn default() -> Self {
+        ClipPlanes(0.1, 100.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// the vertical field of view is `Camera::zoom` degrees
+    Perspective,
+    /// `height` is the world-space height of the view volume at the default zoom; zooming in
+    /// shrinks it proportionally
+    Orthographic { height: f32 },
+}
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective
+    }
+}
+
 pub struct Camera {
     pub position: Point3<f32>,
     pub front: Vector3<f32>,
@@ -70,8 +147,31 @@ pub struct Camera {
     pub pitch: f32,
 
     pub movement_speed: f32,
+    pub acceleration: f32,
+    pub damping: f32,
+    pub turn_speed: f32,
     pub mouse_sensitivity: f32,
     pub zoom: f32,
+
+    pub projection: Projection,
+    pub near_plane: f32,
+    pub far_plane: f32,
+    /// width / height of the viewport, kept up to date by `resize_viewport`
+    pub aspect_ratio: f32,
+
+    mode: CameraMode,
+    // only authoritative in free-flight mode; rotates the camera's local axes (right = +x,
+    // up = +y, front = -z) into world space
+    orientation: Quaternion<f32>,
+
+    // world units per second
+    velocity: Vector3<f32>,
+    // yaw, pitch and roll in degrees per second
+    angular_velocity: Vector3<f32>,
+    // requested by process_keyboard since the last update_motion, in the same terms as the
+    // velocities
+    movement_input: Vector3<f32>,
+    turn_input: Vector3<f32>,
 }
 
 impl Camera {
@@ -81,12 +181,25 @@ impl Camera {
             right: Vector3::zero(),
             up: Vector3::zero(),
             movement_speed: Speed::default().0,
+            acceleration: Acceleration::default().0,
+            damping: Damping::default().0,
+            turn_speed: TurnSpeed::default().0,
             mouse_sensitivity: MouseSensitivity::default().0,
             zoom: Zoom::default().0,
+            projection: Projection::default(),
+            near_plane: ClipPlanes::default().0,
+            far_plane: ClipPlanes::default().1,
+            aspect_ratio: 4.0 / 3.0,
             position: position,
             world_up: up,
             yaw: yaw.0,
             pitch: pitch.0,
+            mode: CameraMode::Fps,
+            orientation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            velocity: Vector3::zero(),
+            angular_velocity: Vector3::zero(),
+            movement_input: Vector3::zero(),
+            turn_input: Vector3::zero(),
         };
 
         camera.update_camera_vectors();
@@ -97,28 +210,173 @@ impl Camera {
         Matrix4::look_at(self.position, self.position + self.front, self.up)
     }
 
-    pub fn process_keyboard(&mut self, direction: CameraMovement, delta_time: f32) {
-        let distance_moved = self.movement_speed * delta_time;
-        match direction {
-            Forward => self.position += self.front * distance_moved,
-            Backward => self.position += -(self.front * distance_moved),
-            TurnRight => {
-                self.yaw += PI * self.movement_speed * distance_moved;
-                self.update_camera_vectors();
+    pub fn projection_matrix(&self) -> Matrix4<f32> {
+        match self.projection {
+            Projection::Perspective => {
+                perspective(Deg(self.zoom), self.aspect_ratio, self.near_plane, self.far_plane)
+            },
+            Projection::Orthographic { height } => {
+                let half_height = 0.5 * height * self.zoom / Zoom::default().0;
+                let half_width = half_height * self.aspect_ratio;
+                ortho(-half_width, half_width, -half_height, half_height, self.near_plane, self.far_plane)
+            },
+        }
+    }
+
+    pub fn set_clip_planes(&mut self, clip_planes: ClipPlanes) {
+        self.near_plane = clip_planes.0;
+        self.far_plane = clip_planes.1;
+    }
+
+    /// Match the projection's aspect ratio to a new framebuffer size
+    pub fn resize_viewport(&mut self, width: u32, height: u32) {
+        // a minimized window reports a zero-sized framebuffer; keep the last usable aspect ratio
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.aspect_ratio = width as f32 / height as f32;
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    /// Switch between FPS and free-flight control, keeping the current view direction. Going back
+    /// to FPS mode levels out any roll and clamps pitch.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+
+        self.mode = mode;
+        match mode {
+            CameraMode::FreeFlight => {
+                self.orientation =
+                    Quaternion::from(Matrix3::from_cols(self.right, self.up, -self.front)).normalize();
+            },
+            CameraMode::Fps => {
+                self.update_euler_angles();
+                self.pitch = self.pitch.max(-89.0).min(89.0);
+            },
+        }
+        self.update_camera_vectors();
+    }
+
+    /// Turn the camera to face the given point without moving it. Like switching to FPS mode,
+    /// this levels out any roll.
+    pub fn look_at(&mut self, target: Point3<f32>) {
+        let direction = target - self.position;
+        if direction.magnitude2() == 0.0 {
+            return;
+        }
+
+        self.front = direction.normalize();
+        match self.mode {
+            CameraMode::FreeFlight => {
+                self.right = self.front.cross(self.world_up).normalize();
+                self.up = self.right.cross(self.front).normalize();
+                self.orientation =
+                    Quaternion::from(Matrix3::from_cols(self.right, self.up, -self.front)).normalize();
+            },
+            CameraMode::Fps => {
+                self.update_euler_angles();
+                self.pitch = self.pitch.max(-89.0).min(89.0);
+            },
+        }
+        self.update_camera_vectors();
+    }
+
+    /// Point the camera along the given yaw and pitch, in degrees. Like `look_at`, this levels out
+    /// any roll.
+    pub fn set_euler_angles(&mut self, yaw: f32, pitch: f32) {
+        match self.mode {
+            CameraMode::FreeFlight => {
+                let front = Vector3::new(
+                    yaw.to_radians().cos() * pitch.to_radians().cos(),
+                    pitch.to_radians().sin(),
+                    yaw.to_radians().sin() * pitch.to_radians().cos(),
+                );
+                let target = self.position + front;
+                self.look_at(target);
             },
-            TurnLeft => {
-                self.yaw -= PI * self.movement_speed * distance_moved;
+            CameraMode::Fps => {
+                self.yaw = yaw;
+                self.pitch = pitch.max(-89.0).min(89.0);
                 self.update_camera_vectors();
             },
-            StrafeRight => self.position += self.right * distance_moved,
-            StrafeLeft => self.position += -(self.right * distance_moved),
         }
     }
 
+    /// Ask for movement this frame; call once per held key, then `update_motion` once to move
+    /// the camera
+    pub fn process_keyboard(&mut self, direction: CameraMovement) {
+        match direction {
+            Forward => self.movement_input += self.front,
+            Backward => self.movement_input -= self.front,
+            TurnRight => self.turn_input.x += 1.0,
+            TurnLeft => self.turn_input.x -= 1.0,
+            StrafeRight => self.movement_input += self.right,
+            StrafeLeft => self.movement_input -= self.right,
+            Up => self.movement_input += self.up,
+            Down => self.movement_input -= self.up,
+            RollRight => self.turn_input.z += 1.0,
+            RollLeft => self.turn_input.z -= 1.0,
+        }
+    }
+
+    /// Advance the camera's velocity and angular velocity by `delta_time` and move it
+    /// accordingly. While keys are held the velocities accelerate towards full speed in the
+    /// requested directions, and once they're released damping brings them back to rest.
+    ///
+    /// The velocities follow an exponential curve that's integrated exactly, so the camera takes
+    /// the same path at any frame rate as long as input doesn't change.
+    pub fn update_motion(&mut self, delta_time: f32) {
+        // moving diagonally shouldn't be faster than moving straight
+        let movement_direction = if self.movement_input.magnitude2() > 1.0 {
+            self.movement_input.normalize()
+        } else {
+            self.movement_input
+        };
+        let (velocity, displacement) = approach(
+            self.velocity,
+            movement_direction * self.movement_speed,
+            if self.movement_input.is_zero() { self.damping } else { self.acceleration },
+            delta_time,
+        );
+        self.velocity = velocity;
+        self.position += displacement;
+
+        let (angular_velocity, rotation) = approach(
+            self.angular_velocity,
+            self.turn_input * self.turn_speed,
+            if self.turn_input.is_zero() { self.damping } else { self.acceleration },
+            delta_time,
+        );
+        self.angular_velocity = angular_velocity;
+        if !rotation.is_zero() {
+            self.rotate(rotation.x, rotation.y, rotation.z);
+        }
+
+        self.movement_input = Vector3::zero();
+        self.turn_input = Vector3::zero();
+    }
+
+    /// Bring the camera to rest immediately, e.g. before moving it along a recorded path
+    pub fn stop(&mut self) {
+        self.velocity = Vector3::zero();
+        self.angular_velocity = Vector3::zero();
+    }
+
     pub fn process_mouse_movement(&mut self, mut x_offset: f32, mut y_offset: f32, constrain_pitch: bool) {
         x_offset *= self.mouse_sensitivity;
         y_offset *= self.mouse_sensitivity;
 
+        // there's no pitch limit to enforce in free-flight mode since it can't flip the view
+        if self.mode == CameraMode::FreeFlight {
+            self.rotate(x_offset, y_offset, 0.0);
+            return;
+        }
+
         self.yaw += x_offset;
         self.pitch += y_offset;
 
@@ -136,26 +394,86 @@ impl Camera {
     }
 
     pub fn process_scroll(&mut self, y_offset: f32) {
-        if self.zoom >= 1.0 && self.zoom <= 45.0 {
-            self.zoom -= y_offset;
-        } else if self.zoom <= 1.0 {
-            self.zoom = 1.0;
-        } else if self.zoom >= 45.0 {
-            self.zoom = 45.0;
-        }
+        // clamp after applying the offset since zoom is used directly as the field of view, which
+        // must stay positive
+        self.zoom = (self.zoom - y_offset).max(1.0).min(Zoom::default().0);
     }
 
 
-    /// Recalculate internal front vector from current Euler angles
+    /// Turn the camera by the given angles in degrees. In FPS mode yaw and pitch are applied to the
+    /// Euler angles and roll is ignored; in free-flight mode each angle turns the camera around
+    /// its own up, right and front axes respectively.
+    fn rotate(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        match self.mode {
+            CameraMode::Fps => {
+                self.yaw += yaw;
+                self.pitch += pitch;
+            },
+            CameraMode::FreeFlight => {
+                let rotation =
+                    Quaternion::from_axis_angle(Vector3::unit_y(), Deg(-yaw)) *
+                    Quaternion::from_axis_angle(Vector3::unit_x(), Deg(pitch)) *
+                    Quaternion::from_axis_angle(-Vector3::unit_z(), Deg(roll));
+                // renormalize so rounding errors don't accumulate into a skewed basis
+                self.orientation = (self.orientation * rotation).normalize();
+            },
+        }
+        self.update_camera_vectors();
+    }
+
+    /// Recalculate internal front, right and up vectors from the current Euler angles (FPS mode)
+    /// or orientation quaternion (free-flight mode)
     fn update_camera_vectors(&mut self) {
-        self.front = Vector3::new(
-            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
-            self.pitch.to_radians().sin(),
-            self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
-        ).normalize();
-        self.right = self.front.cross(self.world_up).normalize();
-        self.up = self.right.cross(self.front).normalize();
+        match self.mode {
+            CameraMode::Fps => {
+                self.front = Vector3::new(
+                    self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
+                    self.pitch.to_radians().sin(),
+                    self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
+                ).normalize();
+                self.right = self.front.cross(self.world_up).normalize();
+                self.up = self.right.cross(self.front).normalize();
+            },
+            CameraMode::FreeFlight => {
+                self.front = (self.orientation * -Vector3::unit_z()).normalize();
+                self.right = (self.orientation * Vector3::unit_x()).normalize();
+                self.up = (self.orientation * Vector3::unit_y()).normalize();
+                // keep yaw and pitch meaningful for code that reads them
+                self.update_euler_angles();
+            },
+        }
+    }
+
+    /// Recalculate yaw and pitch from the front vector. Roll can't be expressed in these angles
+    /// and is dropped.
+    fn update_euler_angles(&mut self) {
+        self.pitch = self.front.y.max(-1.0).min(1.0).asin().to_degrees();
+        self.yaw = self.front.z.atan2(self.front.x).to_degrees();
+    }
+}
+
+/// Move `velocity` towards `target` along the curve `target + (velocity - target) * e^(-rate * t)`
+/// for `delta_time` seconds. Returns the new velocity and the distance covered, which is the curve
+/// integrated over the time step.
+fn approach(
+    velocity: Vector3<f32>,
+    target: Vector3<f32>,
+    rate: f32,
+    delta_time: f32,
+) -> (Vector3<f32>, Vector3<f32>) {
+    if rate <= 0.0 {
+        return (velocity, velocity * delta_time);
+    }
+
+    let decay = (-rate * delta_time).exp();
+    let mut new_velocity = target + (velocity - target) * decay;
+    let displacement = target * delta_time + (velocity - target) * ((1.0 - decay) / rate);
+
+    // the curve never quite reaches zero; stop once the motion is imperceptible
+    if target.is_zero() && new_velocity.magnitude2() < 1e-8 {
+        new_velocity = Vector3::zero();
     }
+    (new_velocity, displacement)
 }
 
 impl Default for Camera {
@@ -167,10 +485,23 @@ impl Default for Camera {
             up: Vector3::zero(),
             world_up: Vector3::unit_y(),
             movement_speed: Speed::default().0,
+            acceleration: Acceleration::default().0,
+            damping: Damping::default().0,
+            turn_speed: TurnSpeed::default().0,
             mouse_sensitivity: MouseSensitivity::default().0,
             zoom: Zoom::default().0,
+            projection: Projection::default(),
+            near_plane: ClipPlanes::default().0,
+            far_plane: ClipPlanes::default().1,
+            aspect_ratio: 4.0 / 3.0,
             yaw: Yaw::default().0,
             pitch: Pitch::default().0,
+            mode: CameraMode::Fps,
+            orientation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            velocity: Vector3::zero(),
+            angular_velocity: Vector3::zero(),
+            movement_input: Vector3::zero(),
+            turn_input: Vector3::zero(),
         };
 
         camera.update_camera_vectors();
@@ -0,0 +1,273 @@
+extern crate glfw;
+use self::glfw::{Action, Key};
+
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use common::camera::{Camera, CameraMode, CameraMovement};
+use common::heightmap::HeightField;
+
+/// Translates window input into camera movement. Lessons pick an implementation at startup and
+/// hand it to `common::input::process_events` and `common::input::process_input` every frame.
+pub trait CameraController {
+    /// Called once for every window event that isn't handled by the window itself.
+    fn process_event(&mut self, event: &glfw::WindowEvent, camera: &mut Camera);
+
+    /// Called once per frame to poll held keys and mouse buttons.
+    fn process_input(&mut self, window: &glfw::Window, delta_time: f32, camera: &mut Camera);
+}
+
+/// First-person controls: WASD/QE to walk and turn, R/F to rise and sink, Z/C to roll (free-flight
+/// mode only), the mouse to look around and the scroll wheel to zoom.
+pub struct FpsController {
+    first_mouse: bool,
+    last_x: f32,
+    last_y: f32,
+}
+
+impl FpsController {
+    pub fn new(screen_width: u32, screen_height: u32) -> Self {
+        FpsController {
+            first_mouse: true,
+            last_x: screen_width as f32 / 2.0,
+            last_y: screen_height as f32 / 2.0,
+        }
+    }
+}
+
+impl CameraController for FpsController {
+    fn process_event(&mut self, event: &glfw::WindowEvent, camera: &mut Camera) {
+        match *event {
+            glfw::WindowEvent::CursorPos(xpos, ypos) => {
+                let (xpos, ypos) = (xpos as f32, ypos as f32);
+                if self.first_mouse {
+                    self.last_x = xpos;
+                    self.last_y = ypos;
+                    self.first_mouse = false;
+                }
+
+                let xoffset = xpos - self.last_x;
+                let yoffset = self.last_y - ypos; // reversed since y-coordinates go from bottom to top
+
+                self.last_x = xpos;
+                self.last_y = ypos;
+
+                camera.process_mouse_movement(xoffset, yoffset, true);
+            }
+            glfw::WindowEvent::Scroll(_xoffset, yoffset) => {
+                camera.process_scroll(yoffset as f32);
+            }
+            _ => {}
+        }
+    }
+
+    fn process_input(&mut self, window: &glfw::Window, delta_time: f32, camera: &mut Camera) {
+        let key_bindings = [
+            (Key::W, CameraMovement::Forward),
+            (Key::S, CameraMovement::Backward),
+            (Key::A, CameraMovement::TurnLeft),
+            (Key::D, CameraMovement::TurnRight),
+            (Key::Q, CameraMovement::StrafeLeft),
+            (Key::E, CameraMovement::StrafeRight),
+            (Key::R, CameraMovement::Up),
+            (Key::F, CameraMovement::Down),
+            // rolling only has an effect in free-flight mode
+            (Key::Z, CameraMovement::RollLeft),
+            (Key::C, CameraMovement::RollRight),
+        ];
+        for &(key, movement) in key_bindings.iter() {
+            if window.get_key(key) == Action::Press {
+                camera.process_keyboard(movement);
+            }
+        }
+        camera.update_motion(delta_time);
+    }
+}
+
+/// Arcball-style controls for inspecting one point of the scene: drag with the left mouse button
+/// to orbit around the target, hold shift while dragging to pan the target, and scroll to zoom.
+pub struct OrbitController {
+    pub target: Point3<f32>,
+    pub rotate_sensitivity: f32,
+    pub pan_sensitivity: f32,
+
+    distance: f32,
+    // angles of the camera's position around the target, in degrees
+    yaw: f32,
+    pitch: f32,
+    last_cursor_position: Option<(f64, f64)>,
+}
+
+impl OrbitController {
+    /// Start orbiting `target` from wherever the camera currently is, and turn the camera to face
+    /// it.
+    pub fn new(target: Point3<f32>, camera: &mut Camera) -> Self {
+        let offset = camera.position - target;
+        let distance = offset.magnitude().max(0.1);
+        let mut controller = OrbitController {
+            target: target,
+            rotate_sensitivity: 0.25,
+            pan_sensitivity: 0.0015,
+            distance: distance,
+            yaw: offset.z.atan2(offset.x).to_degrees(),
+            pitch: (offset.y / distance).max(-1.0).min(1.0).asin().to_degrees(),
+            last_cursor_position: None,
+        };
+
+        controller.update_camera(camera);
+        controller
+    }
+
+    /// Place the camera on the orbit sphere and point it at the target
+    fn update_camera(&mut self, camera: &mut Camera) {
+        // stop short of the poles so the camera's up vector stays well defined
+        self.pitch = self.pitch.max(-89.0).min(89.0);
+
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        let offset = Vector3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        ) * self.distance;
+
+        camera.position = self.target + offset;
+        camera.look_at(self.target);
+    }
+}
+
+impl CameraController for OrbitController {
+    fn process_event(&mut self, event: &glfw::WindowEvent, camera: &mut Camera) {
+        if let glfw::WindowEvent::Scroll(_xoffset, yoffset) = *event {
+            camera.process_scroll(yoffset as f32);
+        }
+    }
+
+    fn process_input(&mut self, window: &glfw::Window, _delta_time: f32, camera: &mut Camera) {
+        if window.get_mouse_button(glfw::MouseButtonLeft) != Action::Press {
+            self.last_cursor_position = None;
+            return;
+        }
+
+        let (xpos, ypos) = window.get_cursor_pos();
+        let (xoffset, yoffset) = match self.last_cursor_position {
+            Some((last_x, last_y)) => ((xpos - last_x) as f32, (ypos - last_y) as f32),
+            None => (0.0, 0.0),
+        };
+        self.last_cursor_position = Some((xpos, ypos));
+
+        let panning = window.get_key(Key::LeftShift) == Action::Press ||
+            window.get_key(Key::RightShift) == Action::Press;
+        if panning {
+            // move the target so the scene follows the cursor, faster the further out we are
+            let pan_scale = self.pan_sensitivity * self.distance;
+            self.target += camera.up * yoffset * pan_scale - camera.right * xoffset * pan_scale;
+        } else {
+            // dragging right swings the camera left around the target, so the scene appears to
+            // turn with the cursor; dragging down raises the camera
+            self.yaw += xoffset * self.rotate_sensitivity;
+            self.pitch += yoffset * self.rotate_sensitivity;
+        }
+
+        self.update_camera(camera);
+    }
+}
+
+/// Wraps another controller to keep the camera on the ground: G toggles between walking and the
+/// wrapped controller's normal movement, and space jumps while walking. When walking, the wrapped
+/// controller only moves the camera horizontally, the edges of the ground stop it, and gravity
+/// pulls it down to `eye_height` above the ground.
+///
+/// Needs key polling enabled on the window.
+pub struct WalkController<C: CameraController> {
+    pub eye_height: f32,
+    /// downward acceleration in world units per second squared
+    pub gravity: f32,
+    /// upward speed at the start of a jump, in world units per second
+    pub jump_speed: f32,
+
+    inner: C,
+    ground: HeightField,
+    walking: bool,
+    /// the camera's mode before walking started, restored when it stops
+    flying_mode: CameraMode,
+    vertical_speed: f32,
+    on_ground: bool,
+}
+
+impl<C: CameraController> WalkController<C> {
+    /// Starts out flying; press G to walk
+    pub fn new(inner: C, ground: HeightField, eye_height: f32) -> Self {
+        WalkController {
+            eye_height: eye_height,
+            gravity: 9.81,
+            jump_speed: 4.0,
+            inner: inner,
+            ground: ground,
+            walking: false,
+            flying_mode: CameraMode::Fps,
+            vertical_speed: 0.0,
+            on_ground: false,
+        }
+    }
+
+    fn toggle_walking(&mut self, camera: &mut Camera) {
+        self.walking = !self.walking;
+        if self.walking {
+            // rolling makes no sense on foot
+            self.flying_mode = camera.mode();
+            camera.set_mode(CameraMode::Fps);
+            self.vertical_speed = 0.0;
+            self.on_ground = false;
+        } else {
+            camera.set_mode(self.flying_mode);
+        }
+    }
+}
+
+impl<C: CameraController> CameraController for WalkController<C> {
+    fn process_event(&mut self, event: &glfw::WindowEvent, camera: &mut Camera) {
+        match *event {
+            glfw::WindowEvent::Key(Key::G, _, Action::Press, _) => self.toggle_walking(camera),
+            glfw::WindowEvent::Key(Key::Space, _, Action::Press, _) if self.walking => {
+                if self.on_ground {
+                    self.vertical_speed = self.jump_speed;
+                    self.on_ground = false;
+                }
+            },
+            _ => self.inner.process_event(event, camera),
+        }
+    }
+
+    fn process_input(&mut self, window: &glfw::Window, delta_time: f32, camera: &mut Camera) {
+        if !self.walking {
+            self.inner.process_input(window, delta_time, camera);
+            return;
+        }
+
+        let previous_position = camera.position;
+        self.inner.process_input(window, delta_time, camera);
+        // height is up to gravity, not to where the camera is looking
+        camera.position.y = previous_position.y;
+
+        let ground_height = match self.ground.height_at(camera.position.x, camera.position.z) {
+            Some(height) => height,
+            None => {
+                camera.position = previous_position;
+                match self.ground.height_at(camera.position.x, camera.position.z) {
+                    Some(height) => height,
+                    // started off the edge of the ground; nothing to walk on
+                    None => return,
+                }
+            },
+        };
+
+        self.vertical_speed -= self.gravity * delta_time;
+        camera.position.y += self.vertical_speed * delta_time;
+
+        let eye_level = ground_height + self.eye_height;
+        self.on_ground = camera.position.y <= eye_level;
+        if self.on_ground {
+            camera.position.y = eye_level;
+            self.vertical_speed = 0.0;
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::{Add, Mul, Sub};
+
+extern crate glfw;
+use self::glfw::{Action, Key};
+
+use cgmath::{EuclideanSpace, Point3};
+
+use common::camera::Camera;
+use common::camera_controller::CameraController;
+
+#[cfg(test)]
+#[path = "./camera_path_tests.rs"]
+mod camera_path_tests;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    /// seconds since the start of the path
+    pub time: f32,
+    pub position: Point3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// A keyframe that would break a path's increasing time order, e.g. one recorded while the clock
+/// was paused
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KeyframeTimeError {
+    pub time: f32,
+    /// the time of the path's last keyframe, if it has any
+    pub previous_time: Option<f32>,
+}
+
+impl fmt::Display for KeyframeTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.previous_time {
+            Some(previous_time) => write!(f, "keyframe time {} isn't after the previous keyframe's {}", self.time, previous_time),
+            None => write!(f, "keyframe time {} isn't a number of seconds", self.time),
+        }
+    }
+}
+
+impl Error for KeyframeTimeError {
+    fn description(&self) -> &str {
+        "camera path keyframe out of time order"
+    }
+}
+
+/// A camera flythrough: keyframes in increasing time order, played back with Catmull-Rom
+/// interpolation.
+///
+/// Paths are saved as text, one keyframe per line: `time x y z yaw pitch`. Blank lines and lines
+/// starting with `#` are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        CameraPath { keyframes: vec![] }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Length of the path in seconds
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// Append a keyframe. Keyframes must be added in increasing time order, so one that isn't
+    /// after the last keyframe, or whose time isn't finite, is left out.
+    pub fn push(&mut self, mut keyframe: Keyframe) -> Result<(), KeyframeTimeError> {
+        let previous_time = self.keyframes.last().map(|previous| previous.time);
+        // written so that NaN times fail too
+        let in_order = previous_time.map_or(true, |previous_time| keyframe.time > previous_time);
+        if !keyframe.time.is_finite() || !in_order {
+            return Err(KeyframeTimeError { time: keyframe.time, previous_time: previous_time });
+        }
+
+        if let Some(previous) = self.keyframes.last() {
+            // yaw wraps around at +/-180 degrees; unwrap it so interpolation takes the short way
+            // around instead of spinning the camera
+            while keyframe.yaw - previous.yaw > 180.0 {
+                keyframe.yaw -= 360.0;
+            }
+            while keyframe.yaw - previous.yaw < -180.0 {
+                keyframe.yaw += 360.0;
+            }
+        }
+        self.keyframes.push(keyframe);
+        Ok(())
+    }
+
+    /// The interpolated position, yaw and pitch at time `t`, or None if the path is empty. Times
+    /// outside the path are clamped to its first and last keyframes.
+    pub fn sample(&self, t: f32) -> Option<(Point3<f32>, f32, f32)> {
+        let last_index = match self.keyframes.len() {
+            0 => return None,
+            len => len - 1,
+        };
+
+        // index of the keyframe that starts the segment containing t
+        let i = match self.keyframes.iter().position(|keyframe| keyframe.time > t) {
+            Some(0) => return Some(self.keyframe_values(0)),
+            Some(next) => next - 1,
+            None => return Some(self.keyframe_values(last_index)),
+        };
+
+        // repeat the end keyframes to get tangents at the ends of the path
+        let k0 = &self.keyframes[if i == 0 { 0 } else { i - 1 }];
+        let k1 = &self.keyframes[i];
+        let k2 = &self.keyframes[i + 1];
+        let k3 = &self.keyframes[if i + 2 > last_index { last_index } else { i + 2 }];
+
+        let u = (t - k1.time) / (k2.time - k1.time);
+        let position = Point3::from_vec(catmull_rom(
+            k0.position.to_vec(),
+            k1.position.to_vec(),
+            k2.position.to_vec(),
+            k3.position.to_vec(),
+            u,
+        ));
+        let yaw = catmull_rom(k0.yaw, k1.yaw, k2.yaw, k3.yaw, u);
+        let pitch = catmull_rom(k0.pitch, k1.pitch, k2.pitch, k3.pitch, u);
+
+        Some((position, yaw, pitch))
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(file, "# time x y z yaw pitch")?;
+        for keyframe in self.keyframes.iter() {
+            writeln!(
+                file,
+                "{} {} {} {} {} {}",
+                keyframe.time,
+                keyframe.position.x,
+                keyframe.position.y,
+                keyframe.position.z,
+                keyframe.yaw,
+                keyframe.pitch,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mut camera_path = CameraPath::new();
+
+        for (line_index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: expected `time x y z yaw pitch`, got `{}`", path, line_index + 1, line),
+            );
+            let values = line
+                .split_whitespace()
+                .map(|value| value.parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| invalid_line())?;
+            if values.len() != 6 {
+                return Err(invalid_line());
+            }
+
+            camera_path.push(Keyframe {
+                time: values[0],
+                position: Point3::new(values[1], values[2], values[3]),
+                yaw: values[4],
+                pitch: values[5],
+            }).map_err(|_| invalid_line())?;
+        }
+
+        Ok(camera_path)
+    }
+
+    fn keyframe_values(&self, index: usize) -> (Point3<f32>, f32, f32) {
+        let keyframe = &self.keyframes[index];
+        (keyframe.position, keyframe.yaw, keyframe.pitch)
+    }
+}
+
+/// Samples the camera at a fixed interval while it's flown around
+pub struct CameraPathRecorder {
+    path: CameraPath,
+    interval: f32,
+    elapsed: f32,
+    next_keyframe_time: f32,
+}
+
+impl CameraPathRecorder {
+    /// `interval` is the number of seconds between keyframes
+    pub fn new(interval: f32) -> Self {
+        CameraPathRecorder {
+            path: CameraPath::new(),
+            interval: interval,
+            elapsed: 0.0,
+            next_keyframe_time: 0.0,
+        }
+    }
+
+    /// Call once per frame after the camera has been moved
+    pub fn record(&mut self, camera: &Camera, delta_time: f32) {
+        if !self.path.keyframes.is_empty() {
+            self.elapsed += delta_time;
+        }
+        if self.elapsed < self.next_keyframe_time {
+            return;
+        }
+
+        // a frame that took no time, e.g. while the clock is paused, would repeat the last
+        // keyframe's time, so it's skipped
+        let keyframe = Keyframe {
+            time: self.elapsed,
+            position: camera.position,
+            yaw: camera.yaw,
+            pitch: camera.pitch,
+        };
+        if self.path.push(keyframe).is_ok() {
+            self.next_keyframe_time = self.elapsed + self.interval;
+        }
+    }
+
+    pub fn finish(self) -> CameraPath {
+        self.path
+    }
+}
+
+/// Moves the camera along a recorded path.
+///
+/// With a fixed time step, every frame advances the path by the same amount no matter how long
+/// the frame took to render, so frame N of a playback always shows the same view. That's what
+/// makes playbacks comparable between builds; without one, playback follows the real frame time.
+pub struct CameraPathPlayer {
+    path: CameraPath,
+    fixed_time_step: Option<f32>,
+    time: f32,
+    frame: u32,
+}
+
+impl CameraPathPlayer {
+    pub fn new(path: CameraPath, fixed_time_step: Option<f32>) -> Self {
+        CameraPathPlayer {
+            path: path,
+            fixed_time_step: fixed_time_step,
+            time: 0.0,
+            frame: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.time > self.path.duration()
+    }
+
+    /// Number of frames played so far
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Move the camera to the current point on the path, then advance the path by one frame
+    pub fn advance(&mut self, delta_time: f32, camera: &mut Camera) {
+        // don't let momentum from before playback carry over once it ends
+        camera.stop();
+        if let Some((position, yaw, pitch)) = self.path.sample(self.time) {
+            camera.position = position;
+            camera.set_euler_angles(yaw, pitch);
+        }
+
+        self.frame += 1;
+        self.time = match self.fixed_time_step {
+            // multiply rather than accumulate so rounding errors don't drift between runs
+            Some(time_step) => self.frame as f32 * time_step,
+            None => self.time + delta_time,
+        };
+    }
+}
+
+/// Wraps another controller to add flythrough recording and playback: F5 starts recording,
+/// pressing it again stops and saves the path to `file_path`, and F6 plays the saved path back.
+/// The wrapped controller moves the camera whenever a path isn't playing.
+///
+/// Needs key polling enabled on the window.
+pub struct CameraPathController<C: CameraController> {
+    inner: C,
+    file_path: String,
+    keyframe_interval: f32,
+    playback_time_step: Option<f32>,
+    recorder: Option<CameraPathRecorder>,
+    player: Option<CameraPathPlayer>,
+}
+
+impl<C: CameraController> CameraPathController<C> {
+    pub fn new(inner: C, file_path: &str, keyframe_interval: f32, playback_time_step: Option<f32>) -> Self {
+        CameraPathController {
+            inner: inner,
+            file_path: file_path.to_string(),
+            keyframe_interval: keyframe_interval,
+            playback_time_step: playback_time_step,
+            recorder: None,
+            player: None,
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        match self.recorder.take() {
+            Some(recorder) => {
+                let path = recorder.finish();
+                match path.save(&self.file_path) {
+                    Ok(()) => println!(
+                        "Saved {} camera keyframes ({:.1}s) to {}",
+                        path.keyframes().len(), path.duration(), self.file_path
+                    ),
+                    Err(error) => println!("Failed to save camera path to {}: {}", self.file_path, error),
+                }
+            },
+            None => {
+                println!("Recording camera path");
+                self.player = None;
+                self.recorder = Some(CameraPathRecorder::new(self.keyframe_interval));
+            },
+        }
+    }
+
+    fn start_playback(&mut self) {
+        if self.recorder.is_some() {
+            return;
+        }
+
+        match CameraPath::load(&self.file_path) {
+            Ok(path) => {
+                println!("Playing back camera path from {}", self.file_path);
+                self.player = Some(CameraPathPlayer::new(path, self.playback_time_step));
+            },
+            Err(error) => println!("Failed to load camera path from {}: {}", self.file_path, error),
+        }
+    }
+}
+
+impl<C: CameraController> CameraController for CameraPathController<C> {
+    fn process_event(&mut self, event: &glfw::WindowEvent, camera: &mut Camera) {
+        match *event {
+            glfw::WindowEvent::Key(Key::F5, _, Action::Press, _) => self.toggle_recording(),
+            glfw::WindowEvent::Key(Key::F6, _, Action::Press, _) => self.start_playback(),
+            _ => {
+                if self.player.is_none() {
+                    self.inner.process_event(event, camera);
+                }
+            },
+        }
+    }
+
+    fn process_input(&mut self, window: &glfw::Window, delta_time: f32, camera: &mut Camera) {
+        let finished_playing = match self.player {
+            Some(ref mut player) => {
+                player.advance(delta_time, camera);
+                player.is_finished()
+            },
+            None => {
+                self.inner.process_input(window, delta_time, camera);
+                false
+            },
+        };
+        if finished_playing {
+            println!("Camera path finished after {} frames", self.player.as_ref().unwrap().frame());
+            self.player = None;
+        }
+
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.record(camera, delta_time);
+        }
+    }
+}
+
+/// Uniform Catmull-Rom spline through p1 (at u = 0) and p2 (at u = 1)
+fn catmull_rom<T>(p0: T, p1: T, p2: T, p3: T, u: f32) -> T
+    where T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>
+{
+    let u2 = u * u;
+    let u3 = u2 * u;
+    (p1 * 2.0 +
+        (p2 - p0) * u +
+        (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * u2 +
+        (p1 * 3.0 - p0 - p2 * 3.0 + p3) * u3) * 0.5
+}
@@ -0,0 +1,85 @@
+use std::f32::NAN;
+
+use super::*;
+
+fn keyframe(time: f32, x: f32, yaw: f32) -> Keyframe {
+    Keyframe {
+        time: time,
+        position: Point3::new(x, 2.0 * x, -x),
+        yaw: yaw,
+        pitch: x / 10.0,
+    }
+}
+
+/// A path through `x` = 0, 1, 4 and 9 at one-second intervals, so no segment is a straight line
+fn curved_path() -> CameraPath {
+    let mut path = CameraPath::new();
+    for i in 0..4 {
+        let i = i as f32;
+        path.push(keyframe(i, i * i, 10.0 * i)).unwrap();
+    }
+    path
+}
+
+#[test]
+fn sampling_at_keyframe_times_gives_the_keyframes() {
+    let path = curved_path();
+    for keyframe in path.keyframes().iter() {
+        assert_eq!(path.sample(keyframe.time), Some((keyframe.position, keyframe.yaw, keyframe.pitch)));
+    }
+}
+
+#[test]
+fn sampling_between_keyframes_follows_the_spline() {
+    let path = curved_path();
+    let (position, yaw, pitch) = path.sample(1.5).unwrap();
+    // Catmull-Rom splines through evenly spaced keyframes follow quadratics exactly, so x = t^2
+    let expected_x = 2.25;
+    assert!((position.x - expected_x).abs() < 1e-5, "expected x {}, got {:?}", expected_x, position);
+    assert!((position.y - 2.0 * expected_x).abs() < 1e-5);
+    assert!((yaw - 15.0).abs() < 1e-5);
+    assert!((pitch - expected_x / 10.0).abs() < 1e-5);
+}
+
+#[test]
+fn sampling_outside_the_path_clamps_to_its_ends() {
+    let path = curved_path();
+    let first = path.keyframes()[0];
+    let last = path.keyframes()[3];
+    assert_eq!(path.sample(-1.0), Some((first.position, first.yaw, first.pitch)));
+    assert_eq!(path.sample(3.5), Some((last.position, last.yaw, last.pitch)));
+    assert_eq!(path.sample(100.0), Some((last.position, last.yaw, last.pitch)));
+    assert_eq!(CameraPath::new().sample(0.0), None);
+}
+
+#[test]
+fn path_with_one_keyframe_always_gives_it() {
+    let mut path = CameraPath::new();
+    path.push(keyframe(2.0, 1.0, 30.0)).unwrap();
+    for &t in [0.0, 2.0, 5.0].iter() {
+        assert_eq!(path.sample(t), Some((Point3::new(1.0, 2.0, -1.0), 30.0, 0.1)));
+    }
+}
+
+#[test]
+fn keyframes_out_of_time_order_are_left_out() {
+    let mut path = CameraPath::new();
+    assert!(path.push(keyframe(NAN, 0.0, 0.0)).is_err());
+    path.push(keyframe(1.0, 0.0, 0.0)).unwrap();
+
+    assert_eq!(path.push(keyframe(1.0, 1.0, 0.0)), Err(KeyframeTimeError { time: 1.0, previous_time: Some(1.0) }));
+    assert!(path.push(keyframe(0.5, 1.0, 0.0)).is_err());
+    assert!(path.push(keyframe(NAN, 1.0, 0.0)).is_err());
+    assert_eq!(path.keyframes().len(), 1);
+
+    path.push(keyframe(1.5, 1.0, 0.0)).unwrap();
+    assert_eq!(path.duration(), 1.5);
+}
+
+#[test]
+fn yaw_is_unwrapped_the_short_way_around() {
+    let mut path = CameraPath::new();
+    path.push(keyframe(0.0, 0.0, 170.0)).unwrap();
+    path.push(keyframe(1.0, 0.0, -170.0)).unwrap();
+    assert_eq!(path.keyframes()[1].yaw, 190.0);
+}
@@ -0,0 +1,108 @@
+// The lesson assets built into the executable with the `embed-assets` feature, so it runs without
+// the `src` folder. Names are relative to `src`, as passed to `common::assets`. Every file under
+// `src` other than Rust source and `.orig` backups must be listed; a test checks.
+
+#[cfg(test)]
+#[path = "./embedded_assets_tests.rs"]
+mod embedded_assets_tests;
+
+#[cfg(feature = "embed-assets")]
+macro_rules! embed {
+    ($name:expr) => {
+        ($name, include_bytes!(concat!("../", $name)) as &'static [u8])
+    };
+}
+
+#[cfg(not(feature = "embed-assets"))]
+pub static ASSETS: &'static [(&'static str, &'static [u8])] = &[];
+
+#[cfg(feature = "embed-assets")]
+pub static ASSETS: &'static [(&'static str, &'static [u8])] = &[
+    embed!("_1_3_shaders/fragment_shader.glsl"),
+    embed!("_1_3_shaders/vertex_shader.glsl"),
+    embed!("_1_4_textures/fragment_shader.glsl"),
+    embed!("_1_4_textures/texture_awesome.png"),
+    embed!("_1_4_textures/texture_container.jpg"),
+    embed!("_1_4_textures/vertex_shader.glsl"),
+    embed!("_1_5_transformations/fragment_shader.glsl"),
+    embed!("_1_5_transformations/texture_awesome.png"),
+    embed!("_1_5_transformations/texture_container.jpg"),
+    embed!("_1_5_transformations/vertex_shader.glsl"),
+    embed!("_1_6_coordinate_systems/fragment_shader.glsl"),
+    embed!("_1_6_coordinate_systems/texture_awesome.png"),
+    embed!("_1_6_coordinate_systems/texture_container.jpg"),
+    embed!("_1_6_coordinate_systems/vertex_shader.glsl"),
+    embed!("_2_1_colors/fragment_shader.glsl"),
+    embed!("_2_1_colors/fragment_shader_lamp.glsl"),
+    embed!("_2_1_colors/vertex_shader.glsl"),
+    embed!("_2_2_basic_lighting/coordinate_axes_fragment_shader.glsl"),
+    embed!("_2_2_basic_lighting/coordinate_axes_vertex_shader.glsl"),
+    embed!("_2_2_basic_lighting/fragment_shader.glsl"),
+    embed!("_2_2_basic_lighting/fragment_shader_lamp.glsl"),
+    embed!("_2_2_basic_lighting/vertex_shader.glsl"),
+    embed!("_2_3_materials/coordinate_axes_fragment_shader.glsl"),
+    embed!("_2_3_materials/coordinate_axes_vertex_shader.glsl"),
+    embed!("_2_3_materials/fragment_shader.glsl"),
+    embed!("_2_3_materials/fragment_shader_lamp.glsl"),
+    embed!("_2_3_materials/vertex_shader.glsl"),
+    embed!("_2_4_lighting_maps/coordinate_axes_fragment_shader.glsl"),
+    embed!("_2_4_lighting_maps/coordinate_axes_vertex_shader.glsl"),
+    embed!("_2_4_lighting_maps/fragment_shader.glsl"),
+    embed!("_2_4_lighting_maps/fragment_shader_lamp.glsl"),
+    embed!("_2_4_lighting_maps/texture_container.png"),
+    embed!("_2_4_lighting_maps/texture_container_specular_map.png"),
+    embed!("_2_4_lighting_maps/texture_emission_map.jpg"),
+    embed!("_2_4_lighting_maps/vertex_shader.glsl"),
+    embed!("_2_5_light_casters/coordinate_axes_fragment_shader.glsl"),
+    embed!("_2_5_light_casters/coordinate_axes_vertex_shader.glsl"),
+    embed!("_2_5_light_casters/fragment_shader_lamp.glsl"),
+    embed!("_2_5_light_casters/fragment_shader_point_light.glsl"),
+    embed!("_2_5_light_casters/fragment_shader_spotlight.glsl"),
+    embed!("_2_5_light_casters/texture_container.png"),
+    embed!("_2_5_light_casters/texture_container_specular_map.png"),
+    embed!("_2_5_light_casters/vertex_shader.glsl"),
+    embed!("_2_6_multiple_lights/coordinate_axes_fragment_shader.glsl"),
+    embed!("_2_6_multiple_lights/coordinate_axes_vertex_shader.glsl"),
+    embed!("_2_6_multiple_lights/fragment_shader.glsl"),
+    embed!("_2_6_multiple_lights/fragment_shader_lamp.glsl"),
+    embed!("_2_6_multiple_lights/texture_crate.png"),
+    embed!("_2_6_multiple_lights/texture_crate_specular_map.png"),
+    embed!("_2_6_multiple_lights/vertex_shader.glsl"),
+    embed!("_4_2_stencil_testing/coordinate_axes_fragment_shader.glsl"),
+    embed!("_4_2_stencil_testing/coordinate_axes_vertex_shader.glsl"),
+    embed!("_4_2_stencil_testing/fragment_shader.glsl"),
+    embed!("_4_2_stencil_testing/fragment_shader_highlight.glsl"),
+    embed!("_4_2_stencil_testing/fragment_shader_lamp.glsl"),
+    embed!("_4_2_stencil_testing/fragment_shader_single_color.glsl"),
+    embed!("_4_2_stencil_testing/sky_back.png"),
+    embed!("_4_2_stencil_testing/sky_bottom.png"),
+    embed!("_4_2_stencil_testing/sky_front.png"),
+    embed!("_4_2_stencil_testing/sky_left.png"),
+    embed!("_4_2_stencil_testing/sky_right.png"),
+    embed!("_4_2_stencil_testing/sky_top.png"),
+    embed!("_4_2_stencil_testing/texture_crate_specular_map.png"),
+    embed!("_4_2_stencil_testing/texture_marble.jpg"),
+    embed!("_4_2_stencil_testing/texture_metal.png"),
+    embed!("_4_2_stencil_testing/texturing_fragment_shader.glsl"),
+    embed!("_4_2_stencil_testing/texturing_vertex_shader.glsl"),
+    embed!("_4_2_stencil_testing/vertex_shader.glsl"),
+    embed!("_4_2_stencil_testing/vertex_shader_lamp.glsl"),
+    embed!("common/shaders/camera_block.glsl"),
+    embed!("common/shaders/light_block.glsl"),
+    embed!("common/shaders/lighting.glsl"),
+    embed!("common/shaders/skybox_fragment_shader.glsl"),
+    embed!("common/shaders/skybox_vertex_shader.glsl"),
+    embed!("common/sky.png"),
+    embed!("heightmap/coordinate_axes_fragment_shader.glsl"),
+    embed!("heightmap/coordinate_axes_vertex_shader.glsl"),
+    embed!("heightmap/fragment_shader.glsl"),
+    embed!("heightmap/heightmap_1.png"),
+    embed!("heightmap/heightmap_2.png"),
+    embed!("heightmap/normals_compute_shader.glsl"),
+    embed!("heightmap/normals_view_geometry_shader.glsl"),
+    embed!("heightmap/normals_view_vertex_shader.glsl"),
+    embed!("heightmap/terrain_diffuse_map.png"),
+    embed!("heightmap/terrain_specular_map.png"),
+    embed!("heightmap/test_heightmap.png"),
+    embed!("heightmap/vertex_shader.glsl"),
+];
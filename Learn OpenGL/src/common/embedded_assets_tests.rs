@@ -0,0 +1,48 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// The names in the `embed!` list, read from the source so it's checked without the feature
+fn listed_assets() -> BTreeSet<String> {
+    include_str!("./embedded_assets.rs")
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.starts_with("embed!(\"") && line.ends_with("\"),") {
+                Some(line["embed!(\"".len()..line.len() - "\"),".len()].to_string())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Every asset file under `folder`, named relative to `src`
+fn asset_files(src_folder: &Path, folder: &Path, assets: &mut BTreeSet<String>) {
+    for entry in fs::read_dir(folder).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            asset_files(src_folder, &path, assets);
+            continue;
+        }
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+        if extension == "rs" || extension == "orig" {
+            continue;
+        }
+        let name = path.strip_prefix(src_folder).unwrap().to_string_lossy().replace('\\', "/");
+        assets.insert(name);
+    }
+}
+
+#[test]
+fn every_lesson_asset_is_embedded() {
+    let src_folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut assets = BTreeSet::new();
+    asset_files(&src_folder, &src_folder, &mut assets);
+
+    let listed = listed_assets();
+    let missing: Vec<&String> = assets.difference(&listed).collect();
+    let stale: Vec<&String> = listed.difference(&assets).collect();
+    assert!(missing.is_empty(), "add these to embedded_assets.rs: {:?}", missing);
+    assert!(stale.is_empty(), "these in embedded_assets.rs don't exist: {:?}", stale);
+}
@@ -0,0 +1,164 @@
+use std::f32::{INFINITY, NEG_INFINITY};
+use std::fmt;
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Vector3, Vector4};
+
+#[cfg(test)]
+#[path = "./frustum_tests.rs"]
+mod frustum_tests;
+
+/// A plane `normal . p + distance = 0`, with the normal pointing into the frustum
+#[derive(Debug, Copy, Clone)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    fn from_row(row: Vector4<f32>) -> Self {
+        let normal = row.truncate();
+        let length = normal.magnitude();
+        Plane {
+            normal: normal / length,
+            distance: row.w / length,
+        }
+    }
+
+    /// Distance from the plane to the point; negative when the point is outside the frustum
+    pub fn signed_distance(&self, point: Point3<f32>) -> f32 {
+        self.normal.dot(point.to_vec()) + self.distance
+    }
+}
+
+/// The six planes bounding everything a camera can see, in world space
+#[derive(Debug, Copy, Clone)]
+pub struct Frustum {
+    /// left, right, bottom, top, near, far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extract the planes from a combined `projection * view` matrix (Gribb & Hartmann). Each
+    /// plane is a sum or difference of the matrix's last row with one of the others.
+    pub fn from_matrix(view_projection: &Matrix4<f32>) -> Self {
+        let m = view_projection;
+        let row = |i: usize| Vector4::new(m.x[i], m.y[i], m.z[i], m.w[i]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        Frustum {
+            planes: [
+                Plane::from_row(w + x),
+                Plane::from_row(w - x),
+                Plane::from_row(w + y),
+                Plane::from_row(w - y),
+                Plane::from_row(w + z),
+                Plane::from_row(w - z),
+            ],
+        }
+    }
+
+    pub fn intersects_sphere(&self, center: Point3<f32>, radius: f32) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(center) >= -radius)
+    }
+
+    pub fn intersects_aabb(&self, min: Point3<f32>, max: Point3<f32>) -> bool {
+        self.planes.iter().all(|plane| {
+            // the box corner furthest along the plane's normal; if even that's outside, the
+            // whole box is
+            let furthest_corner = Point3::new(
+                if plane.normal.x >= 0.0 { max.x } else { min.x },
+                if plane.normal.y >= 0.0 { max.y } else { min.y },
+                if plane.normal.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.signed_distance(furthest_corner) >= 0.0
+        })
+    }
+
+    /// Conservative test: may report a volume near a frustum corner as visible when it isn't, but
+    /// never reports a visible volume as hidden
+    pub fn intersects(&self, bounds: &BoundingVolume) -> bool {
+        match *bounds {
+            BoundingVolume::Sphere { center, radius } => self.intersects_sphere(center, radius),
+            BoundingVolume::Aabb { min, max } => self.intersects_aabb(min, max),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundingVolume {
+    Sphere { center: Point3<f32>, radius: f32 },
+    /// axis-aligned bounding box
+    Aabb { min: Point3<f32>, max: Point3<f32> },
+}
+
+impl BoundingVolume {
+    /// Bounds of the volume after `transform` is applied. Boxes are re-fit around their
+    /// transformed corners so they stay axis-aligned.
+    pub fn transformed(&self, transform: &Matrix4<f32>) -> Self {
+        match *self {
+            BoundingVolume::Sphere { center, radius } => {
+                let max_scale = transform.x.truncate().magnitude()
+                    .max(transform.y.truncate().magnitude())
+                    .max(transform.z.truncate().magnitude());
+                BoundingVolume::Sphere {
+                    center: Point3::from_homogeneous(transform * center.to_homogeneous()),
+                    radius: radius * max_scale,
+                }
+            },
+            BoundingVolume::Aabb { min, max } => {
+                let corners = (0..8).map(|i| Point3::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                ));
+                let mut transformed_min = Point3::new(INFINITY, INFINITY, INFINITY);
+                let mut transformed_max = Point3::new(NEG_INFINITY, NEG_INFINITY, NEG_INFINITY);
+                for corner in corners {
+                    let corner = Point3::from_homogeneous(transform * corner.to_homogeneous());
+                    transformed_min = Point3::new(
+                        transformed_min.x.min(corner.x),
+                        transformed_min.y.min(corner.y),
+                        transformed_min.z.min(corner.z),
+                    );
+                    transformed_max = Point3::new(
+                        transformed_max.x.max(corner.x),
+                        transformed_max.y.max(corner.y),
+                        transformed_max.z.max(corner.z),
+                    );
+                }
+                BoundingVolume::Aabb { min: transformed_min, max: transformed_max }
+            },
+        }
+    }
+}
+
+/// How many scene elements and element instances were drawn and culled in a frame
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct CullingStats {
+    pub elements_drawn: u32,
+    pub elements_culled: u32,
+    pub instances_drawn: u32,
+    pub instances_culled: u32,
+}
+
+impl CullingStats {
+    /// Count one instance of an element, returning whether it's visible
+    pub fn record_instance(&mut self, visible: bool) -> bool {
+        if visible {
+            self.instances_drawn += 1;
+        } else {
+            self.instances_culled += 1;
+        }
+        visible
+    }
+}
+
+impl fmt::Display for CullingStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "elements: {} drawn, {} culled; instances: {} drawn, {} culled",
+            self.elements_drawn, self.elements_culled, self.instances_drawn, self.instances_culled,
+        )
+    }
+}
@@ -0,0 +1,91 @@
+use cgmath::{perspective, Deg, SquareMatrix};
+
+use super::*;
+
+/// Looking down -z from the origin with a 90 degree field of view, so the side planes are at 45
+/// degrees and the near and far planes are at z = -1 and z = -100
+fn frustum() -> Frustum {
+    Frustum::from_matrix(&perspective(Deg(90.0), 1.0, 1.0, 100.0))
+}
+
+fn assert_plane(plane: &Plane, normal: Vector3<f32>, distance: f32) {
+    assert!(
+        (plane.normal - normal).magnitude() < 1e-5 && (plane.distance - distance).abs() < 1e-3,
+        "expected normal {:?} and distance {}, got {:?}", normal, distance, plane,
+    );
+}
+
+fn aabb(min: (f32, f32, f32), max: (f32, f32, f32)) -> BoundingVolume {
+    BoundingVolume::Aabb { min: Point3::new(min.0, min.1, min.2), max: Point3::new(max.0, max.1, max.2) }
+}
+
+#[test]
+fn planes_of_a_known_projection() {
+    let planes = frustum().planes;
+    let diagonal = 0.5f32.sqrt();
+    assert_plane(&planes[0], Vector3::new(diagonal, 0.0, -diagonal), 0.0);
+    assert_plane(&planes[1], Vector3::new(-diagonal, 0.0, -diagonal), 0.0);
+    assert_plane(&planes[2], Vector3::new(0.0, diagonal, -diagonal), 0.0);
+    assert_plane(&planes[3], Vector3::new(0.0, -diagonal, -diagonal), 0.0);
+    assert_plane(&planes[4], Vector3::new(0.0, 0.0, -1.0), -1.0);
+    assert_plane(&planes[5], Vector3::new(0.0, 0.0, 1.0), 100.0);
+}
+
+#[test]
+fn volumes_in_front_are_inside() {
+    let frustum = frustum();
+    assert!(frustum.intersects(&aabb((-1.0, -1.0, -11.0), (1.0, 1.0, -9.0))));
+    assert!(frustum.intersects(&BoundingVolume::Sphere { center: Point3::new(0.0, 0.0, -10.0), radius: 1.0 }));
+}
+
+#[test]
+fn volumes_behind_or_beyond_are_outside() {
+    let frustum = frustum();
+    assert!(!frustum.intersects(&aabb((-1.0, -1.0, 9.0), (1.0, 1.0, 11.0))));
+    assert!(!frustum.intersects(&BoundingVolume::Sphere { center: Point3::new(0.0, 0.0, 10.0), radius: 1.0 }));
+    assert!(!frustum.intersects(&aabb((-1.0, -1.0, -111.0), (1.0, 1.0, -101.0))));
+    // level with the frustum, but off to the left
+    assert!(!frustum.intersects(&aabb((-30.0, -1.0, -11.0), (-20.0, 1.0, -9.0))));
+    assert!(!frustum.intersects(&BoundingVolume::Sphere { center: Point3::new(-20.0, 0.0, -10.0), radius: 1.0 }));
+}
+
+#[test]
+fn volumes_straddling_a_plane_intersect() {
+    let frustum = frustum();
+    // across the near plane
+    assert!(frustum.intersects(&aabb((-1.0, -1.0, -2.0), (1.0, 1.0, 0.5))));
+    // across the left plane, which is at x = -10 this far away
+    assert!(frustum.intersects(&aabb((-20.0, -1.0, -11.0), (-5.0, 1.0, -9.0))));
+    assert!(frustum.intersects(&BoundingVolume::Sphere { center: Point3::new(-10.5, 0.0, -10.0), radius: 1.0 }));
+    // across the far plane
+    assert!(frustum.intersects(&BoundingVolume::Sphere { center: Point3::new(0.0, 0.0, -100.5), radius: 1.0 }));
+}
+
+#[test]
+fn planes_follow_the_view() {
+    // looking at the origin from z = 20
+    let view = Matrix4::look_at(Point3::new(0.0, 0.0, 20.0), Point3::new(0.0, 0.0, 0.0), Vector3::unit_y());
+    let frustum = Frustum::from_matrix(&(perspective(Deg(90.0), 1.0, 1.0, 100.0) * view));
+    assert!(frustum.intersects(&aabb((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0))));
+    assert!(!frustum.intersects(&aabb((-1.0, -1.0, 21.0), (1.0, 1.0, 23.0))));
+    assert_plane(&frustum.planes[4], Vector3::new(0.0, 0.0, -1.0), 19.0);
+}
+
+#[test]
+fn transformed_boxes_stay_axis_aligned() {
+    let translated = aabb((0.0, 0.0, 0.0), (1.0, 1.0, 1.0))
+        .transformed(&Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0)));
+    assert_eq!(translated, aabb((1.0, 2.0, 3.0), (2.0, 3.0, 4.0)));
+
+    let rotated = aabb((0.0, 0.0, 0.0), (1.0, 1.0, 1.0)).transformed(&Matrix4::from_angle_y(Deg(90.0)));
+    match rotated {
+        BoundingVolume::Aabb { min, max } => {
+            assert!((min - Point3::new(0.0, 0.0, -1.0)).magnitude() < 1e-5, "{:?}", min);
+            assert!((max - Point3::new(1.0, 1.0, 0.0)).magnitude() < 1e-5, "{:?}", max);
+        },
+        sphere => panic!("expected a box, got {:?}", sphere),
+    }
+
+    let identity = BoundingVolume::Sphere { center: Point3::new(1.0, 2.0, 3.0), radius: 2.0 };
+    assert_eq!(identity.transformed(&Matrix4::identity()), identity);
+}
@@ -2,52 +2,671 @@
 #[path = "./heightmap_tests.rs"]
 pub mod heightmap_tests;
 
+use std::error::Error;
+use std::f32::{INFINITY, NEG_INFINITY};
+use std::fmt;
+use std::mem;
+use std::os::raw::c_void;
 use std::path::Path;
+use std::str::FromStr;
+use std::u16;
+use std::u32;
 
-use image;
-use image::{GenericImage, Pixel};
+use gl;
+use gl::types::*;
 
-type Vertex = Vec<f32>;
+use cgmath::{InnerSpace, Point3, Vector3};
 
-pub fn heightmap_data(path: &str, flip_y: bool) -> (Vec<f32>, Vec<i32>) {
-    let (vertices, heightmap_height, heightmap_width) =
-        vertices_from_heightmap(path, flip_y);
+use common::assets;
+use common::assets::AssetError;
+use common::ray::Ray;
+use common::texture::{PixelData, Pixels, TextureError};
+
+/// The value of void samples in SRTM tiles
+const SRTM_VOID: i16 = -32_768;
+
+/// The usual `NODATA_value` of ESRI ASCII grids, for grids that don't declare one
+const DEFAULT_ASC_NO_DATA: f32 = -9_999.0;
+
+/// The length of one arc-second of latitude
+const METRES_PER_ARC_SECOND: f32 = 30.87;
+
+#[derive(Debug)]
+pub enum HeightmapError {
+    /// the file couldn't be found or read
+    Asset(AssetError),
+    /// the heightmap image couldn't be read or decoded
+    Image(TextureError),
+    /// a grid needs at least 2 by 2 samples to make any triangles
+    TooSmall { path: String, columns: usize, rows: usize },
+    /// the grid has more vertices than 32-bit indices can reach
+    TooLarge { path: String, columns: usize, rows: usize },
+    /// the file isn't a valid heightmap of its format
+    Format { path: String, problem: String },
+    /// every height is void, so there's nothing to fill the voids from
+    NoData { path: String },
+    /// a height field was given a different number of vertices than its grid has
+    GridMismatch { columns: usize, rows: usize, vertices: usize },
+    /// a name that isn't one of `HeightEncoding`'s
+    UnknownEncoding { name: String },
+}
+
+impl fmt::Display for HeightmapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeightmapError::Asset(ref error) => write!(f, "failed to load heightmap: {}", error),
+            HeightmapError::Image(ref error) => write!(f, "failed to load heightmap: {}", error),
+            HeightmapError::TooSmall { ref path, columns, rows } => {
+                write!(f, "heightmap {} is {} by {} samples, but needs at least 2 by 2", path, columns, rows)
+            },
+            HeightmapError::TooLarge { ref path, columns, rows } => {
+                write!(f, "heightmap {} is {} by {} samples, too many to index with 32 bits", path, columns, rows)
+            },
+            HeightmapError::Format { ref path, ref problem } => write!(f, "invalid heightmap {}: {}", path, problem),
+            HeightmapError::NoData { ref path } => write!(f, "heightmap {} has no valid heights", path),
+            HeightmapError::GridMismatch { columns, rows, vertices } => {
+                write!(f, "{} vertices don't make a {} by {} grid", vertices, columns, rows)
+            },
+            HeightmapError::UnknownEncoding { ref name } => write!(
+                f,
+                "unknown height encoding {}, expected gray8, gray16, terrain-rgb, terrarium or custom:<scale>:<offset>",
+                name,
+            ),
+        }
+    }
+}
+
+impl Error for HeightmapError {
+    fn description(&self) -> &str {
+        match *self {
+            HeightmapError::Asset(_) => "failed to load heightmap",
+            HeightmapError::Image(_) => "failed to load heightmap image",
+            HeightmapError::TooSmall { .. } => "heightmap too small",
+            HeightmapError::TooLarge { .. } => "heightmap too large",
+            HeightmapError::Format { .. } => "invalid heightmap",
+            HeightmapError::NoData { .. } => "heightmap has no valid heights",
+            HeightmapError::GridMismatch { .. } => "vertices don't match the height field's grid",
+            HeightmapError::UnknownEncoding { .. } => "unknown height encoding",
+        }
+    }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            HeightmapError::Asset(ref error) => Some(error),
+            HeightmapError::Image(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<AssetError> for HeightmapError {
+    fn from(error: AssetError) -> Self {
+        HeightmapError::Asset(error)
+    }
+}
+
+impl From<TextureError> for HeightmapError {
+    fn from(error: TextureError) -> Self {
+        HeightmapError::Image(error)
+    }
+}
+
+/// Triangle indices into a heightmap's vertices, 16-bit when there are few enough vertices
+#[derive(Debug, Clone, PartialEq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    pub fn len(&self) -> usize {
+        match *self {
+            Indices::U16(ref indices) => indices.len(),
+            Indices::U32(ref indices) => indices.len(),
+        }
+    }
+
+    /// The type to pass to `glDrawElements`
+    pub fn gl_type(&self) -> GLenum {
+        match *self {
+            Indices::U16(_) => gl::UNSIGNED_SHORT,
+            Indices::U32(_) => gl::UNSIGNED_INT,
+        }
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        match *self {
+            Indices::U16(ref indices) => indices.len() * mem::size_of::<u16>(),
+            Indices::U32(ref indices) => indices.len() * mem::size_of::<u32>(),
+        }
+    }
+
+    /// The start of the indices, for uploading them to an element buffer
+    pub fn as_ptr(&self) -> *const c_void {
+        match *self {
+            Indices::U16(ref indices) => indices.as_ptr() as *const c_void,
+            Indices::U32(ref indices) => indices.as_ptr() as *const c_void,
+        }
+    }
+}
+
+/// Heights in metres on a regular grid, as read from a digital elevation model. The first row is
+/// the northern edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElevationGrid {
+    pub columns: usize,
+    pub rows: usize,
+    /// row by row, with voids filled in
+    pub heights: Vec<f32>,
+    /// the east-west distance between neighbouring samples, in metres
+    pub cell_width: f32,
+    /// the north-south distance between neighbouring samples, in metres
+    pub cell_depth: f32,
+}
+
+impl ElevationGrid {
+    /// Read an SRTM `.hgt` tile: square, big-endian 16-bit samples covering one degree of latitude
+    /// and longitude, with 1201 or 3601 samples per side for 3 or 1 arc-second data. The tile's
+    /// latitude, which sets the east-west spacing, comes from its file name, e.g. `N46E008.hgt`; a
+    /// name without one is taken to be at the equator.
+    pub fn read_hgt(path: &str) -> Result<ElevationGrid, HeightmapError> {
+        let bytes = assets::read(path)?;
+        let format_error = |problem: String| HeightmapError::Format { path: path.to_string(), problem: problem };
+
+        let samples = bytes.len() / 2;
+        let side = (samples as f64).sqrt().round() as usize;
+        if bytes.len() % 2 != 0 || side * side != samples {
+            return Err(format_error(format!("{} bytes isn't a square grid of 16-bit samples", bytes.len())));
+        }
+        validate_size(path, side, side)?;
+
+        let heights: Vec<Option<f32>> = bytes.chunks(2)
+            .map(|pair| ((pair[0] as u16) << 8 | pair[1] as u16) as i16)
+            .map(|sample| if sample == SRTM_VOID { None } else { Some(sample as f32) })
+            .collect();
+
+        // the tile is a degree across, split into `side - 1` cells
+        let cell_size = 3_600.0 / (side - 1) as f32 * METRES_PER_ARC_SECOND;
+        let latitude = tile_latitude(path).unwrap_or(0.0);
+        // the middle of the tile, as its name gives the southern edge
+        let middle_latitude = (latitude + 0.5).to_radians();
+
+        Ok(ElevationGrid {
+            columns: side,
+            rows: side,
+            heights: fill_voids(path, heights, side, side)?,
+            cell_width: cell_size * middle_latitude.cos(),
+            cell_depth: cell_size,
+        })
+    }
+
+    /// Read an ESRI ASCII grid (`.asc`): a header of `ncols`, `nrows`, the lower-left corner,
+    /// `cellsize` (or `dx` and `dy`) and an optional `NODATA_value`, then the values row by row
+    /// from the north. The cell size is taken to be in metres, i.e. the grid is projected.
+    pub fn read_asc(path: &str) -> Result<ElevationGrid, HeightmapError> {
+        let text = assets::read_to_string(path)?;
+        let format_error = |problem: String| HeightmapError::Format { path: path.to_string(), problem: problem };
+
+        let mut tokens = text.split_whitespace().peekable();
+        let (mut columns, mut rows) = (None, None);
+        let (mut cell_width, mut cell_depth) = (None, None);
+        let mut no_data = DEFAULT_ASC_NO_DATA;
+        // header lines are a keyword and a value; the first number starts the data
+        while let Some(&keyword) = tokens.peek() {
+            if keyword.parse::<f32>().is_ok() {
+                break;
+            }
+            tokens.next();
+            let value = match tokens.next() {
+                Some(value) => value,
+                None => return Err(format_error(format!("{} has no value", keyword))),
+            };
+            let number: f32 = value.parse()
+                .map_err(|_| format_error(format!("{} isn't a number: {}", keyword, value)))?;
+            match &keyword.to_lowercase()[..] {
+                "ncols" => columns = Some(number as usize),
+                "nrows" => rows = Some(number as usize),
+                "cellsize" => {
+                    cell_width = Some(number);
+                    cell_depth = Some(number);
+                },
+                "dx" => cell_width = Some(number),
+                "dy" => cell_depth = Some(number),
+                "nodata_value" => no_data = number,
+                // where the grid is doesn't matter for its shape
+                "xllcorner" | "yllcorner" | "xllcenter" | "yllcenter" => {},
+                _ => return Err(format_error(format!("unknown header {}", keyword))),
+            }
+        }
+
+        let (columns, rows, cell_width, cell_depth) = match (columns, rows, cell_width, cell_depth) {
+            (Some(columns), Some(rows), Some(cell_width), Some(cell_depth)) => (columns, rows, cell_width, cell_depth),
+            _ => return Err(format_error("the header needs ncols, nrows and cellsize".to_string())),
+        };
+        validate_size(path, columns, rows)?;
+
+        let mut heights = Vec::with_capacity(columns * rows);
+        for token in tokens {
+            let value: f32 = token.parse()
+                .map_err(|_| format_error(format!("{} isn't a number", token)))?;
+            heights.push(if value == no_data { None } else { Some(value) });
+        }
+        if heights.len() != columns * rows {
+            return Err(format_error(format!("expected {} values, found {}", columns * rows, heights.len())));
+        }
+
+        Ok(ElevationGrid {
+            columns: columns,
+            rows: rows,
+            heights: fill_voids(path, heights, columns, rows)?,
+            cell_width: cell_width,
+            cell_depth: cell_depth,
+        })
+    }
+
+    /// Vertices, triangle indices and grid size like `heightmap_data`'s, with the grid at its real
+    /// size in metres before `scale` is applied. The first row is at z = 0 and rows go south, so x
+    /// points east.
+    pub fn data(&self, scale: Vector3<f32>) -> (Vec<f32>, Indices, usize, usize) {
+        let size = Vector3::new(
+            scale.x * self.cell_width * (self.columns - 1) as f32,
+            scale.y,
+            scale.z * self.cell_depth * (self.rows - 1) as f32,
+        );
+        let vertices = interleaved_vertices(&self.heights, self.columns, self.rows, size);
+        let indices = indices_from_heightmap(self.rows, self.columns);
+
+        (vertices, indices, self.columns, self.rows)
+    }
+}
+
+/// Read the digital elevation model at `path`, an SRTM `.hgt` tile or an ESRI `.asc` grid, into
+/// vertices, triangle indices and grid size like `heightmap_data`'s. See `ElevationGrid::data` for
+/// `scale`.
+pub fn elevation_data(path: &str, scale: Vector3<f32>) -> Result<(Vec<f32>, Indices, usize, usize), HeightmapError> {
+    let extension = Path::new(path).extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let grid = match extension.as_ref().map(|extension| &extension[..]) {
+        Some("hgt") => ElevationGrid::read_hgt(path)?,
+        Some("asc") => ElevationGrid::read_asc(path)?,
+        _ => return Err(HeightmapError::Format {
+            path: path.to_string(),
+            problem: "elevation models must be .hgt or .asc files".to_string(),
+        }),
+    };
+    Ok(grid.data(scale))
+}
+
+/// The latitude of the southern edge of an SRTM tile named like `N46E008.hgt` or `S12W077.hgt`
+fn tile_latitude(path: &str) -> Option<f32> {
+    let name = match Path::new(path).file_stem() {
+        Some(name) => name.to_string_lossy().to_uppercase(),
+        None => return None,
+    };
+    let sign = match name.chars().next() {
+        Some('N') => 1.0,
+        Some('S') => -1.0,
+        _ => return None,
+    };
+    name.get(1..3).and_then(|degrees| degrees.parse::<f32>().ok()).map(|degrees| sign * degrees)
+}
+
+/// Replace each void with an average of the nearest valid heights left, right, above and below it,
+/// weighted by how close they are. Voids with no valid height in line with them are filled in
+/// later passes from the voids filled around them.
+fn fill_voids(path: &str, heights: Vec<Option<f32>>, columns: usize, rows: usize) -> Result<Vec<f32>, HeightmapError> {
+    let mut heights = heights;
+    while heights.iter().any(Option::is_none) {
+        let filled: Vec<Option<f32>> = (0..rows * columns).map(|index| {
+            if heights[index].is_some() {
+                return heights[index];
+            }
+            let (column, row) = ((index % columns) as isize, (index / columns) as isize);
+            let mut total = 0.0;
+            let mut total_weight = 0.0;
+            for &(step_column, step_row) in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+                let (mut neighbour_column, mut neighbour_row) = (column + step_column, row + step_row);
+                let mut distance = 1.0;
+                while neighbour_column >= 0 && neighbour_column < columns as isize
+                    && neighbour_row >= 0 && neighbour_row < rows as isize
+                {
+                    if let Some(height) = heights[neighbour_row as usize * columns + neighbour_column as usize] {
+                        total += height / distance;
+                        total_weight += 1.0 / distance;
+                        break;
+                    }
+                    neighbour_column += step_column;
+                    neighbour_row += step_row;
+                    distance += 1.0;
+                }
+            }
+            if total_weight > 0.0 { Some(total / total_weight) } else { None }
+        }).collect();
+
+        if filled.iter().filter(|height| height.is_none()).count() == heights.iter().filter(|height| height.is_none()).count() {
+            return Err(HeightmapError::NoData { path: path.to_string() });
+        }
+        heights = filled;
+    }
+    Ok(heights.into_iter().map(|height| height.unwrap_or(0.0)).collect())
+}
+
+/// How a heightmap image stores the height of each pixel
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HeightEncoding {
+    /// 8-bit grayscale, from 0 for black to 1 for white
+    Gray8,
+    /// 16-bit grayscale PNG, from 0 for black to 1 for white
+    Gray16,
+    /// Mapbox Terrain-RGB, in metres: `-10000 + (R * 65536 + G * 256 + B) * 0.1`
+    TerrainRgb,
+    /// Terrarium, as served by Mapzen and AWS terrain tiles, in metres: `R * 256 + G + B / 256 - 32768`
+    Terrarium,
+    /// `value * scale + offset` of the first channel's raw value, 0 to 255 for 8-bit images or 0
+    /// to 65535 for 16-bit ones
+    Custom { scale: f32, offset: f32 },
+}
+
+impl HeightEncoding {
+    /// Whether the heights go from 0 to 1 rather than being in metres
+    pub fn is_normalised(&self) -> bool {
+        match *self {
+            HeightEncoding::Gray8 | HeightEncoding::Gray16 => true,
+            _ => false,
+        }
+    }
+
+    /// The height of each pixel, row by row, or why these pixels can't hold this encoding
+    pub fn decode(&self, pixels: &Pixels) -> Result<Vec<f32>, &'static str> {
+        let channels = pixels.channels;
+        match (*self, &pixels.data) {
+            (HeightEncoding::Gray8, &PixelData::U8(ref data)) => {
+                Ok(data.chunks(channels).map(|pixel| pixel[0] as f32 / 255.0).collect())
+            },
+            (HeightEncoding::Gray8, _) => Err("8-bit grayscale heights need an 8-bit image"),
+            (HeightEncoding::Gray16, &PixelData::U16(ref data)) => {
+                Ok(data.chunks(channels).map(|pixel| pixel[0] as f32 / 65_535.0).collect())
+            },
+            (HeightEncoding::Gray16, _) => Err("16-bit grayscale heights need a 16-bit PNG"),
+            (HeightEncoding::TerrainRgb, &PixelData::U8(ref data)) if channels >= 3 => {
+                Ok(data.chunks(channels).map(|pixel| {
+                    -10_000.0 + ((pixel[0] as f32) * 65_536.0 + (pixel[1] as f32) * 256.0 + (pixel[2] as f32)) * 0.1
+                }).collect())
+            },
+            (HeightEncoding::Terrarium, &PixelData::U8(ref data)) if channels >= 3 => {
+                Ok(data.chunks(channels).map(|pixel| {
+                    ((pixel[0] as f32) * 256.0 + (pixel[1] as f32) + (pixel[2] as f32)/256.0) - 32_768.0
+                }).collect())
+            },
+            (HeightEncoding::TerrainRgb, _) | (HeightEncoding::Terrarium, _) => {
+                Err("RGB-encoded heights need an 8-bit RGB or RGBA image")
+            },
+            (HeightEncoding::Custom { scale, offset }, &PixelData::U8(ref data)) => {
+                Ok(data.chunks(channels).map(|pixel| pixel[0] as f32 * scale + offset).collect())
+            },
+            (HeightEncoding::Custom { scale, offset }, &PixelData::U16(ref data)) => {
+                Ok(data.chunks(channels).map(|pixel| pixel[0] as f32 * scale + offset).collect())
+            },
+            (HeightEncoding::Custom { .. }, &PixelData::F32(_)) => Err("custom heights need an 8 or 16-bit image"),
+        }
+    }
+}
+
+impl FromStr for HeightEncoding {
+    type Err = HeightmapError;
+
+    /// `gray8`, `gray16`, `terrain-rgb`, `terrarium`, or `custom:<scale>:<offset>` for a custom
+    /// encoding
+    fn from_str(name: &str) -> Result<Self, HeightmapError> {
+        let unknown = || HeightmapError::UnknownEncoding { name: name.to_string() };
+        match &name.to_lowercase()[..] {
+            "gray8" => Ok(HeightEncoding::Gray8),
+            "gray16" => Ok(HeightEncoding::Gray16),
+            "terrain-rgb" => Ok(HeightEncoding::TerrainRgb),
+            "terrarium" => Ok(HeightEncoding::Terrarium),
+            lowercase => {
+                let parts: Vec<&str> = lowercase.split(':').collect();
+                if parts.len() != 3 || parts[0] != "custom" {
+                    return Err(unknown());
+                }
+                match (parts[1].parse(), parts[2].parse()) {
+                    (Ok(scale), Ok(offset)) => Ok(HeightEncoding::Custom { scale: scale, offset: offset }),
+                    _ => Err(unknown()),
+                }
+            },
+        }
+    }
+}
+
+/// The number of floats in each of `heightmap_data`'s vertices: the position, then the surface
+/// normal, then the texture coordinates
+pub const VERTEX_FLOATS: usize = 8;
+
+/// Vertices and triangle indices for the terrain of the heightmap at `path`, one vertex per
+/// pixel, along with the number of columns and rows of vertices. The grid spans 0 to 1 along x and z, and y is the height `encoding` reads from the
+/// pixel, before each is multiplied by the matching component of `scale`. The normals are worked out from the
+/// scaled positions, so they stay correct however flat or steep the scale makes the terrain.
+/// Texture coordinates span 0 to 1 across the whole terrain.
+///
+/// Fails if the image can't be read, its pixels don't hold `encoding`, or it's less than 2 pixels
+/// wide or tall.
+pub fn heightmap_data(
+    path: &str,
+    flip_y: bool,
+    encoding: HeightEncoding,
+    scale: Vector3<f32>,
+) -> Result<(Vec<f32>, Indices, usize, usize), HeightmapError> {
+    let (heights, heightmap_height, heightmap_width) =
+        heights_from_heightmap(path, flip_y, encoding)?;
+    let vertices = interleaved_vertices(&heights, heightmap_width, heightmap_height, scale);
     let indices = indices_from_heightmap(heightmap_height, heightmap_width);
-    let flattened_vertices: Vec<f32> =
-        vertices.into_iter().flat_map(|point| point.into_iter()).collect();
 
-    return (flattened_vertices, indices);
+    Ok((vertices, indices, heightmap_width, heightmap_height))
+}
+
+/// Terrain heights on a regular grid, for finding the ground height anywhere on a heightmap's
+/// terrain after the heightmap data has been uploaded
+#[derive(Debug, Clone)]
+pub struct HeightField {
+    columns: usize,
+    rows: usize,
+    /// row by row, like the vertices they came from
+    heights: Vec<f32>,
+    min_x: f32,
+    min_z: f32,
+    cell_width: f32,
+    cell_depth: f32,
 }
 
-/// returns a tuple of the vertices, the heightmap's height, and the heightmap's width,
+impl HeightField {
+    /// Build a height field from a grid of `columns` by `rows` vertices laid out row by row, as
+    /// returned by `heightmap_data`. The grid spacing is taken from the vertices' positions.
+    pub fn from_vertices(vertices: &[f32], columns: usize, rows: usize) -> Result<Self, HeightmapError> {
+        let points: Vec<&[f32]> = vertices.chunks(VERTEX_FLOATS).collect();
+        if columns == 0 || rows == 0 || vertices.len() != columns * rows * VERTEX_FLOATS {
+            return Err(HeightmapError::GridMismatch { columns: columns, rows: rows, vertices: points.len() });
+        }
+        let (first, last) = (points[0], points[rows * columns - 1]);
+
+        Ok(HeightField {
+            columns: columns,
+            rows: rows,
+            heights: points.iter().map(|point| point[1]).collect(),
+            min_x: first[0],
+            min_z: first[2],
+            cell_width: if columns > 1 { (last[0] - first[0]) / (columns - 1) as f32 } else { 0.0 },
+            cell_depth: if rows > 1 { (last[2] - first[2]) / (rows - 1) as f32 } else { 0.0 },
+        })
+    }
+
+    /// The same height field moved by `offset`, e.g. to put it where its terrain is in the world
+    pub fn translated(mut self, offset: Vector3<f32>) -> Self {
+        self.min_x += offset.x;
+        self.min_z += offset.z;
+        for height in self.heights.iter_mut() {
+            *height += offset.y;
+        }
+        self
+    }
+
+    /// The terrain height at `(x, z)`, interpolated bilinearly between the four surrounding grid
+    /// points, or None if the point is off the edge of the grid
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        let (column, u) = match cell_position(x - self.min_x, self.cell_width, self.columns) {
+            Some(position) => position,
+            None => return None,
+        };
+        let (row, v) = match cell_position(z - self.min_z, self.cell_depth, self.rows) {
+            Some(position) => position,
+            None => return None,
+        };
+
+        let next_column = (column + 1).min(self.columns - 1);
+        let next_row = (row + 1).min(self.rows - 1);
+        let height = |column: usize, row: usize| self.heights[row * self.columns + column];
+
+        let near = height(column, row) * (1.0 - u) + height(next_column, row) * u;
+        let far = height(column, next_row) * (1.0 - u) + height(next_column, next_row) * u;
+        Some(near * (1.0 - v) + far * v)
+    }
+
+    /// Distance along the ray to where it first hits the surface, or None if it misses
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let min_height = self.heights.iter().fold(INFINITY, |min, &height| min.min(height));
+        let max_height = self.heights.iter().fold(NEG_INFINITY, |max, &height| max.max(height));
+        let bounds_min = Point3::new(self.min_x, min_height, self.min_z);
+        let bounds_max = Point3::new(
+            self.min_x + self.cell_width * (self.columns - 1) as f32,
+            max_height,
+            self.min_z + self.cell_depth * (self.rows - 1) as f32,
+        );
+        let (entry, exit) = match ray.aabb_interval(bounds_min, bounds_max) {
+            Some((entry, exit)) if exit >= 0.0 => (entry.max(0.0), exit),
+            _ => return None,
+        };
+
+        // how far the ray is above the surface; None just outside the grid's edges, where float
+        // rounding can put the clipped ray
+        let height_above_surface = |distance: f32| {
+            let point = ray.point_at(distance);
+            self.height_at(point.x, point.z).map(|height| point.y - height)
+        };
+
+        // march across the grid in steps smaller than a cell until the ray goes below the surface,
+        // then narrow down the crossing point by bisection
+        let smallest_cell = self.cell_width.min(self.cell_depth);
+        let step = if smallest_cell > 0.0 { smallest_cell / 2.0 } else { exit - entry };
+        let mut above = entry;
+        if let Some(height) = height_above_surface(above) {
+            if height <= 0.0 {
+                return Some(above);
+            }
+        }
+        while above < exit {
+            let next = (above + step).min(exit);
+            match height_above_surface(next) {
+                Some(height) if height <= 0.0 => {
+                    let mut below = next;
+                    for _ in 0..16 {
+                        let middle = (above + below) / 2.0;
+                        match height_above_surface(middle) {
+                            Some(height) if height <= 0.0 => below = middle,
+                            _ => above = middle,
+                        }
+                    }
+                    return Some(below);
+                },
+                _ => above = next,
+            }
+        }
+        None
+    }
+}
+
+/// Where `offset` falls along one axis of a grid with `count` points spaced `cell_size` apart: the
+/// index of the cell and how far across it the offset is, from 0 to 1
+fn cell_position(offset: f32, cell_size: f32, count: usize) -> Option<(usize, f32)> {
+    if count == 1 {
+        return if offset == 0.0 { Some((0, 0.0)) } else { None };
+    }
+
+    let position = offset / cell_size;
+    if !(position >= 0.0 && position <= (count - 1) as f32) {
+        return None;
+    }
+    // the far edge belongs to the last cell
+    let index = (position.floor() as usize).min(count - 2);
+    Some((index, position - index as f32))
+}
+
+/// returns a tuple of the heights row by row, the heightmap's height, and the heightmap's width,
 /// respectively
-fn vertices_from_heightmap(path: &str, flip_y: bool) -> (Vec<Vertex>, u32, u32) {
-    let mut img = image::open(&Path::new(path)).expect(&format!("Heightmap {} failed to load", path));
+fn heights_from_heightmap(
+    path: &str,
+    flip_y: bool,
+    encoding: HeightEncoding,
+) -> Result<(Vec<f32>, usize, usize), HeightmapError> {
+    let pixels = Pixels::load(path, flip_y)?;
+    let (width, height) = (pixels.width as usize, pixels.height as usize);
+    validate_size(path, width, height)?;
+    let heights = encoding.decode(&pixels)
+        .map_err(|problem| HeightmapError::Format { path: path.to_string(), problem: problem.to_string() })?;
 
-    if flip_y {
-        img = img.flipv();
+    Ok((heights, height, width))
+}
+
+/// Check that a grid makes at least one cell and that 32-bit indices can reach all its vertices
+fn validate_size(path: &str, columns: usize, rows: usize) -> Result<(), HeightmapError> {
+    if columns < 2 || rows < 2 {
+        return Err(HeightmapError::TooSmall { path: path.to_string(), columns: columns, rows: rows });
+    }
+    if columns as u64 * rows as u64 > u32::MAX as u64 + 1 {
+        return Err(HeightmapError::TooLarge { path: path.to_string(), columns: columns, rows: rows });
     }
+    Ok(())
+}
+
+/// Interleave each grid point's scaled position, its normal and its texture coordinates
+fn interleaved_vertices(heights: &[f32], columns: usize, rows: usize, scale: Vector3<f32>) -> Vec<f32> {
+    let height = |column: usize, row: usize| heights[row * columns + column] * scale.y;
+    let cell_width = scale.x / (columns - 1) as f32;
+    let cell_depth = scale.z / (rows - 1) as f32;
 
-    let (width, height) = img.dimensions();
-    let vertices = img.to_rgb().enumerate_pixels().map(|(x, y, pixel)| {
-        let channels = pixel.channels();
-        let terrain_height_value = ((channels[0] as f32) * 256.0 + (channels[1] as f32) + (channels[2] as f32)/256.0) - 32_768.0;
+    let mut vertices = Vec::with_capacity(columns * rows * VERTEX_FLOATS);
+    for row in 0..rows {
+        for column in 0..columns {
+            let u = (column as f32) / ((columns - 1) as f32);
+            let v = (row as f32) / ((rows - 1) as f32);
 
-        vec![
-            (x as f32)/((width - 1) as f32),
-            terrain_height_value,
-            (y as f32)/((height - 1) as f32),
-        ]
-    }).collect();
+            // central differences, falling back to one-sided ones at the edges
+            let (left, right) = (column.saturating_sub(1), (column + 1).min(columns - 1));
+            let (near, far) = (row.saturating_sub(1), (row + 1).min(rows - 1));
+            let slope_x = slope(height(left, row), height(right, row), (right - left) as f32 * cell_width);
+            let slope_z = slope(height(column, near), height(column, far), (far - near) as f32 * cell_depth);
+            let normal = Vector3::new(-slope_x, 1.0, -slope_z).normalize();
 
-    (vertices, height, width)
+            vertices.extend_from_slice(&[
+                u * scale.x, height(column, row), v * scale.z,
+                normal.x, normal.y, normal.z,
+                u, v,
+            ]);
+        }
+    }
+    vertices
 }
 
-fn indices_from_heightmap(height: u32, width: u32) -> Vec<i32> {
+/// How much the height rises per unit of distance between two samples `distance` apart
+fn slope(from: f32, to: f32, distance: f32) -> f32 {
+    if distance > 0.0 { (to - from) / distance } else { 0.0 }
+}
+
+/// Two triangles for each cell of a grid of at least 2 by 2 vertices, with 16-bit indices if they
+/// reach every vertex
+fn indices_from_heightmap(height: usize, width: usize) -> Indices {
     let triangle_count = 2 * (width - 1) * (height - 1);
-    let mut indices: Vec<i32> = Vec::with_capacity((triangle_count * 3) as usize);
+    let mut indices: Vec<u32> = Vec::with_capacity(triangle_count * 3);
     for i_usize in 0..(width * (height - 1)) {
-        let i = i_usize as i32;
+        let i = i_usize as u32;
 
         if (i_usize + 1) % width == 0 {
             continue;
@@ -55,12 +674,16 @@ fn indices_from_heightmap(height: u32, width: u32) -> Vec<i32> {
 
         indices.push(i);
         indices.push(i + 1);
-        indices.push(i + width as i32);
+        indices.push(i + width as u32);
 
         indices.push(i + 1);
-        indices.push(i + width as i32 + 1);
-        indices.push(i + width as i32);
+        indices.push(i + width as u32 + 1);
+        indices.push(i + width as u32);
     }
 
-    indices
+    if width * height <= u16::MAX as usize + 1 {
+        Indices::U16(indices.into_iter().map(|index| index as u16).collect())
+    } else {
+        Indices::U32(indices)
+    }
 }
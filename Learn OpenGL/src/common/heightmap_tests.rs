@@ -1,9 +1,28 @@
 use super::*;
 
+/// The path of a file in `test/heightmap`, independent of the folder the tests run from
+fn fixture(name: &str) -> String {
+    format!("{}/test/heightmap/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+/// The `x, y, z` of each of `heightmap_data`'s vertices
+fn positions(vertices: &[f32]) -> Vec<f32> {
+    vertices.chunks(VERTEX_FLOATS).flat_map(|vertex| vertex[..3].to_vec()).collect()
+}
+
+fn normals(vertices: &[f32]) -> Vec<Vector3<f32>> {
+    vertices.chunks(VERTEX_FLOATS).map(|vertex| Vector3::new(vertex[3], vertex[4], vertex[5])).collect()
+}
+
+fn assert_close(actual: Vector3<f32>, expected: Vector3<f32>) {
+    assert!((actual - expected).magnitude() < 1e-5, "expected {:?}, got {:?}", expected, actual);
+}
+
 #[test]
 fn heightmap_for_square_peak() {
-    let (actual_vertices, actual_indices) =
-        heightmap_data("test/heightmap/test_heightmap.png", true);
+    let (vertices, actual_indices, _, _) =
+        heightmap_data(&fixture("test_heightmap.png"), true, HeightEncoding::Terrarium, Vector3::new(1.0, 1.0, 1.0)).unwrap();
+    let actual_vertices = positions(&vertices);
 
     let expected_vertices: Vec<f32> = vec![
         // row 1
@@ -19,7 +38,7 @@ fn heightmap_for_square_peak() {
         0.5,  -32_513.0,  1.0,
         1.0,  -32_513.0,  1.0,
     ];
-    let expected_indices: Vec<i32> = vec![
+    let expected_indices: Vec<u16> = vec![
         // strip 1
         0, 1, 3,
         1, 4, 3,
@@ -36,13 +55,15 @@ fn heightmap_for_square_peak() {
     ];
 
     assert_eq!(actual_vertices, expected_vertices);
-    assert_eq!(actual_indices, expected_indices);
+    assert_eq!(actual_indices, Indices::U16(expected_indices));
 }
 
 #[test]
 fn heightmap_for_rectangular_peak() {
-    let (actual_vertices, actual_indices) =
-        heightmap_data("test/heightmap/test_heightmap_2.png", true);
+    let (vertices, actual_indices, columns, rows) =
+        heightmap_data(&fixture("test_heightmap_2.png"), true, HeightEncoding::Terrarium, Vector3::new(1.0, 1.0, 1.0)).unwrap();
+    assert_eq!((columns, rows), (6, 3));
+    let actual_vertices = positions(&vertices);
 
     let expected_vertices: Vec<f32> = vec![
         // row 1
@@ -67,7 +88,7 @@ fn heightmap_for_rectangular_peak() {
         0.8,   18632.78125,     1.0,
         1.0,  -7067.609375,     1.0,
     ];
-    let expected_indices: Vec<i32> = vec![
+    let expected_indices: Vec<u16> = vec![
         // strip 1
         0, 1, 6,
         1, 7, 6,
@@ -102,5 +123,357 @@ fn heightmap_for_rectangular_peak() {
     ];
 
     assert_eq!(actual_vertices, expected_vertices);
-    assert_eq!(actual_indices, expected_indices);
+    assert_eq!(actual_indices, Indices::U16(expected_indices));
+}
+
+#[test]
+fn normals_for_square_peak() {
+    // the peak is one unit above its surroundings and the grid points are one unit apart
+    let (vertices, _, _, _) = heightmap_data(
+        &fixture("test_heightmap.png"), true, HeightEncoding::Terrarium, Vector3::new(2.0, 1.0 / 65_025.0, 2.0)
+    ).unwrap();
+    let normals = normals(&vertices);
+    let up = Vector3::new(0.0, 1.0, 0.0);
+    let diagonal = 0.5f32.sqrt();
+
+    let expected_normals = vec![
+        // row 1: the corners only see their flat neighbours, the middle rises toward the peak
+        up, Vector3::new(0.0, diagonal, -diagonal), up,
+        // row 2: the peak's slopes on either side cancel out
+        Vector3::new(-diagonal, diagonal, 0.0), up, Vector3::new(diagonal, diagonal, 0.0),
+        // row 3
+        up, Vector3::new(0.0, diagonal, diagonal), up,
+    ];
+    for (&actual, &expected) in normals.iter().zip(expected_normals.iter()) {
+        assert_close(actual, expected);
+    }
+}
+
+#[test]
+fn normals_account_for_horizontal_scale() {
+    // the same peak with the grid points two units apart is half as steep
+    let (vertices, _, _, _) = heightmap_data(
+        &fixture("test_heightmap.png"), true, HeightEncoding::Terrarium, Vector3::new(4.0, 1.0 / 65_025.0, 4.0)
+    ).unwrap();
+    let normals = normals(&vertices);
+
+    assert_close(normals[3], Vector3::new(-1.0, 2.0, 0.0).normalize());
+    assert_close(normals[1], Vector3::new(0.0, 2.0, -1.0).normalize());
+}
+
+#[test]
+fn normals_for_rectangular_peak_are_unit_length_and_symmetric() {
+    let (vertices, _, _, _) = heightmap_data(
+        &fixture("test_heightmap_2.png"), true, HeightEncoding::Terrarium, Vector3::new(100.0, 1.0 / 50.0, 100.0)
+    ).unwrap();
+    let normals = normals(&vertices);
+    let columns = 6;
+
+    for normal in normals.iter() {
+        assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+        assert!(normal.y > 0.0);
+    }
+    // the peak is symmetric about the middle of each row
+    for row in 0..3 {
+        for column in 0..columns {
+            let normal = normals[row * columns + column];
+            let mirrored = normals[row * columns + columns - 1 - column];
+            assert_close(normal, Vector3::new(-mirrored.x, mirrored.y, mirrored.z));
+        }
+    }
+    // the top of the ridge is level across its width
+    assert!(normals[columns + 2].x.abs() < normals[columns + 1].x.abs());
+}
+
+#[test]
+fn texture_coordinates_span_the_terrain() {
+    let (vertices, _, _, _) = heightmap_data(
+        &fixture("test_heightmap_2.png"), true, HeightEncoding::Terrarium, Vector3::new(100.0, 1.0, 50.0)
+    ).unwrap();
+    let texture_coordinates: Vec<(f32, f32)> = vertices.chunks(VERTEX_FLOATS)
+        .map(|vertex| (vertex[6], vertex[7]))
+        .collect();
+
+    assert_eq!(texture_coordinates[0], (0.0, 0.0));
+    assert_eq!(texture_coordinates[5], (1.0, 0.0));
+    assert_eq!(texture_coordinates[8], (0.4, 0.5));
+    assert_eq!(texture_coordinates[17], (1.0, 1.0));
+    // positions are scaled, texture coordinates aren't
+    assert_eq!(&vertices[17 * VERTEX_FLOATS..17 * VERTEX_FLOATS + 3], &[100.0, -7067.609375, 50.0]);
+}
+
+#[test]
+fn height_encodings() {
+    let rgb = Pixels {
+        path: "rgb".to_string(),
+        width: 2,
+        height: 1,
+        channels: 3,
+        data: PixelData::U8(vec![1, 134, 160, 128, 0, 128]),
+    };
+    let terrain_rgb = HeightEncoding::TerrainRgb.decode(&rgb).unwrap();
+    assert_eq!(terrain_rgb[0], 0.0);
+    assert!((terrain_rgb[1] - 828_873.6).abs() < 0.1);
+    assert_eq!(HeightEncoding::Terrarium.decode(&rgb), Ok(vec![-32_377.375, 0.5]));
+    assert_eq!(HeightEncoding::Gray8.decode(&rgb), Ok(vec![1.0 / 255.0, 128.0 / 255.0]));
+    assert_eq!(HeightEncoding::Custom { scale: 2.0, offset: -1.0 }.decode(&rgb), Ok(vec![1.0, 255.0]));
+    assert!(HeightEncoding::Gray16.decode(&rgb).is_err());
+
+    let gray_16 = Pixels {
+        path: "gray_16".to_string(),
+        width: 2,
+        height: 1,
+        channels: 1,
+        data: PixelData::U16(vec![0, 65_535]),
+    };
+    assert_eq!(HeightEncoding::Gray16.decode(&gray_16), Ok(vec![0.0, 1.0]));
+    assert_eq!(HeightEncoding::Custom { scale: 0.5, offset: 10.0 }.decode(&gray_16), Ok(vec![10.0, 32_777.5]));
+    assert!(HeightEncoding::Terrarium.decode(&gray_16).is_err());
+    assert!(HeightEncoding::Gray8.decode(&gray_16).is_err());
+}
+
+#[test]
+fn sixteen_bit_grayscale_heightmap_keeps_every_bit() {
+    // 3 by 2 pixels, most of them too high or too precise for 8 bits
+    let heights = |flip_y| {
+        let (vertices, _, columns, rows) = heightmap_data(
+            &fixture("test_heightmap_gray_16.png"), flip_y, HeightEncoding::Gray16, Vector3::new(1.0, 65_535.0, 1.0)
+        ).unwrap();
+        assert_eq!((columns, rows), (3, 2));
+        vertices.chunks(VERTEX_FLOATS).map(|vertex| vertex[1]).collect::<Vec<f32>>()
+    };
+
+    for &(flip_y, expected) in [
+        (false, [0.0, 300.0, 65_535.0, 1_000.0, 32_768.0, 256.0]),
+        (true, [1_000.0, 32_768.0, 256.0, 0.0, 300.0, 65_535.0]),
+    ].iter() {
+        let actual = heights(flip_y);
+        for (&height, &expected_height) in actual.iter().zip(expected.iter()) {
+            assert!((height - expected_height).abs() < 1e-2, "expected {:?}, got {:?}", expected, actual);
+        }
+    }
+}
+
+#[test]
+fn height_encodings_are_parsed_from_their_names() {
+    assert_eq!("gray8".parse::<HeightEncoding>().unwrap(), HeightEncoding::Gray8);
+    assert_eq!("Gray16".parse::<HeightEncoding>().unwrap(), HeightEncoding::Gray16);
+    assert_eq!("terrain-rgb".parse::<HeightEncoding>().unwrap(), HeightEncoding::TerrainRgb);
+    assert_eq!("terrarium".parse::<HeightEncoding>().unwrap(), HeightEncoding::Terrarium);
+    assert_eq!(
+        "custom:0.1:-100".parse::<HeightEncoding>().unwrap(),
+        HeightEncoding::Custom { scale: 0.1, offset: -100.0 }
+    );
+    assert!(HeightEncoding::Gray16.is_normalised());
+    assert!(!HeightEncoding::Custom { scale: 1.0, offset: 0.0 }.is_normalised());
+
+    for name in ["", "gray", "custom", "custom:1", "custom:1:x", "custom:1:2:3", "scaled:1:2"].iter() {
+        match name.parse::<HeightEncoding>() {
+            Err(HeightmapError::UnknownEncoding { name: ref unknown }) => assert_eq!(unknown, name),
+            result => panic!("expected {:?} to be unknown, got {:?}", name, result),
+        }
+    }
+}
+
+#[test]
+fn srtm_tile_fills_voids_and_reads_spacing() {
+    let grid = ElevationGrid::read_hgt(&fixture("N60E010.hgt")).unwrap();
+
+    assert_eq!((grid.columns, grid.rows), (4, 4));
+    // each void is surrounded by four valid heights one sample away
+    assert_eq!(grid.heights, vec![
+        100.0, 110.0, 120.0, 130.0,
+        200.0, 210.0, 220.0, 230.0,
+        300.0, 310.0, 320.0, 330.0,
+        400.0, 410.0, 420.0, 430.0,
+    ]);
+    // 4 samples a side make 1200 arc-second cells, which are narrower east-west at 60.5 degrees north
+    let cell_size = 1_200.0 * 30.87;
+    assert!((grid.cell_depth - cell_size).abs() < 1e-2);
+    assert!((grid.cell_width - cell_size * 60.5f32.to_radians().cos()).abs() < 1e-2);
+}
+
+#[test]
+fn esri_ascii_grid_fills_voids_without_valid_neighbours_in_line() {
+    let grid = ElevationGrid::read_asc(&fixture("test_grid.asc")).unwrap();
+
+    assert_eq!((grid.columns, grid.rows), (3, 3));
+    assert_eq!((grid.cell_width, grid.cell_depth), (10.0, 10.0));
+    // the middle is only filled once the voids around it have been
+    assert_eq!(grid.heights, vec![
+        10.0, 20.0, 30.0,
+        40.0, 50.0, 60.0,
+        70.0, 80.0, 90.0,
+    ]);
+}
+
+#[test]
+fn elevation_data_matches_heightmap_data_layout() {
+    let (vertices, indices, columns, rows) = elevation_data(&fixture("test_grid.asc"), Vector3::new(1.0, 1.0, 1.0)).unwrap();
+    assert_eq!((columns, rows), (3, 3));
+    let (_, heightmap_indices, _, _) = heightmap_data(
+        &fixture("test_heightmap.png"), true, HeightEncoding::Terrarium, Vector3::new(1.0, 1.0, 1.0)
+    ).unwrap();
+
+    // positions are at the grid's real size in metres
+    assert_eq!(positions(&vertices), vec![
+         0.0, 10.0,  0.0,   10.0, 20.0,  0.0,   20.0, 30.0,  0.0,
+         0.0, 40.0, 10.0,   10.0, 50.0, 10.0,   20.0, 60.0, 10.0,
+         0.0, 70.0, 20.0,   10.0, 80.0, 20.0,   20.0, 90.0, 20.0,
+    ]);
+    assert_eq!(indices, heightmap_indices);
+    // an even slope of 1 along x and 3 along z
+    assert_close(normals(&vertices)[4], Vector3::new(-1.0, 1.0, -3.0).normalize());
+}
+
+#[test]
+fn elevation_grid_of_only_voids_is_an_error() {
+    match fill_voids("voids", vec![None; 4], 2, 2) {
+        Err(HeightmapError::NoData { .. }) => {},
+        result => panic!("expected no data, got {:?}", result),
+    }
+}
+
+fn terrarium_heightmap(path: &str) -> Result<(Vec<f32>, Indices, usize, usize), HeightmapError> {
+    heightmap_data(path, true, HeightEncoding::Terrarium, Vector3::new(1.0, 1.0, 1.0))
+}
+
+#[test]
+fn heightmap_one_pixel_wide_or_tall_is_too_small() {
+    for &(path, expected_columns, expected_rows) in [
+        ("test_heightmap_1_wide.png", 1, 3),
+        ("test_heightmap_1_tall.png", 3, 1),
+        ("test_heightmap_1_pixel.png", 1, 1),
+    ].iter() {
+        match terrarium_heightmap(&fixture(path)) {
+            Err(HeightmapError::TooSmall { columns, rows, .. }) => {
+                assert_eq!((columns, rows), (expected_columns, expected_rows));
+            },
+            result => panic!("expected {} to be too small, got {:?}", path, result),
+        }
+    }
+}
+
+#[test]
+fn heightmap_that_cannot_be_read_is_an_error() {
+    match terrarium_heightmap(&fixture("missing.png")) {
+        Err(HeightmapError::Image(TextureError::Asset(AssetError::NotFound { .. }))) => {},
+        result => panic!("expected a missing image, got {:?}", result),
+    }
+    match terrarium_heightmap(&fixture("test_grid.asc")) {
+        Err(HeightmapError::Image(TextureError::Asset(AssetError::Image { .. }))) => {},
+        result => panic!("expected an undecodable image, got {:?}", result),
+    }
+    // a grayscale encoding can't read 8-bit RGB as 16-bit
+    match heightmap_data(&fixture("test_heightmap_2.png"), true, HeightEncoding::Gray16, Vector3::new(1.0, 1.0, 1.0)) {
+        Err(HeightmapError::Format { .. }) => {},
+        result => panic!("expected a format error, got {:?}", result),
+    }
+}
+
+#[test]
+fn heightmap_too_large_for_32_bit_indices() {
+    assert!(validate_size("large", 65_536, 65_536).is_ok());
+    match validate_size("large", 65_536, 65_537) {
+        Err(HeightmapError::TooLarge { columns, rows, .. }) => assert_eq!((columns, rows), (65_536, 65_537)),
+        result => panic!("expected too large, got {:?}", result),
+    }
+}
+
+#[test]
+fn indices_are_16_bit_while_every_vertex_fits() {
+    match indices_from_heightmap(256, 256) {
+        Indices::U16(indices) => assert_eq!(indices.iter().cloned().max(), Some(65_535)),
+        indices => panic!("expected 16-bit indices, got {:?}", indices.gl_type()),
+    }
+    match indices_from_heightmap(257, 256) {
+        Indices::U32(indices) => {
+            assert_eq!(indices.len(), 2 * 255 * 256 * 3);
+            assert_eq!(indices.iter().cloned().max(), Some(257 * 256 - 1));
+        },
+        indices => panic!("expected 32-bit indices, got {:?}", indices.gl_type()),
+    }
+}
+
+/// A 3 by 3 grid two units across with a peak two units high in the middle, moved by `offset`
+fn peak_height_field(offset: Vector3<f32>) -> HeightField {
+    let heights = vec![
+        0.0, 0.0, 0.0,
+        0.0, 2.0, 0.0,
+        0.0, 0.0, 0.0,
+    ];
+    let vertices = interleaved_vertices(&heights, 3, 3, Vector3::new(2.0, 1.0, 2.0));
+    HeightField::from_vertices(&vertices, 3, 3).unwrap().translated(offset)
+}
+
+#[test]
+fn height_field_interpolates_inside_cells() {
+    let field = peak_height_field(Vector3::new(10.0, 1.0, 20.0));
+    assert_eq!(field.height_at(10.0, 20.0), Some(1.0));
+    assert_eq!(field.height_at(11.0, 21.0), Some(3.0));
+    // halfway along an edge of the peak's cells, and a quarter of the way into a cell from it
+    assert_eq!(field.height_at(10.5, 21.0), Some(2.0));
+    assert_eq!(field.height_at(11.5, 21.5), Some(1.0 + 2.0 * 0.25));
+}
+
+#[test]
+fn height_field_edges_belong_to_the_last_cells() {
+    let field = peak_height_field(Vector3::new(10.0, 1.0, 20.0));
+    assert_eq!(field.height_at(12.0, 22.0), Some(1.0));
+    assert_eq!(field.height_at(12.0, 21.0), Some(1.0));
+    assert_eq!(field.height_at(11.0, 22.0), Some(1.0));
+
+    assert_eq!(field.height_at(9.9, 21.0), None);
+    assert_eq!(field.height_at(12.1, 21.0), None);
+    assert_eq!(field.height_at(11.0, 19.9), None);
+    assert_eq!(field.height_at(11.0, 22.1), None);
+}
+
+#[test]
+fn height_field_needs_vertices_for_its_whole_grid() {
+    let (vertices, _, columns, rows) = terrarium_heightmap(&fixture("test_heightmap_2.png")).unwrap();
+    assert!(HeightField::from_vertices(&vertices, columns, rows).is_ok());
+    // the wrong way around still has the right number of vertices
+    assert!(HeightField::from_vertices(&vertices, rows, columns).is_ok());
+
+    for &(columns, rows) in [(6, 4), (5, 3), (0, 3), (0, 0)].iter() {
+        match HeightField::from_vertices(&vertices, columns, rows) {
+            Err(HeightmapError::GridMismatch { vertices, .. }) => assert_eq!(vertices, 18),
+            result => panic!("expected a {} by {} grid to be rejected, got {:?}", columns, rows, result),
+        }
+    }
+    match HeightField::from_vertices(&[], 0, 0) {
+        Err(HeightmapError::GridMismatch { vertices, .. }) => assert_eq!(vertices, 0),
+        result => panic!("expected an empty grid to be rejected, got {:?}", result),
+    }
+}
+
+#[test]
+fn ray_hits_a_known_peak() {
+    let field = peak_height_field(Vector3::new(0.0, 0.0, 0.0));
+
+    // straight down onto the top of the peak
+    let ray = Ray::new(Point3::new(1.0, 5.0, 1.0), Vector3::new(0.0, -1.0, 0.0));
+    let distance = field.intersect_ray(&ray).unwrap();
+    assert!((distance - 3.0).abs() < 1e-4, "expected 3, got {}", distance);
+
+    // level with the middle of the peak's slope, which it reaches halfway to the top
+    let ray = Ray::new(Point3::new(-1.0, 1.0, 1.0), Vector3::new(1.0, 0.0, 0.0));
+    let distance = field.intersect_ray(&ray).unwrap();
+    assert!((ray.point_at(distance).x - 0.5).abs() < 1e-3, "hit at {:?}", ray.point_at(distance));
+}
+
+#[test]
+fn ray_misses_a_known_peak() {
+    let field = peak_height_field(Vector3::new(0.0, 0.0, 0.0));
+
+    // above the peak
+    let ray = Ray::new(Point3::new(-1.0, 2.5, 1.0), Vector3::new(1.0, 0.0, 0.0));
+    assert_eq!(field.intersect_ray(&ray), None);
+    // pointing away from it
+    let ray = Ray::new(Point3::new(1.0, 5.0, 1.0), Vector3::new(0.0, 1.0, 0.0));
+    assert_eq!(field.intersect_ray(&ray), None);
+    // beside the grid
+    let ray = Ray::new(Point3::new(3.0, 5.0, 1.0), Vector3::new(0.0, -1.0, 0.0));
+    assert_eq!(field.intersect_ray(&ray), None);
 }
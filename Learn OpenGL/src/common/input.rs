@@ -5,12 +5,11 @@ extern crate gl;
 extern crate glfw;
 use self::glfw::{Key, Action};
 
-use common::camera::{Camera, CameraMovement};
+use common::camera::Camera;
+use common::camera_controller::CameraController;
 
 pub fn process_events(events: &Receiver<(f64, glfw::WindowEvent)>,
-                  first_mouse: &mut bool,
-                  last_x: &mut f32,
-                  last_y: &mut f32,
+                  camera_controller: &mut CameraController,
                   camera: &mut Camera) {
     for (_, event) in glfw::flush_messages(events) {
         match event {
@@ -18,53 +17,79 @@ pub fn process_events(events: &Receiver<(f64, glfw::WindowEvent)>,
                 // make sure the viewport matches the new window dimensions; note that width and
                 // height will be significantly larger than specified on retina displays.
                 unsafe { gl::Viewport(0, 0, width, height) }
+                camera.resize_viewport(width as u32, height as u32);
             }
-            glfw::WindowEvent::CursorPos(xpos, ypos) => {
-                let (xpos, ypos) = (xpos as f32, ypos as f32);
-                if *first_mouse {
-                    *last_x = xpos;
-                    *last_y = ypos;
-                    *first_mouse = false;
-                }
-
-                let xoffset = xpos - *last_x;
-                let yoffset = *last_y - ypos; // reversed since y-coordinates go from bottom to top
-
-                *last_x = xpos;
-                *last_y = ypos;
-
-                camera.process_mouse_movement(xoffset, yoffset, true);
-            }
-            glfw::WindowEvent::Scroll(_xoffset, yoffset) => {
-                camera.process_scroll(yoffset as f32);
-            }
-            _ => {}
+            _ => camera_controller.process_event(&event, camera),
         }
     }
 }
 
-pub fn process_input(window: &mut glfw::Window, delta_time: f32, camera: &mut Camera) {
+pub fn process_input(window: &mut glfw::Window,
+                  delta_time: f32,
+                  camera_controller: &mut CameraController,
+                  camera: &mut Camera) {
     if window.get_key(Key::Escape) == Action::Press {
         window.set_should_close(true)
     }
 
-    if window.get_key(Key::W) == Action::Press {
-        camera.process_keyboard(CameraMovement::Forward, delta_time);
-    }
-    if window.get_key(Key::S) == Action::Press {
-        camera.process_keyboard(CameraMovement::Backward, delta_time);
-    }
-    if window.get_key(Key::A) == Action::Press {
-        camera.process_keyboard(CameraMovement::TurnLeft, delta_time);
+    camera_controller.process_input(window, delta_time, camera);
+}
+
+/// Reports clicks of the left mouse button. A press that moves the cursor before it's released is
+/// a drag rather than a click, so clicking doesn't get in the way of controllers that drag the
+/// camera around.
+pub struct ClickDetector {
+    press_position: Option<(f64, f64)>,
+}
+
+impl ClickDetector {
+    pub fn new() -> Self {
+        ClickDetector { press_position: None }
     }
-    if window.get_key(Key::D) == Action::Press {
-        camera.process_keyboard(CameraMovement::TurnRight, delta_time);
+
+    /// Call once per frame; returns the cursor position when a click finishes
+    pub fn update(&mut self, window: &glfw::Window) -> Option<(f64, f64)> {
+        // how far, in screen coordinates, the cursor can wobble during a click
+        const MAX_CLICK_DISTANCE: f64 = 3.0;
+
+        let cursor_position = window.get_cursor_pos();
+        if window.get_mouse_button(glfw::MouseButtonLeft) == Action::Press {
+            if self.press_position.is_none() {
+                self.press_position = Some(cursor_position);
+            }
+            return None;
+        }
+
+        match self.press_position.take() {
+            Some((press_x, press_y)) => {
+                let (x, y) = cursor_position;
+                let distance = ((x - press_x).powi(2) + (y - press_y).powi(2)).sqrt();
+                if distance <= MAX_CLICK_DISTANCE {
+                    Some(cursor_position)
+                } else {
+                    None
+                }
+            },
+            None => None,
+        }
     }
-    if window.get_key(Key::Q) == Action::Press {
-        camera.process_keyboard(CameraMovement::StrafeLeft, delta_time);
+}
+
+/// Reports presses of a key, once each rather than every frame it's held down
+pub struct KeyPressDetector {
+    key: Key,
+    pressed: bool,
+}
+
+impl KeyPressDetector {
+    pub fn new(key: Key) -> Self {
+        KeyPressDetector { key: key, pressed: false }
     }
-    if window.get_key(Key::E) == Action::Press {
-        camera.process_keyboard(CameraMovement::StrafeRight, delta_time);
+
+    /// Call once per frame; returns true on the frame the key goes down
+    pub fn update(&mut self, window: &glfw::Window) -> bool {
+        let was_pressed = self.pressed;
+        self.pressed = window.get_key(self.key) == Action::Press;
+        self.pressed && !was_pressed
     }
 }
-
@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::fmt;
+
+use common::heightmap::HeightmapError;
+use common::shader::ShaderError;
+use common::texture::TextureError;
+
+/// Anything that can go wrong building a scene element from its assets
+#[derive(Debug)]
+pub enum LoadError {
+    Shader(ShaderError),
+    Texture(TextureError),
+    Heightmap(HeightmapError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Shader(ref error) => write!(f, "{}", error),
+            LoadError::Texture(ref error) => write!(f, "{}", error),
+            LoadError::Heightmap(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn description(&self) -> &str {
+        match *self {
+            LoadError::Shader(_) => "failed to build shader program",
+            LoadError::Texture(_) => "failed to load texture",
+            LoadError::Heightmap(_) => "failed to load heightmap",
+        }
+    }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            LoadError::Shader(ref error) => Some(error),
+            LoadError::Texture(ref error) => Some(error),
+            LoadError::Heightmap(ref error) => Some(error),
+        }
+    }
+}
+
+impl From<ShaderError> for LoadError {
+    fn from(error: ShaderError) -> Self {
+        LoadError::Shader(error)
+    }
+}
+
+impl From<TextureError> for LoadError {
+    fn from(error: TextureError) -> Self {
+        LoadError::Texture(error)
+    }
+}
+
+impl From<HeightmapError> for LoadError {
+    fn from(error: HeightmapError) -> Self {
+        LoadError::Heightmap(error)
+    }
+}
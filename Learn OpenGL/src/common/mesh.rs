@@ -0,0 +1,76 @@
+use std::mem;
+use std::os::raw::c_void;
+
+use gl;
+use gl::types::*;
+
+use common::shader::{Shader, ShaderError};
+use common::vertex_layout::VertexLayout;
+
+/// Triangles in a vertex buffer of interleaved floats, with the vertex array that reads them. Both
+/// are deleted when it's dropped.
+pub struct Mesh {
+    vao: GLuint,
+    vbo: GLuint,
+    layout: VertexLayout,
+    vertex_count: GLsizei,
+}
+
+impl Mesh {
+    /// Upload `vertices`, split between attributes as `layout` describes. Needs a current GL
+    /// context.
+    pub fn new(vertices: &[f32], layout: VertexLayout) -> Self {
+        let floats_per_vertex = layout.stride() / mem::size_of::<GLfloat>();
+        assert!(
+            floats_per_vertex > 0 && vertices.len() % floats_per_vertex == 0,
+            "{} floats don't make whole vertices of {} floats",
+            vertices.len(), floats_per_vertex,
+        );
+
+        unsafe {
+            let (mut vao, mut vbo) = (0, 0);
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindVertexArray(vao);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(gl::ARRAY_BUFFER,
+                           (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                           vertices.as_ptr() as *const c_void,
+                           gl::STATIC_DRAW);
+
+            layout.apply();
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+
+            Mesh {
+                vao: vao,
+                vbo: vbo,
+                layout: layout,
+                vertex_count: (vertices.len() / floats_per_vertex) as GLsizei,
+            }
+        }
+    }
+
+    /// Check that the mesh has every attribute `shader` reads, where it reads them
+    pub fn validate(&self, shader: &Shader) -> Result<(), ShaderError> {
+        self.layout.validate(shader)
+    }
+
+    /// Draw every triangle with the program in use
+    pub unsafe fn draw(&self) {
+        gl::BindVertexArray(self.vao);
+        gl::DrawArrays(gl::TRIANGLES, 0, self.vertex_count);
+    }
+}
+
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
@@ -1,6 +1,23 @@
+pub mod asset_cache;
+pub mod assets;
 pub mod camera;
+pub mod camera_controller;
+pub mod camera_path;
+pub mod embedded_assets;
+pub mod frustum;
 pub mod heightmap;
 pub mod input;
+pub mod load_error;
 pub mod macros;
+pub mod mesh;
+pub mod program_cache;
+pub mod ray;
+pub mod reloadable_shader;
 pub mod shader;
+pub mod shader_builder;
+pub mod shader_preprocessor;
+pub mod skybox;
 pub mod texture;
+pub mod uniform;
+pub mod uniform_buffer;
+pub mod vertex_layout;
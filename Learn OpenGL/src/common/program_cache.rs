@@ -0,0 +1,165 @@
+use std::env;
+use std::ffi::CStr;
+use std::fs;
+use std::os::raw::c_void;
+use std::path::PathBuf;
+use std::ptr;
+
+use gl;
+use gl::types::*;
+
+use common::shader::ShaderStage;
+use common::shader_preprocessor::ShaderSource;
+
+/// The folder to keep program binaries in. The cache is only used when this is set and not empty.
+pub const PROGRAM_CACHE_FOLDER_VARIABLE: &'static str = "LEARN_OPENGL_PROGRAM_CACHE";
+
+/// Linked program binaries saved on disk (`ARB_get_program_binary`), so programs that were built
+/// before aren't compiled again at startup.
+///
+/// Each binary is keyed by a hash of the preprocessed source of every stage and the driver's
+/// vendor, renderer and version strings, since drivers only accept binaries they produced
+/// themselves. A driver can still reject a binary, e.g. after an update that didn't change its
+/// version string, in which case it's deleted and the program is compiled from source as usual.
+///
+/// The cache is off unless `LEARN_OPENGL_PROGRAM_CACHE` names a folder, which is created if needed.
+/// Deleting the folder clears the cache.
+pub struct ProgramCache {
+    folder: PathBuf,
+    driver: Vec<String>,
+}
+
+impl ProgramCache {
+    /// None if the driver can't save program binaries or the cache is turned off. Needs a current
+    /// GL context.
+    pub unsafe fn new() -> Option<Self> {
+        let folder = match env::var_os(PROGRAM_CACHE_FOLDER_VARIABLE) {
+            Some(ref folder) if !folder.is_empty() => PathBuf::from(folder),
+            _ => return None,
+        };
+        if !gl::GetProgramBinary::is_loaded() || !gl::ProgramBinary::is_loaded() {
+            return None;
+        }
+        let mut format_count = 0;
+        gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut format_count);
+        if format_count == 0 {
+            return None;
+        }
+
+        Some(ProgramCache {
+            folder: folder,
+            driver: vec![gl_string(gl::VENDOR), gl_string(gl::RENDERER), gl_string(gl::VERSION)],
+        })
+    }
+
+    /// The key of the program linked from `sources` on this driver
+    pub fn key<'a, I>(&self, sources: I) -> u64
+        where I: IntoIterator<Item = (ShaderStage, &'a ShaderSource)>
+    {
+        let driver: Vec<&str> = self.driver.iter().map(|string| &string[..]).collect();
+        let stages: Vec<(ShaderStage, &str)> = sources.into_iter()
+            .map(|(stage, source)| (stage, &source.text[..]))
+            .collect();
+        program_hash(&driver, &stages)
+    }
+
+    /// Set on a program before linking it so the driver keeps its binary around for `store`
+    pub unsafe fn prepare(&self, program: GLuint) {
+        gl::ProgramParameteri(program, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint);
+    }
+
+    /// A new program loaded from the binary saved under `key`, or None if there's none or the
+    /// driver rejected it
+    pub unsafe fn load(&self, key: u64) -> Option<GLuint> {
+        let path = self.path(key);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(_) => return None,
+        };
+        // the binary format, least significant byte first, followed by the binary
+        if contents.len() <= 4 {
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        let format = contents[..4].iter().rev().fold(0, |format: GLenum, &byte| format << 8 | byte as GLenum);
+        let binary = &contents[4..];
+
+        let program = gl::CreateProgram();
+        gl::ProgramBinary(program, format, binary.as_ptr() as *const c_void, binary.len() as GLsizei);
+        let mut success = gl::FALSE as GLint;
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+        if success != gl::TRUE as GLint {
+            gl::DeleteProgram(program);
+            let _ = fs::remove_file(&path);
+            if cfg!(debug_assertions) {
+                println!("warning: the driver rejected cached program {}, compiling it again", path.display());
+            }
+            return None;
+        }
+        Some(program)
+    }
+
+    /// Save the binary of a linked `program` under `key`. Failing to is only worth a warning,
+    /// since the program will just be compiled again next time.
+    pub unsafe fn store(&self, key: u64, program: GLuint) {
+        let mut length = 0;
+        gl::GetProgramiv(program, gl::PROGRAM_BINARY_LENGTH, &mut length);
+        if length <= 0 {
+            return;
+        }
+        let mut binary = vec![0u8; length as usize];
+        let mut format = 0;
+        let mut written = 0;
+        gl::GetProgramBinary(program, length, &mut written, &mut format, binary.as_mut_ptr() as *mut c_void);
+        binary.truncate(written as usize);
+
+        let mut contents = Vec::with_capacity(4 + binary.len());
+        contents.extend_from_slice(&[format as u8, (format >> 8) as u8, (format >> 16) as u8, (format >> 24) as u8]);
+        contents.extend_from_slice(&binary);
+
+        // write to a temporary file first so another lesson starting meanwhile never reads half
+        // a binary
+        let path = self.path(key);
+        let temporary_path = path.with_extension("tmp");
+        let result = fs::create_dir_all(&self.folder)
+            .and_then(|_| fs::write(&temporary_path, &contents))
+            .and_then(|_| fs::rename(&temporary_path, &path));
+        if let Err(error) = result {
+            println!("warning: failed to cache program binary {}: {}", path.display(), error);
+        }
+    }
+
+    fn path(&self, key: u64) -> PathBuf {
+        self.folder.join(format!("{:016x}.bin", key))
+    }
+}
+
+/// A 64-bit FNV-1a hash of the driver strings and each stage's source. Unlike `DefaultHasher`,
+/// it's the same in every build, so binaries stay valid when the lessons are rebuilt.
+fn program_hash(driver: &[&str], stages: &[(ShaderStage, &str)]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    {
+        let mut add = |bytes: &[u8]| {
+            for &byte in bytes.iter().chain(&[0u8]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        };
+        for string in driver.iter() {
+            add(string.as_bytes());
+        }
+        for &(stage, text) in stages.iter() {
+            add(stage.to_string().as_bytes());
+            add(text.as_bytes());
+        }
+    }
+    hash
+}
+
+unsafe fn gl_string(name: GLenum) -> String {
+    let string = gl::GetString(name);
+    if string == ptr::null() {
+        return String::new();
+    }
+    CStr::from_ptr(string as *const _).to_string_lossy().into_owned()
+}
@@ -0,0 +1,130 @@
+use std::f32::{INFINITY, NEG_INFINITY};
+
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+
+#[cfg(test)]
+#[path = "./ray_tests.rs"]
+mod ray_tests;
+
+/// A half-line in world space, e.g. from the camera through the cursor
+#[derive(Debug, Copy, Clone)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    /// always unit length, so distances along the ray are in world units
+    pub direction: Vector3<f32>,
+}
+
+/// Where a ray hit a scene element
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RayHit {
+    /// distance from the ray's origin
+    pub distance: f32,
+    /// which of the element's instances was hit; 0 for elements that draw a single object
+    pub instance: usize,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Ray {
+            origin: origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// The ray under the cursor. `cursor_x` and `cursor_y` are window coordinates as GLFW reports
+    /// them, with the origin at the top left, and `window_width` and `window_height` are in the
+    /// same units. Returns None if the view and projection can't be inverted.
+    pub fn from_cursor(
+        cursor_x: f64,
+        cursor_y: f64,
+        window_width: u32,
+        window_height: u32,
+        view_matrix: &Matrix4<f32>,
+        projection_matrix: &Matrix4<f32>,
+    ) -> Option<Self> {
+        let inverse_view_projection = match (projection_matrix * view_matrix).invert() {
+            Some(inverse) => inverse,
+            None => return None,
+        };
+
+        // normalized device coordinates run from -1 to 1, with y pointing up
+        let ndc_x = (2.0 * cursor_x / window_width as f64 - 1.0) as f32;
+        let ndc_y = (1.0 - 2.0 * cursor_y / window_height as f64) as f32;
+        let unproject = |ndc_z: f32| {
+            Point3::from_homogeneous(inverse_view_projection * Vector4::new(ndc_x, ndc_y, ndc_z, 1.0))
+        };
+
+        // from the near plane to the far plane, so this works for orthographic projections too
+        let near_point = unproject(-1.0);
+        let far_point = unproject(1.0);
+        Some(Ray::new(near_point, far_point - near_point))
+    }
+
+    pub fn point_at(&self, distance: f32) -> Point3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// The distances at which the ray enters and leaves an axis-aligned box, or None if it misses.
+    /// The entry distance is negative when the ray starts inside the box.
+    pub fn aabb_interval(&self, min: Point3<f32>, max: Point3<f32>) -> Option<(f32, f32)> {
+        slab_interval(self.origin, self.direction, min, max)
+    }
+
+    /// Distance to the nearest point of an axis-aligned box in front of the ray
+    pub fn intersect_aabb(&self, min: Point3<f32>, max: Point3<f32>) -> Option<f32> {
+        self.aabb_interval(min, max).and_then(nearest_in_front)
+    }
+
+    /// Distance to the nearest point of the box from `min` to `max` in model space after
+    /// `model_matrix` is applied, i.e. an oriented bounding box
+    pub fn intersect_obb(&self, model_matrix: &Matrix4<f32>, min: Point3<f32>, max: Point3<f32>) -> Option<f32> {
+        let inverse_model_matrix = match model_matrix.invert() {
+            Some(inverse) => inverse,
+            None => return None,
+        };
+
+        // test in model space. The model-space direction isn't normalized, so a distance along it
+        // is the same distance along the world-space ray.
+        let origin = Point3::from_homogeneous(inverse_model_matrix * self.origin.to_homogeneous());
+        let direction = (inverse_model_matrix * self.direction.extend(0.0)).truncate();
+        slab_interval(origin, direction, min, max).and_then(nearest_in_front)
+    }
+}
+
+/// Slab test: clip the line to each pair of parallel box faces in turn
+fn slab_interval(
+    origin: Point3<f32>,
+    direction: Vector3<f32>,
+    min: Point3<f32>,
+    max: Point3<f32>,
+) -> Option<(f32, f32)> {
+    let mut entry = NEG_INFINITY;
+    let mut exit = INFINITY;
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            // parallel to this pair of faces; either always between them or never
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let near_face = (min[axis] - origin[axis]) / direction[axis];
+        let far_face = (max[axis] - origin[axis]) / direction[axis];
+        entry = entry.max(near_face.min(far_face));
+        exit = exit.min(near_face.max(far_face));
+        if entry > exit {
+            return None;
+        }
+    }
+    Some((entry, exit))
+}
+
+/// The first point of an entry-exit interval that isn't behind the ray's origin
+fn nearest_in_front((entry, exit): (f32, f32)) -> Option<f32> {
+    if exit < 0.0 {
+        None
+    } else {
+        Some(entry.max(0.0))
+    }
+}
@@ -0,0 +1,92 @@
+use cgmath::{ortho, perspective, Deg};
+
+use super::*;
+
+/// A camera at z = 5 looking at the origin through an 800 by 600 window
+fn camera() -> (Matrix4<f32>, Matrix4<f32>) {
+    let view = Matrix4::look_at(Point3::new(0.0, 0.0, 5.0), Point3::new(0.0, 0.0, 0.0), Vector3::unit_y());
+    let projection = perspective(Deg(60.0), 800.0 / 600.0, 0.1, 100.0);
+    (view, projection)
+}
+
+fn unit_box() -> (Point3<f32>, Point3<f32>) {
+    (Point3::new(-0.5, -0.5, -0.5), Point3::new(0.5, 0.5, 0.5))
+}
+
+fn assert_close(actual: Vector3<f32>, expected: Vector3<f32>) {
+    assert!((actual - expected).magnitude() < 1e-4, "expected {:?}, got {:?}", expected, actual);
+}
+
+#[test]
+fn ray_through_the_screen_centre_hits_a_box_on_the_view_axis() {
+    let (view, projection) = camera();
+    let ray = Ray::from_cursor(400.0, 300.0, 800, 600, &view, &projection).unwrap();
+    assert_close(ray.direction, Vector3::new(0.0, 0.0, -1.0));
+
+    // the ray starts on the near plane, 0.1 in front of the camera
+    let (min, max) = unit_box();
+    let distance = ray.intersect_aabb(min, max).unwrap();
+    assert!((distance - 4.4).abs() < 1e-3, "expected 4.4, got {}", distance);
+    assert_close(ray.point_at(distance) - Point3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.5));
+}
+
+#[test]
+fn rays_through_the_window_edges_follow_the_field_of_view() {
+    let (view, projection) = camera();
+    // the top of the window is 30 degrees above the view axis
+    let top = Ray::from_cursor(400.0, 0.0, 800, 600, &view, &projection).unwrap();
+    assert_close(top.direction, Vector3::new(0.0, 0.5, -0.75f32.sqrt()));
+
+    let (min, max) = unit_box();
+    let corner = Ray::from_cursor(0.0, 600.0, 800, 600, &view, &projection).unwrap();
+    assert!(corner.direction.x < 0.0 && corner.direction.y < 0.0);
+    assert_eq!(corner.intersect_aabb(min, max), None);
+}
+
+#[test]
+fn orthographic_rays_are_parallel_to_the_view_axis() {
+    let (view, _) = camera();
+    let projection = ortho(-4.0, 4.0, -3.0, 3.0, 0.1, 100.0);
+    let ray = Ray::from_cursor(500.0, 300.0, 800, 600, &view, &projection).unwrap();
+    assert_close(ray.direction, Vector3::new(0.0, 0.0, -1.0));
+    assert_close(ray.origin - Point3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 4.9));
+}
+
+#[test]
+fn ray_parallel_to_a_box_misses_it() {
+    let (min, max) = unit_box();
+    // alongside the box's top face, but above it
+    let ray = Ray::new(Point3::new(-5.0, 0.6, 0.0), Vector3::new(1.0, 0.0, 0.0));
+    assert_eq!(ray.aabb_interval(min, max), None);
+    assert_eq!(ray.intersect_aabb(min, max), None);
+
+    // the same line through the box's middle hits it
+    let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+    assert_eq!(ray.intersect_aabb(min, max), Some(4.5));
+}
+
+#[test]
+fn ray_from_inside_a_box_hits_it_at_its_origin() {
+    let (min, max) = unit_box();
+    let ray = Ray::new(Point3::new(0.1, 0.2, 0.0), Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(ray.aabb_interval(min, max), Some((-0.5, 0.5)));
+    assert_eq!(ray.intersect_aabb(min, max), Some(0.0));
+}
+
+#[test]
+fn box_behind_the_ray_is_missed() {
+    let (min, max) = unit_box();
+    let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(ray.intersect_aabb(min, max), None);
+}
+
+#[test]
+fn rotated_box_is_hit_on_its_edge() {
+    let (min, max) = unit_box();
+    // turned 45 degrees about y, so its vertical edge is nearest along z
+    let model_matrix = Matrix4::from_translation(Vector3::new(0.0, 0.0, -2.0)) * Matrix4::from_angle_y(Deg(45.0));
+    let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+    let distance = ray.intersect_obb(&model_matrix, min, max).unwrap();
+    assert!((distance - (7.0 - 0.5f32.sqrt())).abs() < 1e-4, "got {}", distance);
+    assert_eq!(ray.intersect_obb(&Matrix4::from_scale(0.0), min, max), None);
+}
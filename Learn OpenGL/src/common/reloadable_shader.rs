@@ -0,0 +1,160 @@
+use std::fs;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+use common::assets;
+use common::shader::{CompiledStage, Shader, ShaderError, ShaderStage};
+use common::shader_builder::ShaderBuilder;
+use common::shader_preprocessor::{Preprocessor, ShaderSource};
+
+/// how often to look for changed source files
+const CHECK_INTERVAL_MILLISECONDS: u64 = 250;
+
+/// A shader program that's rebuilt whenever one of its source files, or a file they `#include`,
+/// changes on disk, so shaders can be edited while a lesson is running. It derefs to the current
+/// `Shader`.
+///
+/// Call `reload_if_changed` once per frame at a point where no draw calls are in flight, e.g.
+/// before the scene graph renders. If an edit fails to compile or link, the error is printed and
+/// the previous program stays in use until the next save.
+pub struct ReloadableShader {
+    shader: Shader,
+    builder: ShaderBuilder,
+    // stages the program was linked from that other programs may share, kept compiled until the
+    // next rebuild, which compiles its own
+    stages: Vec<Rc<CompiledStage>>,
+    // the stages' files and everything they included when last built
+    watched_paths: Vec<String>,
+    modified_times: Vec<Option<SystemTime>>,
+    last_check: Instant,
+}
+
+impl ReloadableShader {
+    pub fn new(vertex_path: &str, fragment_path: &str) -> Result<Self, ShaderError> {
+        ReloadableShader::from_builder(ShaderBuilder::new().vertex(vertex_path).fragment(fragment_path))
+    }
+
+    /// Build the program from the given stages' files, keeping `preprocessor` to use for every
+    /// rebuild
+    pub fn from_files_with(stages: &[(ShaderStage, &str)], preprocessor: Preprocessor) -> Result<Self, ShaderError> {
+        ReloadableShader::from_builder(ShaderBuilder::new().preprocessor(preprocessor).files(stages))
+    }
+
+    /// Build the program from any set of stages, rebuilding it the same way whenever one of the
+    /// files it reads changes. Stages given as strings never change.
+    pub fn from_builder(builder: ShaderBuilder) -> Result<Self, ShaderError> {
+        let (shader, watched_paths, modified_times) = build(&builder)?;
+
+        Ok(ReloadableShader {
+            shader: shader,
+            builder: builder,
+            stages: vec![],
+            watched_paths: watched_paths,
+            modified_times: modified_times,
+            last_check: Instant::now(),
+        })
+    }
+
+    /// Build the program like `from_builder`, but link it from stages `compile` hands back for
+    /// each preprocessed source, e.g. ones `AssetCache::shader` shares between programs
+    pub fn from_builder_compiling<F>(builder: ShaderBuilder, mut compile: F) -> Result<Self, ShaderError>
+        where F: FnMut(ShaderStage, ShaderSource) -> Result<Rc<CompiledStage>, ShaderError>
+    {
+        let sources = builder.preprocess()?;
+        let watched_paths = watched_paths(&sources);
+        let modified_times = modified_times(&watched_paths);
+
+        let mut stages = Vec::with_capacity(sources.len());
+        for (stage, source) in sources.into_iter() {
+            stages.push(compile(stage, source)?);
+        }
+        let shader = Shader::from_stages(&stages)?;
+
+        Ok(ReloadableShader {
+            shader: shader,
+            builder: builder,
+            stages: stages,
+            watched_paths: watched_paths,
+            modified_times: modified_times,
+            last_check: Instant::now(),
+        })
+    }
+
+    /// Rebuild the program if any of its source files changed since it was last built. Returns
+    /// whether a new program was swapped in.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if self.last_check.elapsed() < Duration::from_millis(CHECK_INTERVAL_MILLISECONDS) {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified_times = modified_times(&self.watched_paths);
+        if modified_times == self.modified_times {
+            return false;
+        }
+        // remember these times even if the build fails so a broken edit is only reported once
+        self.modified_times = modified_times;
+
+        match build(&self.builder) {
+            Ok((shader, watched_paths, modified_times)) => {
+                // dropping the previous program deletes it
+                self.shader = shader;
+                self.stages.clear();
+                // the edit may have added or removed includes
+                self.watched_paths = watched_paths;
+                self.modified_times = modified_times;
+                println!("Reloaded shader program ({})", self.builder.names().join(", "));
+                true
+            },
+            Err(error) => {
+                println!("{}\nKeeping the previous shader program", error);
+                false
+            },
+        }
+    }
+}
+
+impl Deref for ReloadableShader {
+    type Target = Shader;
+
+    fn deref(&self) -> &Shader {
+        &self.shader
+    }
+}
+
+/// Preprocess and build the program, returning it along with every file it was built from and
+/// their modification times
+fn build(builder: &ShaderBuilder) -> Result<(Shader, Vec<String>, Vec<Option<SystemTime>>), ShaderError> {
+    let sources = builder.preprocess()?;
+    let watched_paths = watched_paths(&sources);
+    // read the times before compiling so an edit made meanwhile is picked up next time
+    let modified_times = modified_times(&watched_paths);
+
+    let shader = Shader::from_sources(sources)?;
+    Ok((shader, watched_paths, modified_times))
+}
+
+/// Every file the sources were built from, each once
+fn watched_paths(sources: &[(ShaderStage, ShaderSource)]) -> Vec<String> {
+    let mut watched_paths: Vec<String> = vec![];
+    for &(_, ref source) in sources.iter() {
+        for file in source.files.iter() {
+            if !watched_paths.contains(&file.path) {
+                watched_paths.push(file.path.clone());
+            }
+        }
+    }
+    watched_paths
+}
+
+/// None for files that can't be read, so a file that's briefly missing while an editor saves it
+/// counts as changed once it's back. Embedded files never change.
+fn modified_times(paths: &[String]) -> Vec<Option<SystemTime>> {
+    paths.iter()
+        .map(|path| {
+            assets::file_path(path)
+                .and_then(|file_path| fs::metadata(file_path).and_then(|metadata| metadata.modified()).ok())
+        })
+        .collect()
+}
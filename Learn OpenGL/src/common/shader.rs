@@ -1,66 +1,309 @@
 #![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
 use std::ffi::{CString, CStr};
-use std::fs::File;
-use std::io::Read;
+use std::fmt;
+use std::fmt::Write;
+use std::ops::BitOr;
 use std::ptr;
-use std::str;
+use std::rc::Rc;
 
 use gl;
 use gl::types::*;
 
-use cgmath::{Matrix, Matrix3, Matrix4, Vector3};
-use cgmath::prelude::*;
+use cgmath::{Matrix3, Matrix4, Vector3};
 
+use common::assets::AssetError;
+use common::program_cache::ProgramCache;
+use common::shader_preprocessor::ShaderSource;
+use common::uniform::Uniform;
+use common::uniform_buffer::bind_shared_blocks;
+use common::vertex_layout::LayoutMismatch;
+
+/// lines of source shown on each side of a line the driver complains about
+const SNIPPET_CONTEXT_LINES: usize = 2;
+
+/// The stages in the order they run in, which is how they're ordered
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShaderStage {
+    Vertex,
+    TessellationControl,
+    TessellationEvaluation,
+    Geometry,
+    Fragment,
+    /// a compute shader, which makes up a program on its own
+    Compute,
+}
+
+impl ShaderStage {
+    fn gl_type(&self) -> GLenum {
+        match *self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::TessellationControl => gl::TESS_CONTROL_SHADER,
+            ShaderStage::TessellationEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::TessellationControl => "tessellation control",
+            ShaderStage::TessellationEvaluation => "tessellation evaluation",
+            ShaderStage::Geometry => "geometry",
+            ShaderStage::Fragment => "fragment",
+            ShaderStage::Compute => "compute",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    /// the source file is missing, unreadable or not UTF-8
+    Io { stage: ShaderStage, path: String, error: AssetError },
+    /// the source contains a NUL byte, which can't be passed to OpenGL
+    Nul { stage: ShaderStage, path: String },
+    /// an `#include` on line `line` of `path` isn't followed by a quoted file name
+    MalformedInclude { stage: ShaderStage, path: String, line: usize, text: String },
+    /// none of the `searched` paths exist
+    IncludeNotFound { stage: ShaderStage, path: String, line: usize, name: String, searched: Vec<String> },
+    /// `chain` starts and ends with the same file
+    IncludeCycle { stage: ShaderStage, chain: Vec<String> },
+    /// the stages can't be linked together, e.g. a fragment shader without a vertex shader
+    InvalidPipeline { stages: Vec<ShaderStage>, problem: &'static str },
+    /// `log` is the driver's complete info log, whose line numbers refer to the expanded `source`
+    Compile { stage: ShaderStage, path: String, source: ShaderSource, log: String },
+    Link { paths: Vec<String>, log: String },
+    /// the vertex attributes the program reads don't match the vertex data it's given
+    VertexLayout { paths: Vec<String>, mismatches: Vec<LayoutMismatch> },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShaderError::Io { stage, ref path, ref error } => {
+                write!(f, "failed to read {} shader {}: {}", stage, path, error)
+            },
+            ShaderError::Nul { stage, ref path } => {
+                write!(f, "{} shader {} contains a NUL byte", stage, path)
+            },
+            ShaderError::MalformedInclude { stage, ref path, line, ref text } => {
+                write!(f, "{}:{}: expected a quoted file name in {} shader: {}", path, line, stage, text)
+            },
+            ShaderError::IncludeNotFound { stage, ref path, line, ref name, ref searched } => {
+                write!(
+                    f,
+                    "{}:{}: can't find \"{}\" included by {} shader (tried {})",
+                    path, line, name, stage, searched.join(", "),
+                )
+            },
+            ShaderError::IncludeCycle { stage, ref chain } => {
+                write!(f, "{} shader includes itself: {}", stage, chain.join(" -> "))
+            },
+            ShaderError::InvalidPipeline { ref stages, problem } => {
+                let stage_names: Vec<String> = stages.iter().map(|stage| stage.to_string()).collect();
+                write!(f, "can't make a program of [{}]: {}", stage_names.join(", "), problem)
+            },
+            ShaderError::Compile { stage, ref path, ref source, ref log } => {
+                writeln!(f, "{} shader {} failed to compile:", stage, path)?;
+                for log_line in log.lines().filter(|line| !line.trim().is_empty()) {
+                    writeln!(f, "{}", log_line)?;
+                    if let Some(line_number) = log_line_number(log_line) {
+                        // show the line in the file it came from rather than the expanded source
+                        match source.origin(line_number) {
+                            Some((file, file_line)) => {
+                                writeln!(f, "  in {}:{}", file.path, file_line)?;
+                                write_snippet(f, &file.text, file_line)?;
+                            },
+                            None => write_snippet(f, &source.text, line_number)?,
+                        }
+                    }
+                }
+                Ok(())
+            },
+            ShaderError::Link { ref paths, ref log } => {
+                writeln!(f, "shader program ({}) failed to link:", paths.join(", "))?;
+                write!(f, "{}", log.trim_end())
+            },
+            ShaderError::VertexLayout { ref paths, ref mismatches } => {
+                write!(f, "vertex layout doesn't match shader program ({}):", paths.join(", "))?;
+                for mismatch in mismatches.iter() {
+                    write!(f, "\n  {}", mismatch)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn description(&self) -> &str {
+        match *self {
+            ShaderError::Io { .. } => "failed to read shader source",
+            ShaderError::Nul { .. } => "shader source contains a NUL byte",
+            ShaderError::MalformedInclude { .. } => "malformed shader #include",
+            ShaderError::IncludeNotFound { .. } => "shader #include not found",
+            ShaderError::IncludeCycle { .. } => "shader #includes form a cycle",
+            ShaderError::InvalidPipeline { .. } => "invalid combination of shader stages",
+            ShaderError::Compile { .. } => "shader failed to compile",
+            ShaderError::Link { .. } => "shader program failed to link",
+            ShaderError::VertexLayout { .. } => "vertex layout doesn't match shader program",
+        }
+    }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            ShaderError::Io { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// The source line a driver log line refers to. Drivers disagree on the format, e.g.
+/// `0(12) : error C0000: ...` (NVIDIA), `0:12(5): error: ...` (Mesa) and `ERROR: 0:12: ...` (AMD),
+/// but all of them give the source string index followed by the line number.
+fn log_line_number(log_line: &str) -> Option<usize> {
+    let location = log_line.trim_start_matches(|c: char| !c.is_digit(10));
+    let after_source_index = location.trim_start_matches(|c: char| c.is_digit(10));
+    let line_number = match after_source_index.chars().next() {
+        Some('(') | Some(':') => &after_source_index[1..],
+        _ => return None,
+    };
+    let digits: String = line_number.chars().take_while(|c| c.is_digit(10)).collect();
+    digits.parse().ok()
+}
+
+/// Write the numbered source lines around `line_number`, marking that line
+fn write_snippet(f: &mut fmt::Formatter, source: &str, line_number: usize) -> fmt::Result {
+    let line_count = source.lines().count();
+    if line_number == 0 || line_number > line_count {
+        return Ok(());
+    }
+
+    let first_line = line_number.saturating_sub(SNIPPET_CONTEXT_LINES).max(1);
+    let last_line = (line_number + SNIPPET_CONTEXT_LINES).min(line_count);
+    let lines = source.lines().enumerate().skip(first_line - 1).take(last_line - first_line + 1);
+    for (index, line) in lines {
+        let marker = if index + 1 == line_number { ">" } else { " " };
+        writeln!(f, "  {} {:>4} | {}", marker, index + 1, line)?;
+    }
+    Ok(())
+}
+
+/// An active attribute or uniform of a linked program, as reported by the driver
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveVariable {
+    pub name: String,
+    /// e.g. `gl::FLOAT_VEC3`; `glsl_type_name` gives the GLSL name
+    pub gl_type: GLenum,
+    /// the number of elements for arrays, otherwise 1
+    pub size: GLint,
+    /// -1 for built-ins like `gl_VertexID` and for uniforms in uniform blocks
+    pub location: GLint,
+}
+
+/// A linked program, deleted when it's dropped
 pub struct Shader {
     pub ID: u32,
+    // the files the program was built from, for error messages
+    paths: Vec<String>,
+    // locations of the uniforms set so far, -1 for names that aren't active uniforms
+    uniform_locations: RefCell<HashMap<String, GLint>>,
+    // reused to build names like `pointLights[2].diffuseColor` without allocating every frame
+    uniform_name: RefCell<String>,
 }
 
-/// panics if the shader files can't be found, the shaders fail to compile, or the shader program
-/// fails to link
-#[allow(dead_code)]
 impl Shader {
-    pub fn new(vertexPath: &str, fragmentPath: &str) -> Shader {
-        let mut shader = Shader { ID: 0 };
-        // 1. retrieve the vertex/fragment source code from filesystem
-        let mut vShaderFile = File::open(vertexPath).expect(&format!("Failed to open {}", vertexPath));
-        let mut fShaderFile = File::open(fragmentPath).expect(&format!("Failed to open {}", fragmentPath));
-        let mut vertexCode = String::new();
-        let mut fragmentCode = String::new();
-        vShaderFile
-            .read_to_string(&mut vertexCode)
-            .expect("Failed to read vertex shader");
-        fShaderFile
-            .read_to_string(&mut fragmentCode)
-            .expect("Failed to read fragment shader");
-
-        let vShaderCode = CString::new(vertexCode.as_bytes()).unwrap();
-        let fShaderCode = CString::new(fragmentCode.as_bytes()).unwrap();
-
-        // 2. compile shaders
-        unsafe {
-            // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &vShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            shader.checkCompileErrors(vertex, "VERTEX");
-            // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &fShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            shader.checkCompileErrors(fragment, "FRAGMENT");
-            // shader Program
-            let ID = gl::CreateProgram();
-            gl::AttachShader(ID, vertex);
-            gl::AttachShader(ID, fragment);
-            gl::LinkProgram(ID);
-            shader.checkCompileErrors(ID, "PROGRAM");
-            // delete the shaders as they're linked into our program now and no longer necessary
-            gl::DeleteShader(vertex);
-            gl::DeleteShader(fragment);
-            shader.ID = ID;
-        }
-
-        shader
+    /// Compile already preprocessed sources and link them into a program, or load the program
+    /// from the `ProgramCache` if it was built before
+    pub fn from_sources(sources: Vec<(ShaderStage, ShaderSource)>) -> Result<Shader, ShaderError> {
+        let paths: Vec<String> = sources.iter().map(|&(_, ref source)| source.path().to_string()).collect();
+        let cache = unsafe { ProgramCache::new() };
+        let cache_key = cache.as_ref().map(|cache| cache.key(sources.iter().map(|&(stage, ref source)| (stage, source))));
+        if let Some(shader) = unsafe { Shader::from_program_cache(&cache, cache_key, &paths) } {
+            return Ok(shader);
+        }
+
+        // stages compiled before one that fails are deleted as they're dropped
+        let mut stages = Vec::with_capacity(sources.len());
+        for (stage, source) in sources.into_iter() {
+            stages.push(CompiledStage::compile(stage, source)?);
+        }
+        let stages: Vec<&CompiledStage> = stages.iter().collect();
+        unsafe { Shader::link(&stages, paths, &cache, cache_key) }
+    }
+
+    /// Link stages that were already compiled, which other programs may be using too, or load the
+    /// program from the `ProgramCache` if it was built before
+    pub fn from_stages(stages: &[Rc<CompiledStage>]) -> Result<Shader, ShaderError> {
+        let paths: Vec<String> = stages.iter().map(|stage| stage.source.path().to_string()).collect();
+        let cache = unsafe { ProgramCache::new() };
+        let cache_key = cache.as_ref().map(|cache| cache.key(stages.iter().map(|stage| (stage.stage, &stage.source))));
+        if let Some(shader) = unsafe { Shader::from_program_cache(&cache, cache_key, &paths) } {
+            return Ok(shader);
+        }
+
+        let stages: Vec<&CompiledStage> = stages.iter().map(|stage| &**stage).collect();
+        unsafe { Shader::link(&stages, paths, &cache, cache_key) }
+    }
+
+    unsafe fn from_program_cache(cache: &Option<ProgramCache>, key: Option<u64>, paths: &[String]) -> Option<Shader> {
+        match (cache.as_ref(), key) {
+            (Some(cache), Some(key)) => cache.load(key).map(|ID| Shader::from_linked_program(ID, paths.to_vec())),
+            _ => None,
+        }
+    }
+
+    /// Link the stages into a new program, saving its binary in the cache if there's one. The
+    /// stages are detached again and left compiled for other programs to link.
+    unsafe fn link(
+        stages: &[&CompiledStage],
+        paths: Vec<String>,
+        cache: &Option<ProgramCache>,
+        cache_key: Option<u64>,
+    ) -> Result<Shader, ShaderError> {
+        let ID = gl::CreateProgram();
+        for stage in stages.iter() {
+            gl::AttachShader(ID, stage.ID);
+        }
+        if let Some(ref cache) = *cache {
+            cache.prepare(ID);
+        }
+        gl::LinkProgram(ID);
+        // the program no longer needs the stages once it's linked (or failed to), and attached
+        // stages would outlive their last handle
+        for stage in stages.iter() {
+            gl::DetachShader(ID, stage.ID);
+        }
+
+        let mut success = gl::FALSE as GLint;
+        gl::GetProgramiv(ID, gl::LINK_STATUS, &mut success);
+        if success != gl::TRUE as GLint {
+            let log = program_info_log(ID);
+            gl::DeleteProgram(ID);
+            return Err(ShaderError::Link { paths: paths, log: log });
+        }
+        if let (Some(cache), Some(key)) = (cache.as_ref(), cache_key) {
+            cache.store(key, ID);
+        }
+
+        Ok(Shader::from_linked_program(ID, paths))
+    }
+
+    unsafe fn from_linked_program(ID: GLuint, paths: Vec<String>) -> Shader {
+        bind_shared_blocks(ID);
+        Shader {
+            ID: ID,
+            paths: paths,
+            uniform_locations: RefCell::new(HashMap::new()),
+            uniform_name: RefCell::new(String::new()),
+        }
     }
 
     /// activate the shader
@@ -69,136 +312,364 @@ impl Shader {
         gl::UseProgram(self.ID)
     }
 
+    /// The `local_size_x`, `_y` and `_z` a compute program declares
+    pub fn work_group_size(&self) -> [u32; 3] {
+        let mut size: [GLint; 3] = [0; 3];
+        unsafe {
+            gl::GetProgramiv(self.ID, gl::COMPUTE_WORK_GROUP_SIZE, size.as_mut_ptr());
+        }
+        [size[0] as u32, size[1] as u32, size[2] as u32]
+    }
+
+    /// Run a compute program in `x` × `y` × `z` work groups. Call `memory_barrier` before using
+    /// what it wrote.
+    pub unsafe fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.use_program();
+        gl::DispatchCompute(x, y, z);
+    }
+
+    /// Run a compute program in enough work groups for at least `x` × `y` × `z` invocations, e.g.
+    /// one per texel of an image. The shader has to skip invocations past the edges when the
+    /// sizes aren't multiples of the work group size.
+    pub unsafe fn dispatch_invocations(&self, x: u32, y: u32, z: u32) {
+        let size = self.work_group_size();
+        let groups = |invocations: u32, group_size: u32| (invocations + group_size - 1) / group_size.max(1);
+        self.dispatch(groups(x, size[0]), groups(y, size[1]), groups(z, size[2]));
+    }
+
+    /// The source files of each stage the program was built from
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// The vertex shader inputs that the linked program actually reads
+    pub fn active_attributes(&self) -> Vec<ActiveVariable> {
+        unsafe {
+            active_variables(self.ID, gl::ACTIVE_ATTRIBUTES, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, |index, name| {
+                let capacity = name.len() as GLsizei;
+                let mut variable = query_active_variable(name, |length, size, gl_type, name_buffer| {
+                    gl::GetActiveAttrib(self.ID, index, capacity, length, size, gl_type, name_buffer)
+                });
+                variable.location = gl::GetAttribLocation(self.ID, name.as_ptr() as *const GLchar);
+                variable
+            })
+        }
+    }
+
+    /// The uniforms that the linked program actually uses. Arrays are listed once, by the name of
+    /// their first element, e.g. `pointLights[0].diffuseColor`.
+    pub fn active_uniforms(&self) -> Vec<ActiveVariable> {
+        unsafe {
+            active_variables(self.ID, gl::ACTIVE_UNIFORMS, gl::ACTIVE_UNIFORM_MAX_LENGTH, |index, name| {
+                let capacity = name.len() as GLsizei;
+                let mut variable = query_active_variable(name, |length, size, gl_type, name_buffer| {
+                    gl::GetActiveUniform(self.ID, index, capacity, length, size, gl_type, name_buffer)
+                });
+                variable.location = gl::GetUniformLocation(self.ID, name.as_ptr() as *const GLchar);
+                variable
+            })
+        }
+    }
+
+    /// The location of uniform `name`, which is only looked up in GL the first time. In debug
+    /// builds, a warning listing the uniforms that can be set is printed the first time a name
+    /// isn't an active uniform, which is usually a typo or a uniform the compiler optimized out.
+    pub fn uniform_location(&self, name: &str) -> GLint {
+        if let Some(&location) = self.uniform_locations.borrow().get(name) {
+            return location;
+        }
+
+        let location = match CString::new(name) {
+            Ok(c_name) => unsafe { gl::GetUniformLocation(self.ID, c_name.as_ptr()) },
+            Err(_) => -1,
+        };
+        if location == -1 && cfg!(debug_assertions) {
+            // uniforms in uniform blocks have no location of their own
+            let settable: Vec<String> = self.active_uniforms().iter()
+                .filter(|uniform| uniform.location != -1)
+                .map(|uniform| match uniform.size {
+                    1 => format!("{} {}", glsl_type_name(uniform.gl_type), uniform.name),
+                    size => format!("{} {} ({} elements)", glsl_type_name(uniform.gl_type), uniform.name, size),
+                })
+                .collect();
+            println!(
+                "warning: shader program {} ({}) has no active uniform named {}; its uniforms are: {}",
+                self.ID, self.paths.join(", "), name, settable.join(", "),
+            );
+        }
+        self.uniform_locations.borrow_mut().insert(name.to_string(), location);
+        location
+    }
+
+    /// Set uniform `name` of this program, which must be in use
+    pub unsafe fn set<T: Uniform + ?Sized>(&self, name: &str, value: &T) {
+        let location = self.uniform_location(name);
+        if location != -1 {
+            value.set_uniform(location);
+        }
+    }
+
+    /// Set a field of a struct uniform, e.g. `set_field("spotlight", "cutOffInner", &cos)` for
+    /// `spotlight.cutOffInner`
+    pub unsafe fn set_field<T: Uniform + ?Sized>(&self, struct_name: &str, field: &str, value: &T) {
+        let location = {
+            let mut name = self.uniform_name.borrow_mut();
+            name.clear();
+            write!(name, "{}.{}", struct_name, field).unwrap();
+            self.uniform_location(&name)
+        };
+        if location != -1 {
+            value.set_uniform(location);
+        }
+    }
+
+    /// Set a field of one element of an array of struct uniforms, e.g.
+    /// `set_element_field("pointLights", 2, "diffuseColor", &color)` for
+    /// `pointLights[2].diffuseColor`
+    pub unsafe fn set_element_field<T: Uniform + ?Sized>(
+        &self,
+        array_name: &str,
+        index: usize,
+        field: &str,
+        value: &T,
+    ) {
+        let location = {
+            let mut name = self.uniform_name.borrow_mut();
+            name.clear();
+            write!(name, "{}[{}].{}", array_name, index, field).unwrap();
+            self.uniform_location(&name)
+        };
+        if location != -1 {
+            value.set_uniform(location);
+        }
+    }
+
     /// utility uniform functions
     /// ------------------------------------------------------------------------
     pub unsafe fn set_bool(&self, name: &CStr, value: bool) {
-        gl::Uniform1i(gl::GetUniformLocation(self.ID, name.as_ptr()), value as i32);
+        self.set(&name.to_string_lossy(), &value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn set_int(&self, name: &CStr, value: i32) {
-        gl::Uniform1i(gl::GetUniformLocation(self.ID, name.as_ptr()), value);
+        self.set(&name.to_string_lossy(), &value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn set_float(&self, name: &CStr, value: f32) {
-        gl::Uniform1f(gl::GetUniformLocation(self.ID, name.as_ptr()), value);
+        self.set(&name.to_string_lossy(), &value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn set_3fv(&self, name: &CStr, value: &Vector3<f32>) {
-        let location = gl::GetUniformLocation(self.ID, name.as_ptr());
-        gl::Uniform3fv(location, 1, value.as_ptr());
-    }
-    /// ------------------------------------------------------------------------
-    pub unsafe fn set_3f(&self, name: &CStr, x: f32, y: f32, z: f32) {
-        gl::Uniform3f(gl::GetUniformLocation(self.ID, name.as_ptr()), x, y, z);
+        self.set(&name.to_string_lossy(), value);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn set_mat3fv(&self, name: &CStr, mat: &Matrix3<f32>) {
-        gl::UniformMatrix3fv(gl::GetUniformLocation(self.ID, name.as_ptr()), 1, gl::FALSE, mat.as_ptr());
+        self.set(&name.to_string_lossy(), mat);
     }
     /// ------------------------------------------------------------------------
     pub unsafe fn set_mat4fv(&self, name: &CStr, mat: &Matrix4<f32>) {
-        gl::UniformMatrix4fv(gl::GetUniformLocation(self.ID, name.as_ptr()), 1, gl::FALSE, mat.as_ptr());
+        self.set(&name.to_string_lossy(), mat);
     }
+}
 
-    /// utility function for checking shader compilation/linking errors.
-    /// ------------------------------------------------------------------------
-    unsafe fn checkCompileErrors(&self, shader: u32, type_: &str) {
-        let error_message_capacity: i32 = 1024;
-        let mut success = gl::FALSE as GLint;
-        let mut info_log = Vec::with_capacity(error_message_capacity as usize);
-        // subtract 1 to skip the trailing null character
-        info_log.set_len(error_message_capacity as usize - 1);
-        if type_ != "PROGRAM" {
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.ID);
+        }
+    }
+}
+
+/// A compiled stage, deleted when it's dropped. Linking doesn't use it up, so programs built with
+/// `Shader::from_stages` can share one, e.g. through `AssetCache::shader`.
+pub struct CompiledStage {
+    pub ID: GLuint,
+    pub stage: ShaderStage,
+    /// the preprocessed source it was compiled from
+    pub source: ShaderSource,
+}
+
+impl CompiledStage {
+    pub fn compile(stage: ShaderStage, source: ShaderSource) -> Result<CompiledStage, ShaderError> {
+        let source_c_string = match CString::new(source.text.as_bytes()) {
+            Ok(source_c_string) => source_c_string,
+            Err(_) => return Err(ShaderError::Nul { stage: stage, path: source.path().to_string() }),
+        };
+
+        unsafe {
+            let shader = gl::CreateShader(stage.gl_type());
+            gl::ShaderSource(shader, 1, &source_c_string.as_ptr(), ptr::null());
+            gl::CompileShader(shader);
+
+            let mut success = gl::FALSE as GLint;
             gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
             if success != gl::TRUE as GLint {
-                gl::GetShaderInfoLog(
-                    shader,
-                    error_message_capacity,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                panic!("ERROR::SHADER_COMPILATION_ERROR of type: {}\n{}\n \
-                          -- --------------------------------------------------- -- ",
-                         type_,
-                         String::from_utf8_lossy(&info_log),
-                );
+                let log = shader_info_log(shader);
+                gl::DeleteShader(shader);
+                return Err(ShaderError::Compile {
+                    stage: stage,
+                    path: source.path().to_string(),
+                    source: source,
+                    log: log,
+                });
             }
 
-        } else {
-            gl::GetProgramiv(shader, gl::LINK_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetProgramInfoLog(
-                    shader,
-                    error_message_capacity,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                panic!("ERROR::PROGRAM_LINKING_ERROR of type: {}\n{}\n \
-                          -- --------------------------------------------------- -- ",
-                         type_,
-                         String::from_utf8_lossy(&info_log),
-                );
-            }
+            Ok(CompiledStage {
+                ID: shader,
+                stage: stage,
+                source: source,
+            })
         }
-
     }
+}
 
-    /// Only used in 4.9 Geometry shaders - ignore until then (shader.h in original C++)
-    pub fn with_geometry_shader(vertexPath: &str, fragmentPath: &str, geometryPath: &str) -> Self {
-        let mut shader = Shader { ID: 0 };
-        // 1. retrieve the vertex/fragment source code from filesystem
-        let mut vShaderFile = File::open(vertexPath).expect(&format!("Failed to open {}", vertexPath));
-        let mut fShaderFile = File::open(fragmentPath).expect(&format!("Failed to open {}", fragmentPath));
-        let mut gShaderFile = File::open(geometryPath).expect(&format!("Failed to open {}", geometryPath));
-        let mut vertexCode = String::new();
-        let mut fragmentCode = String::new();
-        let mut geometryCode = String::new();
-        vShaderFile
-            .read_to_string(&mut vertexCode)
-            .expect("Failed to read vertex shader");
-        fShaderFile
-            .read_to_string(&mut fragmentCode)
-            .expect("Failed to read fragment shader");
-        gShaderFile
-            .read_to_string(&mut geometryCode)
-            .expect("Failed to read geometry shader");
-
-        let vShaderCode = CString::new(vertexCode.as_bytes()).unwrap();
-        let fShaderCode = CString::new(fragmentCode.as_bytes()).unwrap();
-        let gShaderCode = CString::new(geometryCode.as_bytes()).unwrap();
-
-        // 2. compile shaders
+impl Drop for CompiledStage {
+    fn drop(&mut self) {
         unsafe {
-            // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &vShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            shader.checkCompileErrors(vertex, "VERTEX");
-            // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &fShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            shader.checkCompileErrors(fragment, "FRAGMENT");
-            // geometry shader
-            let geometry = gl::CreateShader(gl::GEOMETRY_SHADER);
-            gl::ShaderSource(geometry, 1, &gShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(geometry);
-            shader.checkCompileErrors(geometry, "GEOMETRY");
-
-            // shader Program
-            let ID = gl::CreateProgram();
-            gl::AttachShader(ID, vertex);
-            gl::AttachShader(ID, fragment);
-            gl::AttachShader(ID, geometry);
-            gl::LinkProgram(ID);
-            shader.checkCompileErrors(ID, "PROGRAM");
-            // delete the shaders as they're linked into our program now and no longer necessary
-            gl::DeleteShader(vertex);
-            gl::DeleteShader(fragment);
-            gl::DeleteShader(geometry);
-            shader.ID = ID;
-        }
-
-        shader
+            gl::DeleteShader(self.ID);
+        }
+    }
+}
+
+/// Whether the context can run compute programs, which needs OpenGL 4.3
+pub unsafe fn compute_shaders_supported() -> bool {
+    let (mut major, mut minor) = (0, 0);
+    gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+    gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    (major, minor) >= (4, 3)
+}
+
+/// The kinds of memory access that must see the writes of earlier shader invocations, which
+/// combine with `|`. E.g. `MemoryBarrier::VERTEX_ATTRIBUTES` after a compute shader writes to a
+/// buffer that's then drawn from.
+#[derive(Copy, Clone, PartialEq)]
+pub struct MemoryBarrier(pub GLbitfield);
+
+impl MemoryBarrier {
+    pub const VERTEX_ATTRIBUTES: MemoryBarrier = MemoryBarrier(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    pub const ELEMENT_ARRAY: MemoryBarrier = MemoryBarrier(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: MemoryBarrier = MemoryBarrier(gl::UNIFORM_BARRIER_BIT);
+    pub const TEXTURE_FETCH: MemoryBarrier = MemoryBarrier(gl::TEXTURE_FETCH_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: MemoryBarrier = MemoryBarrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const TEXTURE_UPDATE: MemoryBarrier = MemoryBarrier(gl::TEXTURE_UPDATE_BARRIER_BIT);
+    pub const BUFFER_UPDATE: MemoryBarrier = MemoryBarrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    pub const SHADER_STORAGE: MemoryBarrier = MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT);
+    pub const ALL: MemoryBarrier = MemoryBarrier(gl::ALL_BARRIER_BITS);
+}
+
+impl BitOr for MemoryBarrier {
+    type Output = MemoryBarrier;
+
+    fn bitor(self, other: MemoryBarrier) -> MemoryBarrier {
+        MemoryBarrier(self.0 | other.0)
+    }
+}
+
+/// Lists the kinds of access by name, e.g. `MemoryBarrier(VERTEX_ATTRIBUTES | SHADER_STORAGE)`
+impl fmt::Debug for MemoryBarrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == MemoryBarrier::ALL {
+            return write!(f, "MemoryBarrier(ALL)");
+        }
+        let named = [
+            (MemoryBarrier::VERTEX_ATTRIBUTES, "VERTEX_ATTRIBUTES"),
+            (MemoryBarrier::ELEMENT_ARRAY, "ELEMENT_ARRAY"),
+            (MemoryBarrier::UNIFORM, "UNIFORM"),
+            (MemoryBarrier::TEXTURE_FETCH, "TEXTURE_FETCH"),
+            (MemoryBarrier::SHADER_IMAGE_ACCESS, "SHADER_IMAGE_ACCESS"),
+            (MemoryBarrier::TEXTURE_UPDATE, "TEXTURE_UPDATE"),
+            (MemoryBarrier::BUFFER_UPDATE, "BUFFER_UPDATE"),
+            (MemoryBarrier::SHADER_STORAGE, "SHADER_STORAGE"),
+        ];
+        let names: Vec<&str> = named.iter()
+            .filter(|&&(barrier, _)| self.0 & barrier.0 == barrier.0)
+            .map(|&(_, name)| name)
+            .collect();
+        write!(f, "MemoryBarrier({})", names.join(" | "))
     }
 }
+
+/// Wait for earlier shader writes to be visible to the given kinds of access
+pub unsafe fn memory_barrier(barrier: MemoryBarrier) {
+    gl::MemoryBarrier(barrier.0);
+}
+
+/// Query each of the `count_parameter` active variables of a program with `query`, which is given
+/// the variable's index and a buffer long enough for any of their names
+unsafe fn active_variables<F>(
+    program: GLuint,
+    count_parameter: GLenum,
+    max_length_parameter: GLenum,
+    mut query: F,
+) -> Vec<ActiveVariable>
+    where F: FnMut(GLuint, &mut Vec<u8>) -> ActiveVariable
+{
+    let (mut count, mut max_length) = (0, 0);
+    gl::GetProgramiv(program, count_parameter, &mut count);
+    gl::GetProgramiv(program, max_length_parameter, &mut max_length);
+
+    let mut name = vec![0u8; max_length.max(1) as usize];
+    (0..count.max(0) as GLuint).map(|index| query(index, &mut name)).collect()
+}
+
+/// Call one of `glGetActiveAttrib` and `glGetActiveUniform` through `get_active`, leaving the
+/// NUL-terminated name in `name` for a location query
+unsafe fn query_active_variable<F>(name: &mut Vec<u8>, get_active: F) -> ActiveVariable
+    where F: FnOnce(*mut GLsizei, *mut GLint, *mut GLenum, *mut GLchar)
+{
+    let (mut length, mut size, mut gl_type) = (0, 0, 0);
+    get_active(&mut length, &mut size, &mut gl_type, name.as_mut_ptr() as *mut GLchar);
+    ActiveVariable {
+        name: String::from_utf8_lossy(&name[..length as usize]).into_owned(),
+        gl_type: gl_type,
+        size: size,
+        location: -1,
+    }
+}
+
+/// The GLSL name of a type returned by the active variable queries
+pub fn glsl_type_name(gl_type: GLenum) -> &'static str {
+    match gl_type {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        _ => "(unknown type)",
+    }
+}
+
+/// The whole info log, however long the driver made it
+unsafe fn shader_info_log(shader: GLuint) -> String {
+    let mut log_length = 0;
+    gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut log_length);
+    let mut log = vec![0u8; log_length.max(1) as usize];
+    let mut written = 0;
+    gl::GetShaderInfoLog(shader, log.len() as GLsizei, &mut written, log.as_mut_ptr() as *mut GLchar);
+    log.truncate(written as usize);
+    String::from_utf8_lossy(&log).into_owned()
+}
+
+unsafe fn program_info_log(program: GLuint) -> String {
+    let mut log_length = 0;
+    gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut log_length);
+    let mut log = vec![0u8; log_length.max(1) as usize];
+    let mut written = 0;
+    gl::GetProgramInfoLog(program, log.len() as GLsizei, &mut written, log.as_mut_ptr() as *mut GLchar);
+    log.truncate(written as usize);
+    String::from_utf8_lossy(&log).into_owned()
+}
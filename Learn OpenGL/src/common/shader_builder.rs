@@ -0,0 +1,130 @@
+use common::shader::{ShaderError, ShaderStage};
+use common::shader_preprocessor::{Preprocessor, ShaderSource};
+
+#[derive(Debug, Clone)]
+enum StageSource {
+    File(String),
+    Text { name: String, text: String },
+}
+
+/// Describes a program made of any valid set of stages, for `ReloadableShader::from_builder` to
+/// build, e.g. a tessellated pipeline:
+///
+/// ```ignore
+/// let shader = ReloadableShader::from_builder(ShaderBuilder::new()
+///     .vertex("heightmap/vertex_shader.glsl")
+///     .file(ShaderStage::TessellationControl, "heightmap/tessellation_control_shader.glsl")
+///     .file(ShaderStage::TessellationEvaluation, "heightmap/tessellation_evaluation_shader.glsl")
+///     .fragment("heightmap/fragment_shader.glsl"))?;
+/// ```
+///
+/// or a compute program, which has a compute shader and nothing else. Every source goes through
+/// the builder's `Preprocessor`, including sources given as strings. Giving a stage a second
+/// source replaces the first.
+#[derive(Debug, Clone)]
+pub struct ShaderBuilder {
+    stages: Vec<(ShaderStage, StageSource)>,
+    preprocessor: Preprocessor,
+}
+
+impl ShaderBuilder {
+    pub fn new() -> Self {
+        ShaderBuilder {
+            stages: vec![],
+            preprocessor: Preprocessor::new(),
+        }
+    }
+
+    /// The `#include` folders and `#define`s for every stage
+    pub fn preprocessor(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
+
+    /// Read `stage` from the asset at `path`
+    pub fn file(self, stage: ShaderStage, path: &str) -> Self {
+        self.stage(stage, StageSource::File(path.to_string()))
+    }
+
+    pub fn files(self, stages: &[(ShaderStage, &str)]) -> Self {
+        stages.iter().fold(self, |builder, &(stage, path)| builder.file(stage, path))
+    }
+
+    /// Use `text` as the source of `stage`. `name` stands in for a path in error messages and
+    /// relative `#include`s are looked for in its folder.
+    pub fn source(self, stage: ShaderStage, name: &str, text: &str) -> Self {
+        self.stage(stage, StageSource::Text { name: name.to_string(), text: text.to_string() })
+    }
+
+    pub fn vertex(self, path: &str) -> Self {
+        self.file(ShaderStage::Vertex, path)
+    }
+
+    pub fn geometry(self, path: &str) -> Self {
+        self.file(ShaderStage::Geometry, path)
+    }
+
+    pub fn fragment(self, path: &str) -> Self {
+        self.file(ShaderStage::Fragment, path)
+    }
+
+    pub fn compute(self, path: &str) -> Self {
+        self.file(ShaderStage::Compute, path)
+    }
+
+    /// The path or name of each stage's source, in pipeline order
+    pub fn names(&self) -> Vec<&str> {
+        self.stages.iter()
+            .map(|&(_, ref source)| match *source {
+                StageSource::File(ref path) => &path[..],
+                StageSource::Text { ref name, .. } => &name[..],
+            })
+            .collect()
+    }
+
+    /// Check that the stages can be linked together and preprocess each of them
+    pub fn preprocess(&self) -> Result<Vec<(ShaderStage, ShaderSource)>, ShaderError> {
+        let stages: Vec<ShaderStage> = self.stages.iter().map(|&(stage, _)| stage).collect();
+        if let Some(problem) = pipeline_problem(&stages) {
+            return Err(ShaderError::InvalidPipeline { stages: stages, problem: problem });
+        }
+
+        let mut sources = Vec::with_capacity(self.stages.len());
+        for &(stage, ref source) in self.stages.iter() {
+            let processed = match *source {
+                StageSource::File(ref path) => self.preprocessor.process(stage, path)?,
+                StageSource::Text { ref name, ref text } => self.preprocessor.process_text(stage, name, text)?,
+            };
+            sources.push((stage, processed));
+        }
+        Ok(sources)
+    }
+
+    /// Add or replace a stage, keeping them in pipeline order
+    fn stage(mut self, stage: ShaderStage, source: StageSource) -> Self {
+        self.stages.retain(|&(existing, _)| existing != stage);
+        self.stages.push((stage, source));
+        self.stages.sort_by_key(|&(stage, _)| stage);
+        self
+    }
+}
+
+/// Why `stages` can't make up a program, if they can't
+fn pipeline_problem(stages: &[ShaderStage]) -> Option<&'static str> {
+    let has = |stage: ShaderStage| stages.contains(&stage);
+    if stages.is_empty() {
+        Some("there are no stages")
+    } else if has(ShaderStage::Compute) {
+        if stages.len() > 1 {
+            Some("a compute shader can't be linked with other stages")
+        } else {
+            None
+        }
+    } else if !has(ShaderStage::Vertex) {
+        Some("drawing needs a vertex shader")
+    } else if has(ShaderStage::TessellationControl) && !has(ShaderStage::TessellationEvaluation) {
+        Some("a tessellation control shader needs a tessellation evaluation shader")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use common::assets;
+use common::shader::{ShaderError, ShaderStage};
+
+#[cfg(test)]
+#[path = "./shader_preprocessor_tests.rs"]
+mod shader_preprocessor_tests;
+
+/// where shaders shared between lessons live, relative to the `src` folder lessons run from
+pub const COMMON_SHADER_FOLDER: &'static str = "common/shaders";
+
+/// A file read while preprocessing a shader
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: String,
+    pub text: String,
+}
+
+/// A shader's source after `#include`s are expanded and `#define`s injected, along with where each
+/// line came from so compile errors can point at the file that was actually edited
+#[derive(Debug, Clone)]
+pub struct ShaderSource {
+    /// the expanded source, as passed to the driver
+    pub text: String,
+    /// every file read to build the source, the requested one first
+    pub files: Vec<SourceFile>,
+    // the index into `files` and the line number for each line of `text`; None for injected lines
+    origins: Vec<Option<(usize, usize)>>,
+}
+
+impl ShaderSource {
+    /// The path of the file that was asked for, as opposed to the ones it includes
+    pub fn path(&self) -> &str {
+        &self.files[0].path
+    }
+
+    /// The file and line number that line `line_number` (starting from 1) of the expanded source
+    /// came from. None for injected `#define`s and lines out of range.
+    pub fn origin(&self, line_number: usize) -> Option<(&SourceFile, usize)> {
+        if line_number == 0 {
+            return None;
+        }
+        match self.origins.get(line_number - 1) {
+            Some(&Some((file_index, file_line))) => Some((&self.files[file_index], file_line)),
+            _ => None,
+        }
+    }
+}
+
+/// Expands `#include "name.glsl"` lines and injects `#define`s ahead of a shader's source.
+///
+/// Paths are asset names, found through `common::assets`. An included file is looked for next to
+/// the file including it, then in each include folder in the order they were added, starting with
+/// `COMMON_SHADER_FOLDER`. Defines go right after the `#version` line, which GLSL requires to come
+/// first.
+///
+/// Only whole lines are looked at, so an `#include` is expanded even inside a block comment or an
+/// `#if` that's false, and including the same file twice declares everything in it twice.
+#[derive(Debug, Clone)]
+pub struct Preprocessor {
+    include_folders: Vec<String>,
+    defines: Vec<(String, String)>,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        let preprocessor = Preprocessor {
+            include_folders: vec![],
+            defines: vec![],
+        };
+        preprocessor.include_folder(COMMON_SHADER_FOLDER)
+    }
+
+    /// Also look for included files in `folder`, after the ones added before it
+    pub fn include_folder(mut self, folder: &str) -> Self {
+        if !self.include_folders.iter().any(|added| added == folder) {
+            self.include_folders.push(folder.to_string());
+        }
+        self
+    }
+
+    /// Inject `#define name value`, replacing an earlier define of the same name
+    pub fn define<T: Display>(mut self, name: &str, value: T) -> Self {
+        let value = value.to_string();
+        match self.defines.iter().position(|&(ref defined, _)| defined == name) {
+            Some(index) => self.defines[index].1 = value,
+            None => self.defines.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    pub fn process(&self, stage: ShaderStage, path: &str) -> Result<ShaderSource, ShaderError> {
+        let text = assets::read_to_string(path)
+            .map_err(|error| ShaderError::Io { stage: stage, path: path.to_string(), error: error })?;
+        self.process_text(stage, path, &text)
+    }
+
+    /// Like `process` for source that isn't in a file. `name` stands in for its path in error
+    /// messages, and relative `#include`s are looked for in its folder.
+    pub fn process_text(&self, stage: ShaderStage, name: &str, text: &str) -> Result<ShaderSource, ShaderError> {
+        let mut expansion = Expansion::new(stage);
+        expansion.expand_text(self, name, canonical_path(name), text)?;
+        Ok(self.finish(expansion))
+    }
+
+    /// Inject the defines into the expanded source
+    fn finish(&self, mut expansion: Expansion) -> ShaderSource {
+        let define_lines: Vec<String> = self.defines.iter()
+            .map(|&(ref name, ref value)| format!("#define {} {}", name, value))
+            .collect();
+        let version_line = expansion.lines.iter().position(|line| directive(line, "version").is_some());
+        let insert_at = version_line.map(|index| index + 1).unwrap_or(0);
+        for (offset, define_line) in define_lines.into_iter().enumerate() {
+            expansion.lines.insert(insert_at + offset, define_line);
+            expansion.origins.insert(insert_at + offset, None);
+        }
+
+        let mut text = expansion.lines.join("\n");
+        text.push('\n');
+        ShaderSource {
+            text: text,
+            files: expansion.files,
+            origins: expansion.origins,
+        }
+    }
+
+    /// The first existing file called `name` in the folder of `including_path` or an include
+    /// folder. Also returns every path tried, for the error message if there's none.
+    fn resolve_include(&self, including_path: &str, name: &str) -> (Option<String>, Vec<String>) {
+        let including_folder = Path::new(including_path).parent().unwrap_or(Path::new(""));
+        let folders = Some(including_folder).into_iter()
+            .chain(self.include_folders.iter().map(|folder| Path::new(folder)));
+
+        let mut searched = vec![];
+        for folder in folders {
+            let candidate = folder.join(name).to_string_lossy().into_owned();
+            if searched.contains(&candidate) {
+                continue;
+            }
+            if assets::exists(&candidate) {
+                return (Some(candidate), searched);
+            }
+            searched.push(candidate);
+        }
+        (None, searched)
+    }
+}
+
+/// The state of one `Preprocessor::process` call
+struct Expansion {
+    stage: ShaderStage,
+    files: Vec<SourceFile>,
+    lines: Vec<String>,
+    origins: Vec<Option<(usize, usize)>>,
+    // the canonical and given paths of the files currently being expanded, outermost first
+    include_stack: Vec<(String, String)>,
+}
+
+impl Expansion {
+    fn new(stage: ShaderStage) -> Self {
+        Expansion {
+            stage: stage,
+            files: vec![],
+            lines: vec![],
+            origins: vec![],
+            include_stack: vec![],
+        }
+    }
+
+    fn expand_file(&mut self, preprocessor: &Preprocessor, path: &str) -> Result<(), ShaderError> {
+        let text = assets::read_to_string(path)
+            .map_err(|error| ShaderError::Io { stage: self.stage, path: path.to_string(), error: error })?;
+        self.expand_text(preprocessor, path, canonical_path(path), &text)
+    }
+
+    fn expand_text(
+        &mut self,
+        preprocessor: &Preprocessor,
+        path: &str,
+        canonical_path: String,
+        text: &str,
+    ) -> Result<(), ShaderError> {
+        let cycle_start = self.include_stack.iter().position(|&(ref included, _)| *included == canonical_path);
+        if let Some(start) = cycle_start {
+            let mut chain: Vec<String> = self.include_stack[start..].iter()
+                .map(|&(_, ref included_path)| included_path.clone())
+                .collect();
+            chain.push(path.to_string());
+            return Err(ShaderError::IncludeCycle { stage: self.stage, chain: chain });
+        }
+
+        let file_index = self.files.len();
+        self.files.push(SourceFile { path: path.to_string(), text: text.to_string() });
+        self.include_stack.push((canonical_path, path.to_string()));
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let include = match directive(line, "include") {
+                Some(argument) => argument,
+                None => {
+                    self.lines.push(line.to_string());
+                    self.origins.push(Some((file_index, line_number)));
+                    continue;
+                },
+            };
+
+            let name = match quoted_name(include) {
+                Some(name) => name,
+                None => return Err(ShaderError::MalformedInclude {
+                    stage: self.stage,
+                    path: path.to_string(),
+                    line: line_number,
+                    text: line.trim().to_string(),
+                }),
+            };
+            match preprocessor.resolve_include(path, name) {
+                (Some(included_path), _) => self.expand_file(preprocessor, &included_path)?,
+                (None, searched) => return Err(ShaderError::IncludeNotFound {
+                    stage: self.stage,
+                    path: path.to_string(),
+                    line: line_number,
+                    name: name.to_string(),
+                    searched: searched,
+                }),
+            }
+        }
+
+        self.include_stack.pop();
+        Ok(())
+    }
+}
+
+/// The full path of the file asset `name` resolves to, so the same file reached through different
+/// relative paths is recognized when looking for include cycles. Names that aren't files are
+/// used as they are.
+fn canonical_path(name: &str) -> String {
+    assets::file_path(name)
+        .and_then(|file_path| fs::canonicalize(file_path).ok())
+        .map(|canonical| canonical.to_string_lossy().into_owned())
+        .unwrap_or(name.to_string())
+}
+
+/// The rest of the line if it's the given preprocessor directive, e.g. `"lighting.glsl"` for
+/// `#include "lighting.glsl"`. Whitespace is allowed around the `#`, as in GLSL.
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let line = line.trim();
+    if !line.starts_with('#') {
+        return None;
+    }
+    let after_hash = line[1..].trim_start();
+    if !after_hash.starts_with(name) {
+        return None;
+    }
+    let argument = &after_hash[name.len()..];
+    match argument.chars().next() {
+        None => Some(argument),
+        Some(c) if c.is_whitespace() || c == '"' => Some(argument.trim()),
+        _ => None,
+    }
+}
+
+/// `lighting.glsl` from `"lighting.glsl"`, optionally followed by a `//` comment
+fn quoted_name(argument: &str) -> Option<&str> {
+    if !argument.starts_with('"') {
+        return None;
+    }
+    let end = match argument[1..].find('"') {
+        Some(end) => end + 1,
+        None => return None,
+    };
+    let rest = argument[end + 1..].trim();
+    if end == 1 || !(rest.is_empty() || rest.starts_with("//")) {
+        return None;
+    }
+    Some(&argument[1..end])
+}
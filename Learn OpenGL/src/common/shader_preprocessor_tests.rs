@@ -0,0 +1,171 @@
+use super::*;
+
+/// The path of a file in `test/shader_preprocessor`, independent of the folder the tests run from
+fn fixture(name: &str) -> String {
+    format!("{}/test/shader_preprocessor/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn lines(source: &ShaderSource) -> Vec<&str> {
+    source.text.lines().collect()
+}
+
+#[test]
+fn includes_are_expanded_in_place() {
+    let source = Preprocessor::new().process(ShaderStage::Fragment, &fixture("main.glsl")).unwrap();
+    assert_eq!(&lines(&source)[..5], &[
+        "#version 330 core",
+        "vec4 shade() {",
+        "    return vec4(1.0);",
+        "}",
+        "",
+    ]);
+    assert_eq!(source.path(), fixture("main.glsl"));
+    let paths: Vec<&str> = source.files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(paths, vec![fixture("main.glsl"), fixture("shade.glsl")]);
+}
+
+#[test]
+fn includes_are_looked_for_next_to_the_including_file_then_in_the_common_folder() {
+    let text = "#include \"scale.glsl\"\nfloat scaled = LIBRARY_SCALE;\n";
+
+    match Preprocessor::new().process_text(ShaderStage::Vertex, &fixture("inline.glsl"), text) {
+        Err(ShaderError::IncludeNotFound { ref name, line, ref searched, .. }) => {
+            assert_eq!(name, "scale.glsl");
+            assert_eq!(line, 1);
+            assert_eq!(searched.len(), 2);
+            assert_eq!(searched[0], fixture("scale.glsl"));
+            assert!(searched[1].starts_with(COMMON_SHADER_FOLDER));
+        },
+        other => panic!("expected a missing include, got {:?}", other),
+    }
+
+    let source = Preprocessor::new()
+        .process_text(ShaderStage::Vertex, &fixture("library/inline.glsl"), text)
+        .unwrap();
+    assert_eq!(lines(&source)[1], "const float LIBRARY_SCALE = 2.0;");
+    assert_eq!(source.files[1].path, fixture("library/scale.glsl"));
+
+    // lighting.glsl is only in the common folder
+    let source = Preprocessor::new()
+        .process_text(ShaderStage::Fragment, &fixture("inline.glsl"), "#include \"lighting.glsl\"\n")
+        .unwrap();
+    assert!(source.files[1].path.ends_with("shaders/lighting.glsl"), "{}", source.files[1].path);
+}
+
+#[test]
+fn includes_are_looked_for_in_added_include_folders() {
+    let text = "#include \"scale.glsl\"\nfloat scaled = LIBRARY_SCALE;\n";
+
+    let source = Preprocessor::new()
+        .include_folder(&fixture("library"))
+        .process_text(ShaderStage::Vertex, &fixture("inline.glsl"), text)
+        .unwrap();
+    assert_eq!(lines(&source)[1], "const float LIBRARY_SCALE = 2.0;");
+    assert_eq!(source.files[1].path, fixture("library/scale.glsl"));
+
+    let preprocessor = Preprocessor::new().include_folder(&fixture("missing"));
+    match preprocessor.process_text(ShaderStage::Vertex, "inline.glsl", text) {
+        Err(ShaderError::IncludeNotFound { ref searched, .. }) => {
+            assert_eq!(searched.len(), 3);
+            assert!(searched[1].starts_with(COMMON_SHADER_FOLDER));
+            assert_eq!(searched[2], fixture("missing/scale.glsl"));
+        },
+        other => panic!("expected a missing include, got {:?}", other),
+    }
+}
+
+#[test]
+fn include_cycles_are_reported_with_the_chain() {
+    match Preprocessor::new().process(ShaderStage::Fragment, &fixture("cycle_a.glsl")) {
+        Err(ShaderError::IncludeCycle { stage, ref chain }) => {
+            assert_eq!(stage, ShaderStage::Fragment);
+            assert_eq!(chain, &vec![fixture("cycle_a.glsl"), fixture("cycle_b.glsl"), fixture("cycle_a.glsl")]);
+        },
+        other => panic!("expected an include cycle, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_includes_are_rejected_with_their_line() {
+    let malformed = [
+        "#include shade.glsl",
+        "#include <shade.glsl>",
+        "#include \"shade.glsl",
+        "#include \"\"",
+        "#include \"shade.glsl\" extra",
+        "#include",
+    ];
+    for text in malformed.iter() {
+        let source = format!("#version 330 core\n{}\n", text);
+        match Preprocessor::new().process_text(ShaderStage::Fragment, &fixture("inline.glsl"), &source) {
+            Err(ShaderError::MalformedInclude { line, text: ref line_text, .. }) => {
+                assert_eq!(line, 2);
+                assert_eq!(line_text, text);
+            },
+            other => panic!("expected {} to be malformed, got {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn include_directives_allow_spacing_and_trailing_comments() {
+    let text = "  #  include \"shade.glsl\" // for shade()\n#includes aren't directives\n";
+    let source = Preprocessor::new()
+        .process_text(ShaderStage::Fragment, &fixture("inline.glsl"), text)
+        .unwrap();
+    assert_eq!(lines(&source), vec!["vec4 shade() {", "    return vec4(1.0);", "}", "#includes aren't directives"]);
+}
+
+#[test]
+fn defines_go_after_the_version_line() {
+    let text = "#version 330 core\nvoid main() {}\n";
+    let source = Preprocessor::new()
+        .define("POINT_LIGHT_COUNT", 4)
+        .define("SHADOWS", 1)
+        .define("POINT_LIGHT_COUNT", 5)
+        .process_text(ShaderStage::Fragment, "inline.glsl", text)
+        .unwrap();
+    assert_eq!(lines(&source), vec![
+        "#version 330 core",
+        "#define POINT_LIGHT_COUNT 5",
+        "#define SHADOWS 1",
+        "void main() {}",
+    ]);
+
+    let source = Preprocessor::new()
+        .define("SHADOWS", 1)
+        .process_text(ShaderStage::Fragment, "inline.glsl", "void main() {}\n")
+        .unwrap();
+    assert_eq!(lines(&source), vec!["#define SHADOWS 1", "void main() {}"]);
+}
+
+#[test]
+fn expanded_lines_map_back_to_their_files() {
+    let source = Preprocessor::new()
+        .define("SHADOWS", 1)
+        .process(ShaderStage::Fragment, &fixture("main.glsl"))
+        .unwrap();
+
+    let origin = |line_number| source.origin(line_number).map(|(file, line)| (file.path.clone(), line));
+    assert_eq!(origin(1), Some((fixture("main.glsl"), 1)));
+    assert_eq!(origin(2), None);
+    assert_eq!(origin(3), Some((fixture("shade.glsl"), 1)));
+    assert_eq!(origin(5), Some((fixture("shade.glsl"), 3)));
+    assert_eq!(origin(6), Some((fixture("main.glsl"), 3)));
+    assert_eq!(origin(0), None);
+    assert_eq!(origin(100), None);
+}
+
+#[test]
+fn compile_errors_point_at_the_included_file() {
+    let source = Preprocessor::new().process(ShaderStage::Fragment, &fixture("main.glsl")).unwrap();
+    let error = ShaderError::Compile {
+        stage: ShaderStage::Fragment,
+        path: fixture("main.glsl"),
+        source: source,
+        log: "0:3(12): error: syntax error".to_string(),
+    };
+    let message = error.to_string();
+    assert!(message.contains(&format!("in {}:2", fixture("shade.glsl"))), "{}", message);
+    assert!(message.contains(">    2 |     return vec4(1.0);"), "{}", message);
+}
@@ -0,0 +1,7 @@
+// The camera's matrices, shared by every program through a uniform buffer that the scene graph
+// fills once per frame
+
+layout (std140) uniform Camera {
+	mat4 viewMatrix;
+	mat4 projectionMatrix;
+};
@@ -0,0 +1,13 @@
+// The scene's lights in view space, shared by every program through a uniform buffer that the
+// scene graph fills once per frame. Include lighting.glsl first for the light structs.
+
+// must match MAX_POINT_LIGHTS in common/uniform_buffer.rs
+#define MAX_POINT_LIGHTS 128
+
+layout (std140) uniform Lights {
+	// how many elements of pointLights are in use
+	int pointLightCount;
+	PointLight pointLights[MAX_POINT_LIGHTS];
+	DirectionalLight directionalLight;
+	Spotlight spotlight;
+};
@@ -0,0 +1,132 @@
+// Material and light structs shared by the lit lessons, and the color each kind of light
+// contributes to a fragment. The including shader must declare the `normal` and `texCoords`
+// inputs before including this file.
+
+struct Material {
+	vec3 ambientColor;
+	sampler2D diffuseColor;
+	sampler2D specularColor;
+	bool emissionPresent;
+	sampler2D emissionColor;
+	float shininess;
+};
+
+struct PointLight {
+	vec3 positionView;
+	vec3 ambientColor;
+	vec3 diffuseColor;
+	vec3 specularColor;
+	float constant;
+	float linear;
+	float quadratic;
+};
+
+struct DirectionalLight {
+	vec3 positionView;
+	vec3 ambientColor;
+	vec3 diffuseColor;
+	vec3 specularColor;
+};
+
+struct Spotlight {
+	vec3 positionView;
+	vec3 ambientColor;
+	vec3 diffuseColor;
+	vec3 specularColor;
+	vec3 directionView;
+	float cutOffInner;	// the cosine of the splotlight's inner angle
+	float cutOffOuter;	// the cosine of the splotlight's outer angle
+};
+
+vec3 pointLightColor(
+	PointLight light,
+	Material material,
+	vec3 vertexPositionView,
+	vec3 normalizedNormal
+) {
+	// this is the incident vector of the light on the surface
+	vec3 directionToLightInViewSpace = normalize(vertexPositionView - light.positionView);
+	vec3 baseColor = vec3(texture(material.diffuseColor, texCoords));
+
+	// attenuation
+	float distance = length(light.positionView - vertexPositionView);
+	float attenuation = 1.0 / (light.constant + (light.linear * distance) + (light.quadratic * distance * distance));
+
+	// ambient
+	vec3 ambientColor = attenuation * light.ambientColor * baseColor;
+
+	// diffuse
+	float diffuseFraction = max(dot(normalizedNormal, -directionToLightInViewSpace), 0.0);
+	vec3 diffuseColor = attenuation * light.diffuseColor * diffuseFraction * baseColor;
+
+	// specular
+	vec3 viewDirection = normalize(vertexPositionView);
+	vec3 reflectionDirection = reflect(directionToLightInViewSpace, normal);
+	float specularFraction = pow(max(dot(-viewDirection, reflectionDirection), 0.0), material.shininess);
+	vec3 specularColor = attenuation * light.specularColor * specularFraction * vec3(texture(material.specularColor, texCoords));
+
+	return ambientColor + diffuseColor + specularColor;
+}
+
+vec3 directionalLightColor(
+	DirectionalLight light,
+	Material material,
+	vec3 vertexPositionView,
+	vec3 normalizedNormal
+) {
+	// this is the incident vector of the light on the surface
+	vec3 directionToLightInViewSpace = light.positionView;
+	vec3 baseColor = vec3(texture(material.diffuseColor, texCoords));
+
+	// ambient
+	vec3 ambientColor = light.ambientColor * baseColor;
+
+	// diffuse
+	float diffuseFraction = max(dot(normalizedNormal, -directionToLightInViewSpace), 0.0);
+	vec3 diffuseColor = light.diffuseColor * diffuseFraction * baseColor;
+
+	// specular
+	vec3 viewDirection = normalize(vertexPositionView);
+	vec3 reflectionDirection = reflect(directionToLightInViewSpace, normal);
+	float specularFraction = pow(max(dot(-viewDirection, reflectionDirection), 0.0), material.shininess);
+	vec3 specularColor = light.specularColor * specularFraction * vec3(texture(material.specularColor, texCoords));
+
+	return ambientColor + diffuseColor + specularColor;
+}
+
+vec3 spotlightColor(
+	Spotlight light,
+	Material material,
+	vec3 vertexPositionView,
+	vec3 normalizedNormal
+) {
+	// a scene without a spotlight leaves it all zeros: no direction to normalize and no cone to
+	// divide by, and nothing to add
+	float epsilon = light.cutOffInner - light.cutOffOuter;
+	if (epsilon <= 0.0) {
+		return vec3(0.0);
+	}
+
+	// this is the incident vector of the light on the surface
+	vec3 directionToLightInViewSpace = normalize(vertexPositionView - light.positionView);
+	vec3 baseColor = vec3(texture(material.diffuseColor, texCoords));
+
+	vec3 finalColor;
+	float theta = dot(directionToLightInViewSpace, normalize(light.directionView));
+	float intensity = clamp((theta - light.cutOffOuter)/epsilon, 0.0, 1.0);
+
+	// ambient
+	vec3 ambientColor = light.ambientColor * baseColor;
+
+	// diffuse
+	float diffuseFraction = max(dot(normalizedNormal, -directionToLightInViewSpace), 0.0);
+	vec3 diffuseColor = intensity * light.diffuseColor * diffuseFraction * baseColor;
+
+	// specular
+	vec3 viewDirection = normalize(vertexPositionView);
+	vec3 reflectionDirection = reflect(directionToLightInViewSpace, normal);
+	float specularFraction = pow(max(dot(-viewDirection, reflectionDirection), 0.0), material.shininess);
+	vec3 specularColor = intensity * light.specularColor * specularFraction * vec3(texture(material.specularColor, texCoords));
+
+	return ambientColor + diffuseColor + specularColor;
+}
@@ -0,0 +1,15 @@
+#version 330 core
+
+#include "camera_block.glsl"
+
+layout (location = 0) in vec3 aPos;
+
+out vec3 direction;
+
+void main() {
+	direction = aPos;
+	// without the view matrix's translation the sky never gets any closer, and using w as z puts
+	// every fragment on the far plane, behind everything already drawn
+	vec4 position = projectionMatrix * mat4(mat3(viewMatrix)) * vec4(aPos, 1.0);
+	gl_Position = position.xyww;
+}
@@ -0,0 +1,111 @@
+use std::mem;
+use std::os::raw::c_void;
+use std::rc::Rc;
+
+use gl;
+use gl::types::*;
+
+use common::reloadable_shader::ReloadableShader;
+use common::shader::ShaderError;
+use common::texture::Cubemap;
+use common::uniform::TextureUnit;
+use common::vertex_layout::VertexLayout;
+
+const VERTEX_SHADER_PATH: &'static str = "common/shaders/skybox_vertex_shader.glsl";
+const FRAGMENT_SHADER_PATH: &'static str = "common/shaders/skybox_fragment_shader.glsl";
+
+/// A cubemap drawn around the camera, in place of a flat clear color. It reads the view and
+/// projection matrices from the shared camera block.
+///
+/// The sky is drawn on the far plane, so it should be drawn after everything else: depth testing
+/// then skips every pixel that's already covered instead of shading it only to be overwritten.
+pub struct Skybox {
+    shader_program: ReloadableShader,
+    vao: GLuint,
+    vbo: GLuint,
+    cubemap: Rc<Cubemap>,
+}
+
+impl Skybox {
+    pub fn new(cubemap: Rc<Cubemap>) -> Result<Self, ShaderError> {
+        let shader_program = ReloadableShader::new(VERTEX_SHADER_PATH, FRAGMENT_SHADER_PATH)?;
+        let layout = VertexLayout::new(&[("aPos", 0, 3)]);
+        layout.validate(&shader_program)?;
+
+        // a cube around the origin, with each corner's position as its sampling direction
+        let vertices: [f32; 108] = [
+            -1.0,  1.0, -1.0,   -1.0, -1.0, -1.0,    1.0, -1.0, -1.0,
+             1.0, -1.0, -1.0,    1.0,  1.0, -1.0,   -1.0,  1.0, -1.0,
+
+            -1.0, -1.0,  1.0,   -1.0, -1.0, -1.0,   -1.0,  1.0, -1.0,
+            -1.0,  1.0, -1.0,   -1.0,  1.0,  1.0,   -1.0, -1.0,  1.0,
+
+             1.0, -1.0, -1.0,    1.0, -1.0,  1.0,    1.0,  1.0,  1.0,
+             1.0,  1.0,  1.0,    1.0,  1.0, -1.0,    1.0, -1.0, -1.0,
+
+            -1.0, -1.0,  1.0,   -1.0,  1.0,  1.0,    1.0,  1.0,  1.0,
+             1.0,  1.0,  1.0,    1.0, -1.0,  1.0,   -1.0, -1.0,  1.0,
+
+            -1.0,  1.0, -1.0,    1.0,  1.0, -1.0,    1.0,  1.0,  1.0,
+             1.0,  1.0,  1.0,   -1.0,  1.0,  1.0,   -1.0,  1.0, -1.0,
+
+            -1.0, -1.0, -1.0,   -1.0, -1.0,  1.0,    1.0, -1.0, -1.0,
+             1.0, -1.0, -1.0,   -1.0, -1.0,  1.0,    1.0, -1.0,  1.0,
+        ];
+
+        let (mut vao, mut vbo) = (0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindVertexArray(vao);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(gl::ARRAY_BUFFER,
+                           (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                           &vertices[0] as *const f32 as *const c_void,
+                           gl::STATIC_DRAW);
+
+            layout.apply();
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+
+        Ok(Skybox {
+            shader_program: shader_program,
+            vao: vao,
+            vbo: vbo,
+            cubemap: cubemap,
+        })
+    }
+
+    pub fn render(&self) {
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+            // the sky's depth is exactly 1.0, the value the depth buffer is cleared to
+            gl::DepthFunc(gl::LEQUAL);
+
+            self.shader_program.use_program();
+            self.cubemap.bind(TextureUnit(0));
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, 36);
+            gl::BindVertexArray(0);
+
+            gl::DepthFunc(gl::LESS);
+        }
+    }
+
+    pub fn reload_if_changed(&mut self) {
+        self.shader_program.reload_if_changed();
+    }
+}
+
+impl Drop for Skybox {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
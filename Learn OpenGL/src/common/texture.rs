@@ -1,45 +1,622 @@
+use std::error::Error;
+use std::f32::consts::PI;
+use std::ffi::CStr;
+use std::fmt;
+use std::io::Cursor;
 use std::os::raw::c_void;
-use std::path::Path;
 
 use gl;
+use gl::types::*;
+
+use cgmath::{InnerSpace, Vector3, Vector4};
 
 use image;
 use image::GenericImage;
 use image::DynamicImage::*;
+use image::hdr::HDRDecoder;
+
+use png;
+use png::HasParameters;
+
+use common::assets;
+use common::assets::AssetError;
+use common::uniform::TextureUnit;
+
+#[cfg(test)]
+#[path = "./texture_tests.rs"]
+mod texture_tests;
+
+// from EXT_texture_filter_anisotropic, which isn't in the core profile bindings
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+#[derive(Debug)]
+pub enum TextureError {
+    /// the image couldn't be found, read or decoded
+    Asset(AssetError),
+    /// the image's pixels can't be stored the way the options ask, e.g. a 16-bit image as sRGB
+    UnsupportedFormat { path: String, problem: &'static str },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TextureError::Asset(ref error) => write!(f, "failed to load texture: {}", error),
+            TextureError::UnsupportedFormat { ref path, problem } => {
+                write!(f, "can't make a texture of {}: {}", path, problem)
+            },
+        }
+    }
+}
+
+impl Error for TextureError {
+    fn description(&self) -> &str {
+        match *self {
+            TextureError::Asset(_) => "failed to load texture image",
+            TextureError::UnsupportedFormat { .. } => "unsupported texture format",
+        }
+    }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            TextureError::Asset(ref error) => Some(error),
+            TextureError::UnsupportedFormat { .. } => None,
+        }
+    }
+}
+
+impl From<AssetError> for TextureError {
+    fn from(error: AssetError) -> Self {
+        TextureError::Asset(error)
+    }
+}
+
+/// What happens to texture coordinates outside of [0, 1]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// use the options' border color
+    ClampToBorder,
+}
+
+impl Wrap {
+    fn gl_enum(&self) -> GLenum {
+        match *self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// How the values of an 8-bit image are encoded. Color maps are usually painted in sRGB, and
+/// sampling them as sRGB gives the linear values that lighting needs. Data such as specular and
+/// normal maps is already linear. Images with more than 8 bits per channel are always linear.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ColorSpace {
+    Linear,
+    Srgb,
+}
+
+/// How floating-point images, e.g. Radiance `.hdr` files, are stored on the GPU
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FloatPrecision {
+    /// 16-bit half floats, which are enough for most colors and take half the memory. The largest
+    /// is 65504.
+    Half,
+    Full,
+}
+
+/// How a texture is loaded and sampled. The defaults flip the image so its first row is at the
+/// bottom, as OpenGL expects, treat it as linear, and sample it with trilinear filtering and
+/// repeat wrapping.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureOptions {
+    pub flip_y: bool,
+    pub color_space: ColorSpace,
+    pub float_precision: FloatPrecision,
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+    /// the filter within a mip level when the texture is minified; with mipmaps, the same filter
+    /// is used between levels
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    pub mipmaps: bool,
+    /// the maximum anisotropy, where 1 turns anisotropic filtering off. It's limited to what the
+    /// driver supports, and ignored if it has no support at all.
+    pub anisotropy: f32,
+    /// the color sampled outside the texture with `Wrap::ClampToBorder`; OpenGL's transparent
+    /// black if it's None
+    pub border_color: Option<Vector4<f32>>,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        TextureOptions {
+            flip_y: true,
+            color_space: ColorSpace::Linear,
+            float_precision: FloatPrecision::Half,
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmaps: true,
+            anisotropy: 1.0,
+            border_color: None,
+        }
+    }
+}
+
+impl TextureOptions {
+    pub fn new() -> Self {
+        TextureOptions::default()
+    }
+
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn float_precision(mut self, float_precision: FloatPrecision) -> Self {
+        self.float_precision = float_precision;
+        self
+    }
+
+    /// Wrap both coordinates the same way
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap_s = wrap;
+        self.wrap_t = wrap;
+        self
+    }
+
+    pub fn filter(mut self, min_filter: Filter, mag_filter: Filter) -> Self {
+        self.min_filter = min_filter;
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    pub fn anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    pub fn border_color(mut self, border_color: Vector4<f32>) -> Self {
+        self.border_color = Some(border_color);
+        self
+    }
+
+    fn min_filter_enum(&self) -> GLenum {
+        match (self.min_filter, self.mipmaps) {
+            (Filter::Nearest, false) => gl::NEAREST,
+            (Filter::Linear, false) => gl::LINEAR,
+            (Filter::Nearest, true) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    fn mag_filter_enum(&self) -> GLenum {
+        match self.mag_filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+/// A 2D texture, deleted when it's dropped
+#[derive(Debug)]
+pub struct Texture {
+    id: GLuint,
+}
 
-pub fn load_texture(path: &str, flip_y: bool) -> u32 {
-    let mut texture_id = 0;
+impl Texture {
+    /// Load the image asset at `path` into a new texture. 8-bit images in any format `image`
+    /// reads, 16-bit PNGs and Radiance `.hdr` files are supported.
+    pub fn load(path: &str, options: &TextureOptions) -> Result<Texture, TextureError> {
+        let pixels = Pixels::load(path, options.flip_y)?;
+        // check the format before creating anything that would have to be deleted
+        pixels.formats(options)?;
 
-    unsafe {
-        gl::GenTextures(1, &mut texture_id);
-        let mut img = image::open(&Path::new(path)).expect(&format!("Texture {} failed to load", path));
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+        }
+        // owned from here on, so the texture is deleted if the upload fails
+        let texture = Texture { id: id };
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture.id);
+            let uploaded = pixels.upload(gl::TEXTURE_2D, options);
+            if uploaded.is_ok() {
+                apply_options(gl::TEXTURE_2D, options);
+            }
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            uploaded?;
+        }
+
+        Ok(texture)
+    }
+
+    /// Bind the texture to `unit`, which leaves that unit active
+    pub unsafe fn bind(&self, unit: TextureUnit) {
+        gl::ActiveTexture(gl::TEXTURE0 + unit.0);
+        gl::BindTexture(gl::TEXTURE_2D, self.id);
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// A cube map texture, e.g. for a skybox or environment reflections, deleted when it's dropped.
+/// It's sampled with a direction rather than texture coordinates.
+#[derive(Debug)]
+pub struct Cubemap {
+    id: GLuint,
+}
+
+impl Cubemap {
+    /// Load a cubemap from six square images of the same size, in the order of the
+    /// `TEXTURE_CUBE_MAP_POSITIVE_X + i` targets: right (+X), left (-X), top (+Y), bottom (-Y),
+    /// front (+Z) and back (-Z). Cubemap faces start with their top row, so the images are never
+    /// flipped, whatever `options.flip_y` says.
+    pub fn from_faces(paths: &[&str; 6], options: &TextureOptions) -> Result<Cubemap, TextureError> {
+        let faces = load_faces(paths, options)?;
+        Cubemap::from_pixels(&faces, options)
+    }
+
+    /// Load a cubemap from a single equirectangular (latitude-longitude) panorama, such as most
+    /// `.hdr` environment maps, resampling it into faces of `face_size` texels. The middle of the
+    /// panorama faces +X and its top row is straight up.
+    pub fn from_equirectangular(path: &str, face_size: u32, options: &TextureOptions) -> Result<Cubemap, TextureError> {
+        let panorama = Pixels::load(path, false)?;
+        panorama.formats(options)?;
+        let faces: Vec<Pixels> = (0..6).map(|face| panorama.cubemap_face(face, face_size)).collect();
+        Cubemap::from_pixels(&faces, options)
+    }
+
+    fn from_pixels(faces: &[Pixels], options: &TextureOptions) -> Result<Cubemap, TextureError> {
+        // sampling across an edge should reach the next face rather than wrap around this one,
+        // which needs TEXTURE_CUBE_MAP_SEAMLESS
+        let options = options.wrap(Wrap::ClampToEdge);
+
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, id);
+            for (index, face) in faces.iter().enumerate() {
+                if let Err(error) = face.upload(gl::TEXTURE_CUBE_MAP_POSITIVE_X + index as GLenum, &options) {
+                    gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+                    gl::DeleteTextures(1, &id);
+                    return Err(error);
+                }
+            }
+            apply_options(gl::TEXTURE_CUBE_MAP, &options);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, options.wrap_s.gl_enum() as i32);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+
+        Ok(Cubemap { id: id })
+    }
+
+    /// Bind the cubemap to `unit`, which leaves that unit active
+    pub unsafe fn bind(&self, unit: TextureUnit) {
+        gl::ActiveTexture(gl::TEXTURE0 + unit.0);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+    }
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Decode the images of `Cubemap::from_faces`, checking that they make a cubemap before anything
+/// is created
+fn load_faces(paths: &[&str; 6], options: &TextureOptions) -> Result<Vec<Pixels>, TextureError> {
+    let mut faces = Vec::with_capacity(6);
+    for path in paths.iter() {
+        let face = Pixels::load(path, false)?;
+        face.formats(options)?;
+        faces.push(face);
+    }
+    let size = faces[0].width;
+    if let Some(face) = faces.iter().find(|face| face.width != size || face.height != size) {
+        return Err(TextureError::UnsupportedFormat {
+            path: face.path.clone(),
+            problem: "cubemap faces must be square and all the same size",
+        });
+    }
+    Ok(faces)
+}
+
+/// The direction through texel coordinates `s` and `t`, each from -1 to 1, of a cubemap face
+/// counted from `TEXTURE_CUBE_MAP_POSITIVE_X`, as laid out in the OpenGL specification
+fn cubemap_direction(face: usize, s: f32, t: f32) -> Vector3<f32> {
+    match face {
+        0 => Vector3::new(1.0, -t, -s),
+        1 => Vector3::new(-1.0, -t, s),
+        2 => Vector3::new(s, 1.0, t),
+        3 => Vector3::new(s, -1.0, -t),
+        4 => Vector3::new(s, -t, 1.0),
+        _ => Vector3::new(-s, -t, -1.0),
+    }
+}
+
+/// The texels of an image, one value per channel
+#[derive(Debug, Clone)]
+pub enum PixelData {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    F32(Vec<f32>),
+}
+
+/// A decoded image, ready to be uploaded to a texture
+#[derive(Debug, Clone)]
+pub struct Pixels {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// 1 to 4, for red, red and green, RGB or RGBA
+    pub channels: usize,
+    pub data: PixelData,
+}
+
+impl Pixels {
+    /// Decode the image asset at `path`, turning it upside down if `flip_y` is set
+    pub fn load(path: &str, flip_y: bool) -> Result<Pixels, TextureError> {
+        let bytes = assets::read(path)?;
+        let image_error = |error| TextureError::Asset(AssetError::Image { path: path.to_string(), error: error });
+
+        let mut pixels = if bytes.starts_with(b"#?") {
+            // Radiance files start with `#?RADIANCE` or `#?RGBE`
+            let decoder = HDRDecoder::new(Cursor::new(&bytes[..])).map_err(&image_error)?;
+            let metadata = decoder.metadata();
+            let texels = decoder.read_image_hdr().map_err(&image_error)?;
+            Pixels {
+                path: path.to_string(),
+                width: metadata.width,
+                height: metadata.height,
+                channels: 3,
+                data: PixelData::F32(texels.iter().flat_map(|texel| texel.data.to_vec()).collect()),
+            }
+        } else {
+            match decode_16_bit_png(path, &bytes)? {
+                Some(pixels) => pixels,
+                None => {
+                    let img = image::load_from_memory(&bytes).map_err(&image_error)?;
+                    let channels = match img {
+                        ImageLuma8(_) => 1,
+                        ImageLumaA8(_) => 2,
+                        ImageRgb8(_) => 3,
+                        ImageRgba8(_) => 4,
+                    };
+                    let (width, height) = img.dimensions();
+                    Pixels {
+                        path: path.to_string(),
+                        width: width,
+                        height: height,
+                        channels: channels,
+                        data: PixelData::U8(img.raw_pixels()),
+                    }
+                },
+            }
+        };
 
         if flip_y {
-            img = img.flipv(); // flip loaded texture on the y-axis.
+            pixels.flip_y();
+        }
+        Ok(pixels)
+    }
+
+    pub fn flip_y(&mut self) {
+        let row_length = self.width as usize * self.channels;
+        match self.data {
+            PixelData::U8(ref mut data) => reverse_rows(data, row_length),
+            PixelData::U16(ref mut data) => reverse_rows(data, row_length),
+            PixelData::F32(ref mut data) => reverse_rows(data, row_length),
+        }
+    }
+
+    /// Face `face` of a cubemap, counted from `TEXTURE_CUBE_MAP_POSITIVE_X` and `size` texels
+    /// square, sampled bilinearly from these pixels as an equirectangular panorama with its top
+    /// row first
+    pub fn cubemap_face(&self, face: usize, size: u32) -> Pixels {
+        let panorama = self.values();
+        let (width, height) = (self.width as usize, self.height as usize);
+        let channels = self.channels;
+        let value = |x: usize, y: usize, channel: usize| panorama[(y * width + x) * channels + channel];
+
+        let mut values = Vec::with_capacity(size as usize * size as usize * channels);
+        for y in 0..size {
+            for x in 0..size {
+                let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+                let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+                let direction = cubemap_direction(face, s, t).normalize();
+                let longitude = direction.z.atan2(direction.x);
+                let latitude = direction.y.max(-1.0).min(1.0).asin();
+
+                // texel centers are at half coordinates; longitude wraps around, latitude doesn't
+                let u = (0.5 + longitude / (2.0 * PI)) * width as f32 - 0.5;
+                let v = ((0.5 - latitude / PI) * height as f32 - 0.5).max(0.0).min((height - 1) as f32);
+                let (u_fraction, v_fraction) = (u - u.floor(), v - v.floor());
+                let x0 = ((u.floor() as isize % width as isize + width as isize) % width as isize) as usize;
+                let x1 = (x0 + 1) % width;
+                let y0 = v.floor() as usize;
+                let y1 = (y0 + 1).min(height - 1);
+                for channel in 0..channels {
+                    let top = value(x0, y0, channel) * (1.0 - u_fraction) + value(x1, y0, channel) * u_fraction;
+                    let bottom = value(x0, y1, channel) * (1.0 - u_fraction) + value(x1, y1, channel) * u_fraction;
+                    values.push(top * (1.0 - v_fraction) + bottom * v_fraction);
+                }
+            }
         }
-        let img = img;
 
-        let format = match img {
-            ImageLuma8(_) => gl::RED,
-            ImageLumaA8(_) => gl::RG,
-            ImageRgb8(_) => gl::RGB,
-            ImageRgba8(_) => gl::RGBA,
+        let data = match self.data {
+            PixelData::U8(_) => PixelData::U8(values.iter().map(|&value| value.round().max(0.0).min(255.0) as u8).collect()),
+            PixelData::U16(_) => PixelData::U16(values.iter().map(|&value| value.round().max(0.0).min(65535.0) as u16).collect()),
+            PixelData::F32(_) => PixelData::F32(values),
         };
+        Pixels {
+            path: self.path.clone(),
+            width: size,
+            height: size,
+            channels: channels,
+            data: data,
+        }
+    }
 
-        let data = img.raw_pixels();
+    /// Every value as a float, without scaling integers to 0..1
+    fn values(&self) -> Vec<f32> {
+        match self.data {
+            PixelData::U8(ref data) => data.iter().map(|&value| value as f32).collect(),
+            PixelData::U16(ref data) => data.iter().map(|&value| value as f32).collect(),
+            PixelData::F32(ref data) => data.clone(),
+        }
+    }
 
-        gl::BindTexture(gl::TEXTURE_2D, texture_id);
-        gl::TexImage2D(gl::TEXTURE_2D, 0, format as i32, img.width() as i32, img.height() as i32,
-            0, format, gl::UNSIGNED_BYTE, &data[0] as *const u8 as *const c_void);
-        gl::GenerateMipmap(gl::TEXTURE_2D);
+    /// The internal format, format and type to upload these pixels with
+    pub fn formats(&self, options: &TextureOptions) -> Result<(GLenum, GLenum, GLenum), TextureError> {
+        let unsupported = |problem| Err(TextureError::UnsupportedFormat { path: self.path.clone(), problem: problem });
+        let format = match self.channels {
+            1 => gl::RED,
+            2 => gl::RG,
+            3 => gl::RGB,
+            4 => gl::RGBA,
+            _ => return unsupported("textures have 1 to 4 channels"),
+        };
+        let index = self.channels - 1;
 
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        let (internal_format, data_type) = match (&self.data, options.color_space) {
+            (&PixelData::U8(_), ColorSpace::Linear) => ([gl::R8, gl::RG8, gl::RGB8, gl::RGBA8][index], gl::UNSIGNED_BYTE),
+            (&PixelData::U8(_), ColorSpace::Srgb) => match self.channels {
+                3 => (gl::SRGB8, gl::UNSIGNED_BYTE),
+                4 => (gl::SRGB8_ALPHA8, gl::UNSIGNED_BYTE),
+                _ => return unsupported("only RGB and RGBA images can be sRGB"),
+            },
+            (&PixelData::U16(_), ColorSpace::Linear) => ([gl::R16, gl::RG16, gl::RGB16, gl::RGBA16][index], gl::UNSIGNED_SHORT),
+            (&PixelData::F32(_), ColorSpace::Linear) => match options.float_precision {
+                FloatPrecision::Half => ([gl::R16F, gl::RG16F, gl::RGB16F, gl::RGBA16F][index], gl::FLOAT),
+                FloatPrecision::Full => ([gl::R32F, gl::RG32F, gl::RGB32F, gl::RGBA32F][index], gl::FLOAT),
+            },
+            (_, ColorSpace::Srgb) => return unsupported("only 8-bit images can be sRGB"),
+        };
+        Ok((internal_format, format, data_type))
+    }
 
-        gl::BindTexture(gl::TEXTURE_2D, 0);
+    /// Upload the pixels as level 0 of `target`, e.g. `gl::TEXTURE_2D` or a cubemap face, of the
+    /// bound texture
+    pub unsafe fn upload(&self, target: GLenum, options: &TextureOptions) -> Result<(), TextureError> {
+        let (internal_format, format, data_type) = self.formats(options)?;
+        let data = match self.data {
+            PixelData::U8(ref data) => data.as_ptr() as *const c_void,
+            PixelData::U16(ref data) => data.as_ptr() as *const c_void,
+            PixelData::F32(ref data) => data.as_ptr() as *const c_void,
+        };
+        // rows aren't always a multiple of 4 bytes long, e.g. for RGB images of odd widths
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage2D(target, 0, internal_format as GLint, self.width as GLsizei, self.height as GLsizei,
+            0, format, data_type, data);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        Ok(())
+    }
+}
+
+/// The pixels of `bytes` if they're a PNG with 16 bits per channel. `image` reduces those to 8
+/// bits, so they're decoded with `png` itself, expanding palettes and transparency but nothing
+/// else.
+fn decode_16_bit_png(path: &str, bytes: &[u8]) -> Result<Option<Pixels>, TextureError> {
+    if !bytes.starts_with(b"\x89PNG") {
+        return Ok(None);
+    }
+    let png_error = |error: png::DecodingError| TextureError::Asset(AssetError::Image {
+        path: path.to_string(),
+        error: image::ImageError::FormatError(error.to_string()),
+    });
 
-        texture_id
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set(png::Transformations::EXPAND);
+    let (info, mut reader) = decoder.read_info().map_err(&png_error)?;
+    // the output info claims expanded images are 8-bit, so the depth comes from the header
+    if reader.info().bit_depth != png::BitDepth::Sixteen {
+        return Ok(None);
     }
+    let mut big_endian = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut big_endian).map_err(&png_error)?;
+
+    Ok(Some(Pixels {
+        path: path.to_string(),
+        width: info.width,
+        height: info.height,
+        channels: info.color_type.samples(),
+        data: PixelData::U16(big_endian.chunks(2).map(|pair| (pair[0] as u16) << 8 | pair[1] as u16).collect()),
+    }))
+}
+
+fn reverse_rows<T: Copy>(data: &mut Vec<T>, row_length: usize) {
+    if row_length == 0 {
+        return;
+    }
+    let flipped: Vec<T> = data.chunks(row_length).rev().flat_map(|row| row.iter().cloned()).collect();
+    *data = flipped;
+}
+
+/// Set the sampling parameters of the texture bound to `target`, generating its mipmaps if they're
+/// wanted
+unsafe fn apply_options(target: GLenum, options: &TextureOptions) {
+    if options.mipmaps {
+        gl::GenerateMipmap(target);
+    }
+    gl::TexParameteri(target, gl::TEXTURE_WRAP_S, options.wrap_s.gl_enum() as i32);
+    gl::TexParameteri(target, gl::TEXTURE_WRAP_T, options.wrap_t.gl_enum() as i32);
+    gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, options.min_filter_enum() as i32);
+    gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, options.mag_filter_enum() as i32);
+    if let Some(border_color) = options.border_color {
+        gl::TexParameterfv(target, gl::TEXTURE_BORDER_COLOR, &border_color[0]);
+    }
+
+    if options.anisotropy > 1.0 && anisotropic_filtering_supported() {
+        let mut max_anisotropy = 1.0;
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+        gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, options.anisotropy.min(max_anisotropy));
+    }
+}
+
+unsafe fn anisotropic_filtering_supported() -> bool {
+    let mut count = 0;
+    gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+    (0..count.max(0) as GLuint).any(|index| {
+        let name = gl::GetStringi(gl::EXTENSIONS, index);
+        if name.is_null() {
+            return false;
+        }
+        match CStr::from_ptr(name as *const _).to_str() {
+            Ok("GL_EXT_texture_filter_anisotropic") | Ok("GL_ARB_texture_filter_anisotropic") => true,
+            _ => false,
+        }
+    })
 }
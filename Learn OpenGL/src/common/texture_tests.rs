@@ -0,0 +1,53 @@
+use super::*;
+
+/// The path of a file in `test/texture`, independent of the folder the tests run from
+fn fixture(name: &str) -> String {
+    format!("{}/test/texture/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn values(pixels: &Pixels) -> &[u16] {
+    match pixels.data {
+        PixelData::U16(ref data) => data,
+        ref other => panic!("expected 16-bit pixels, got {:?}", other),
+    }
+}
+
+#[test]
+fn sixteen_bit_pngs_keep_every_bit() {
+    // one column of two RGB pixels
+    let pixels = Pixels::load(&fixture("rgb_16_bit.png"), false).unwrap();
+    assert_eq!((pixels.width, pixels.height, pixels.channels), (1, 2, 3));
+    assert_eq!(values(&pixels), &[1000, 2000, 65535, 300, 0, 256]);
+    assert_eq!(pixels.formats(&TextureOptions::new()).unwrap(), (gl::RGB16, gl::RGB, gl::UNSIGNED_SHORT));
+}
+
+#[test]
+fn sixteen_bit_pngs_are_flipped_like_any_other_image() {
+    let pixels = Pixels::load(&fixture("rgb_16_bit.png"), true).unwrap();
+    assert_eq!(values(&pixels), &[300, 0, 256, 1000, 2000, 65535]);
+}
+
+#[test]
+fn float_images_are_half_or_full_precision_as_asked() {
+    let pixels = Pixels {
+        path: "inline.hdr".to_string(),
+        width: 1,
+        height: 1,
+        channels: 3,
+        data: PixelData::F32(vec![100_000.0, 1.0, 0.5]),
+    };
+    assert_eq!(pixels.formats(&TextureOptions::new()).unwrap(), (gl::RGB16F, gl::RGB, gl::FLOAT));
+    let options = TextureOptions::new().float_precision(FloatPrecision::Full);
+    assert_eq!(pixels.formats(&options).unwrap(), (gl::RGB32F, gl::RGB, gl::FLOAT));
+    assert!(pixels.formats(&options.color_space(ColorSpace::Srgb)).is_err());
+}
+
+#[test]
+fn cubemap_faces_must_be_square() {
+    let face = fixture("rgb_16_bit.png");
+    let paths = [&face[..], &face[..], &face[..], &face[..], &face[..], &face[..]];
+    match load_faces(&paths, &TextureOptions::new()) {
+        Err(TextureError::UnsupportedFormat { ref path, .. }) => assert_eq!(path, &face),
+        other => panic!("expected non-square faces to be rejected, got {:?}", other),
+    }
+}
@@ -0,0 +1,148 @@
+use gl;
+use gl::types::*;
+
+use cgmath::{Deg, Matrix3, Matrix4, Point3, Rad, Vector2, Vector3, Vector4};
+use cgmath::prelude::*;
+
+/// A value that can be stored in a GLSL uniform of the matching type. Use `Shader::set` rather
+/// than calling `set_uniform` directly.
+pub trait Uniform {
+    /// Store the value at `location` in the program currently in use
+    unsafe fn set_uniform(&self, location: GLint);
+}
+
+/// The texture unit a `sampler2D` or `samplerCube` uniform reads from, e.g. `TextureUnit(1)` for
+/// the texture bound after `gl::ActiveTexture(gl::TEXTURE1)`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureUnit(pub u32);
+
+impl Uniform for TextureUnit {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1i(location, self.0 as GLint);
+    }
+}
+
+impl Uniform for bool {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1i(location, *self as GLint);
+    }
+}
+
+impl Uniform for i32 {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1i(location, *self);
+    }
+}
+
+impl Uniform for u32 {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1ui(location, *self);
+    }
+}
+
+impl Uniform for f32 {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1f(location, *self);
+    }
+}
+
+/// Angles are passed in radians, which is what GLSL's trigonometric functions expect
+impl Uniform for Deg<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1f(location, Rad::from(*self).0);
+    }
+}
+
+impl Uniform for Rad<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1f(location, self.0);
+    }
+}
+
+impl Uniform for Vector2<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform2fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for Vector3<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform3fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for Vector4<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform4fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for Point3<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform3fv(location, 1, self.as_ptr());
+    }
+}
+
+impl Uniform for Matrix3<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::UniformMatrix3fv(location, 1, gl::FALSE, self.as_ptr());
+    }
+}
+
+impl Uniform for Matrix4<f32> {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::UniformMatrix4fv(location, 1, gl::FALSE, self.as_ptr());
+    }
+}
+
+// Slices fill a uniform array from its first element, e.g. `float weights[5]`. The cgmath types
+// are laid out as consecutive floats, so they can be passed to GL as one block.
+
+impl Uniform for [i32] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1iv(location, self.len() as GLsizei, self.as_ptr());
+    }
+}
+
+impl Uniform for [f32] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform1fv(location, self.len() as GLsizei, self.as_ptr());
+    }
+}
+
+impl Uniform for [Vector2<f32>] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform2fv(location, self.len() as GLsizei, self.as_ptr() as *const f32);
+    }
+}
+
+impl Uniform for [Vector3<f32>] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform3fv(location, self.len() as GLsizei, self.as_ptr() as *const f32);
+    }
+}
+
+impl Uniform for [Vector4<f32>] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::Uniform4fv(location, self.len() as GLsizei, self.as_ptr() as *const f32);
+    }
+}
+
+impl Uniform for [Matrix3<f32>] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::UniformMatrix3fv(location, self.len() as GLsizei, gl::FALSE, self.as_ptr() as *const f32);
+    }
+}
+
+impl Uniform for [Matrix4<f32>] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        gl::UniformMatrix4fv(location, self.len() as GLsizei, gl::FALSE, self.as_ptr() as *const f32);
+    }
+}
+
+impl Uniform for [TextureUnit] {
+    unsafe fn set_uniform(&self, location: GLint) {
+        let units: Vec<GLint> = self.iter().map(|unit| unit.0 as GLint).collect();
+        gl::Uniform1iv(location, units.len() as GLsizei, units.as_ptr());
+    }
+}
@@ -0,0 +1,231 @@
+use std::ffi::CString;
+use std::os::raw::c_void;
+
+use gl;
+use gl::types::*;
+
+use cgmath::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4};
+
+#[cfg(test)]
+#[path = "./uniform_buffer_tests.rs"]
+mod uniform_buffer_tests;
+
+/// The binding point of the `Camera` block of `camera_block.glsl`
+pub const CAMERA_BLOCK_BINDING: GLuint = 0;
+/// The binding point of the `Lights` block of `light_block.glsl`
+pub const LIGHT_BLOCK_BINDING: GLuint = 1;
+
+/// The blocks every program is bound to by name when it's linked, so shaders only have to include
+/// the declarations
+const SHARED_BLOCKS: [(&'static str, GLuint); 2] = [
+    ("Camera", CAMERA_BLOCK_BINDING),
+    ("Lights", LIGHT_BLOCK_BINDING),
+];
+
+/// The length of the `pointLights` array in `light_block.glsl`, which must match. The block is
+/// about 10 KiB, well within the 16 KiB every implementation supports.
+pub const MAX_POINT_LIGHTS: usize = 128;
+
+/// Bind whichever of the shared uniform blocks the program declares to their binding points
+pub unsafe fn bind_shared_blocks(program: GLuint) {
+    for &(name, binding) in SHARED_BLOCKS.iter() {
+        let c_name = CString::new(name).unwrap();
+        let index = gl::GetUniformBlockIndex(program, c_name.as_ptr());
+        if index != gl::INVALID_INDEX {
+            gl::UniformBlockBinding(program, index, binding);
+        }
+    }
+}
+
+/// A value that can be written to a `layout (std140)` uniform block
+pub trait Std140 {
+    fn write_std140(&self, writer: &mut Std140Writer);
+}
+
+/// Packs values into bytes following the std140 layout rules, so the contents of a uniform block
+/// can be built from Rust values. Members must be written in the order the block declares them,
+/// with structs written through `write_struct`.
+#[derive(Debug, Clone, Default)]
+pub struct Std140Writer {
+    bytes: Vec<u8>,
+}
+
+impl Std140Writer {
+    pub fn new() -> Self {
+        Std140Writer { bytes: vec![] }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn write<T: Std140 + ?Sized>(&mut self, value: &T) -> &mut Self {
+        value.write_std140(self);
+        self
+    }
+
+    /// Write a struct, or one element of an array of structs, with `write_fields` writing its
+    /// members. Structs start and end on a 16 byte boundary.
+    pub fn write_struct<F: FnOnce(&mut Std140Writer)>(&mut self, write_fields: F) -> &mut Self {
+        self.align(16);
+        write_fields(self);
+        self.align(16);
+        self
+    }
+
+    /// Write an array of scalars or vectors, where every element takes at least 16 bytes
+    pub fn write_array<T: Std140>(&mut self, values: &[T]) -> &mut Self {
+        for value in values.iter() {
+            self.align(16);
+            value.write_std140(self);
+            self.align(16);
+        }
+        self
+    }
+
+    fn align(&mut self, alignment: usize) {
+        let padding = (alignment - self.bytes.len() % alignment) % alignment;
+        let padded_length = self.bytes.len() + padding;
+        self.bytes.resize(padded_length, 0);
+    }
+
+    /// Write consecutive 4 byte values, starting at a multiple of `alignment`
+    fn write_words(&mut self, alignment: usize, words: &[u32]) {
+        self.align(alignment);
+        for &word in words.iter() {
+            // buffers are read in the machine's byte order
+            let bytes = [word as u8, (word >> 8) as u8, (word >> 16) as u8, (word >> 24) as u8];
+            if cfg!(target_endian = "big") {
+                self.bytes.extend(bytes.iter().rev());
+            } else {
+                self.bytes.extend_from_slice(&bytes);
+            }
+        }
+    }
+
+    fn write_floats(&mut self, alignment: usize, floats: &[f32]) {
+        let words: Vec<u32> = floats.iter().map(|float| float.to_bits()).collect();
+        self.write_words(alignment, &words);
+    }
+}
+
+impl Std140 for f32 {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_floats(4, &[*self]);
+    }
+}
+
+impl Std140 for i32 {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_words(4, &[*self as u32]);
+    }
+}
+
+impl Std140 for u32 {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_words(4, &[*self]);
+    }
+}
+
+/// GLSL bools are 4 bytes in a block
+impl Std140 for bool {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_words(4, &[*self as u32]);
+    }
+}
+
+impl Std140 for Vector2<f32> {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_floats(8, &[self.x, self.y]);
+    }
+}
+
+/// A vec3 is aligned like a vec4, but a following scalar can fill its fourth component
+impl Std140 for Vector3<f32> {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_floats(16, &[self.x, self.y, self.z]);
+    }
+}
+
+impl Std140 for Point3<f32> {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_floats(16, &[self.x, self.y, self.z]);
+    }
+}
+
+impl Std140 for Vector4<f32> {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_floats(16, &[self.x, self.y, self.z, self.w]);
+    }
+}
+
+/// Matrices are stored as arrays of their columns, so each column of a mat3 is padded to 16 bytes
+impl Std140 for Matrix3<f32> {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_array(&[self.x, self.y, self.z]);
+    }
+}
+
+impl Std140 for Matrix4<f32> {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer.write_array(&[self.x, self.y, self.z, self.w]);
+    }
+}
+
+/// The `Camera` block of `camera_block.glsl`
+pub fn camera_block_data(view_matrix: &Matrix4<f32>, projection_matrix: &Matrix4<f32>) -> Std140Writer {
+    let mut writer = Std140Writer::new();
+    writer.write(view_matrix).write(projection_matrix);
+    writer
+}
+
+/// A uniform buffer object that stays bound to one binding point
+pub struct UniformBuffer {
+    id: GLuint,
+    binding: GLuint,
+    // the allocated size in bytes
+    size: usize,
+}
+
+impl UniformBuffer {
+    pub fn new(binding: GLuint) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+        }
+        UniformBuffer {
+            id: id,
+            binding: binding,
+            size: 0,
+        }
+    }
+
+    /// Upload the block's new contents, growing the buffer if they don't fit, and bind it
+    pub fn update(&mut self, contents: &Std140Writer) {
+        let bytes = contents.bytes();
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.id);
+            if bytes.len() > self.size {
+                gl::BufferData(
+                    gl::UNIFORM_BUFFER,
+                    bytes.len() as GLsizeiptr,
+                    bytes.as_ptr() as *const c_void,
+                    gl::DYNAMIC_DRAW,
+                );
+                self.size = bytes.len();
+            } else {
+                gl::BufferSubData(gl::UNIFORM_BUFFER, 0, bytes.len() as GLsizeiptr, bytes.as_ptr() as *const c_void);
+            }
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, self.binding, self.id);
+        }
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use cgmath::SquareMatrix;
+
+use super::*;
+
+/// The 4 byte words written so far, to compare against expected offsets a word at a time
+fn words(writer: &Std140Writer) -> Vec<u32> {
+    writer.bytes()
+        .chunks(4)
+        .map(|bytes| {
+            let word = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            let word = if cfg!(target_endian = "big") { [word[3], word[2], word[1], word[0]] } else { word };
+            word[0] as u32 | (word[1] as u32) << 8 | (word[2] as u32) << 16 | (word[3] as u32) << 24
+        })
+        .collect()
+}
+
+fn floats(writer: &Std140Writer) -> Vec<f32> {
+    words(writer).into_iter().map(f32::from_bits).collect()
+}
+
+#[test]
+fn words_are_written_in_native_byte_order() {
+    let mut writer = Std140Writer::new();
+    writer.write(&0x0102_0304u32).write(&1.5f32);
+    let expected: [u8; 4] = if cfg!(target_endian = "big") { [1, 2, 3, 4] } else { [4, 3, 2, 1] };
+    assert_eq!(&writer.bytes()[..4], &expected);
+    assert_eq!(floats(&writer)[1], 1.5);
+}
+
+#[test]
+fn a_scalar_after_a_vec3_fills_its_fourth_component() {
+    let mut writer = Std140Writer::new();
+    writer.write(&Vector3::new(1.0f32, 2.0, 3.0)).write(&4.0f32);
+    assert_eq!(floats(&writer), vec![1.0, 2.0, 3.0, 4.0]);
+
+    // but a vec3 after a scalar starts on the next 16 byte boundary
+    let mut writer = Std140Writer::new();
+    writer.write(&4.0f32).write(&Vector3::new(1.0f32, 2.0, 3.0));
+    assert_eq!(floats(&writer), vec![4.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn vec2s_are_aligned_to_8_bytes() {
+    let mut writer = Std140Writer::new();
+    writer.write(&1.0f32).write(&Vector2::new(2.0f32, 3.0)).write(&4.0f32);
+    assert_eq!(floats(&writer), vec![1.0, 0.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn mat3_columns_are_padded_to_16_bytes() {
+    let matrix = Matrix3::new(
+        1.0f32, 2.0, 3.0,
+        4.0, 5.0, 6.0,
+        7.0, 8.0, 9.0,
+    );
+    let mut writer = Std140Writer::new();
+    writer.write(&1.0f32).write(&matrix).write(&10.0f32);
+    assert_eq!(floats(&writer), vec![
+        1.0, 0.0, 0.0, 0.0,
+        1.0, 2.0, 3.0, 0.0,
+        4.0, 5.0, 6.0, 0.0,
+        7.0, 8.0, 9.0, 0.0,
+        10.0,
+    ]);
+}
+
+#[test]
+fn array_elements_take_16_bytes_each() {
+    let mut writer = Std140Writer::new();
+    writer.write_array(&[1.0f32, 2.0]).write(&3.0f32);
+    assert_eq!(floats(&writer), vec![1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 3.0]);
+}
+
+#[test]
+fn arrays_of_structs_start_each_struct_on_a_16_byte_boundary() {
+    // struct Light { vec3 position; float range; vec3 color; }; uniform Lights { int count; Light lights[2]; };
+    let lights = [
+        (Vector3::new(1.0f32, 2.0, 3.0), 4.0f32, Vector3::new(5.0f32, 6.0, 7.0)),
+        (Vector3::new(8.0f32, 9.0, 10.0), 11.0f32, Vector3::new(12.0f32, 13.0, 14.0)),
+    ];
+    let mut writer = Std140Writer::new();
+    writer.write(&2i32);
+    for &(position, range, color) in lights.iter() {
+        writer.write_struct(|writer| {
+            writer.write(&position).write(&range).write(&color);
+        });
+    }
+    writer.write(&true);
+
+    let words = words(&writer);
+    assert_eq!(words.len(), 4 + 2 * 8 + 1);
+    assert_eq!(words[0], 2);
+    let floats: Vec<f32> = words[4..20].iter().map(|&word| f32::from_bits(word)).collect();
+    assert_eq!(floats, vec![
+        1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 0.0,
+        8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 0.0,
+    ]);
+    assert_eq!(words[20], 1);
+}
+
+#[test]
+fn camera_block_holds_two_mat4s() {
+    let projection_matrix = Matrix4::from_scale(2.0f32);
+    let data = camera_block_data(&Matrix4::identity(), &projection_matrix);
+    let floats = floats(&data);
+    assert_eq!(floats.len(), 32);
+    assert_eq!(floats[0], 1.0);
+    assert_eq!(floats[16], 2.0);
+    assert_eq!(floats[31], 1.0);
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::mem;
+use std::os::raw::c_void;
+
+use gl;
+use gl::types::*;
+
+use common::shader::{glsl_type_name, Shader, ShaderError};
+
+/// One vertex shader input, filled from consecutive floats of each vertex
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VertexAttribute {
+    /// the name of the `in` variable in the vertex shader
+    pub name: &'static str,
+    /// the `layout (location = ...)` of the variable
+    pub location: GLuint,
+    /// the number of floats, e.g. 3 for a `vec3`
+    pub components: usize,
+}
+
+/// How the floats of each vertex in an interleaved vertex buffer are split between the vertex
+/// shader's inputs. Declaring this once both sets up the attribute pointers and lets the layout be
+/// checked against the shader, instead of trusting that hand-written locations match the GLSL.
+#[derive(Debug, Clone)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+}
+
+/// A way a vertex layout disagrees with the attributes a program reads
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutMismatch {
+    /// the program reads an attribute the layout doesn't provide, so it would see a constant value
+    Missing { name: String, gl_type: GLenum, location: GLint },
+    /// the attribute is at a different location in the program
+    Location { name: String, program_location: GLint, layout_location: GLuint },
+    /// the attribute's type in the program doesn't hold `layout_components` floats
+    Type { name: String, gl_type: GLenum, layout_components: usize },
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LayoutMismatch::Missing { ref name, gl_type, location } => write!(
+                f,
+                "{} ({} at location {}) is read by the shader but missing from the vertex layout",
+                name, glsl_type_name(gl_type), location,
+            ),
+            LayoutMismatch::Location { ref name, program_location, layout_location } => write!(
+                f,
+                "{} is at location {} in the shader but {} in the vertex layout",
+                name, program_location, layout_location,
+            ),
+            LayoutMismatch::Type { ref name, gl_type, layout_components } => write!(
+                f,
+                "{} is a {} in the shader but has {} float(s) in the vertex layout",
+                name, glsl_type_name(gl_type), layout_components,
+            ),
+        }
+    }
+}
+
+impl VertexLayout {
+    /// `attributes` are (name, location, number of floats) in the order they appear in each vertex
+    pub fn new(attributes: &[(&'static str, GLuint, usize)]) -> Self {
+        VertexLayout {
+            attributes: attributes.iter()
+                .map(|&(name, location, components)| VertexAttribute {
+                    name: name,
+                    location: location,
+                    components: components,
+                })
+                .collect(),
+        }
+    }
+
+    /// The size of one vertex in bytes
+    pub fn stride(&self) -> usize {
+        self.attributes.iter().map(|attribute| attribute.components).sum::<usize>() * mem::size_of::<GLfloat>()
+    }
+
+    /// Point and enable each attribute of the bound vertex array at the buffer bound to
+    /// `GL_ARRAY_BUFFER`
+    pub unsafe fn apply(&self) {
+        let stride = self.stride() as GLsizei;
+        let mut offset = 0;
+        for attribute in self.attributes.iter() {
+            gl::VertexAttribPointer(
+                attribute.location,
+                attribute.components as GLint,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset as *const c_void,
+            );
+            gl::EnableVertexAttribArray(attribute.location);
+            offset += attribute.components * mem::size_of::<GLfloat>();
+        }
+    }
+
+    /// Check that every attribute the program reads is in the layout, at the same location and
+    /// with the right number of floats. Attributes of the layout that the program doesn't read
+    /// are fine, since drivers drop inputs that don't affect the output.
+    pub fn validate(&self, shader: &Shader) -> Result<(), ShaderError> {
+        let mismatches = self.mismatches(shader);
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ShaderError::VertexLayout { paths: shader.paths().to_vec(), mismatches: mismatches })
+        }
+    }
+
+    fn mismatches(&self, shader: &Shader) -> Vec<LayoutMismatch> {
+        let mut mismatches = vec![];
+        for active in shader.active_attributes() {
+            // built-ins like gl_VertexID don't come from vertex buffers
+            if active.location < 0 {
+                continue;
+            }
+
+            let attribute = match self.attributes.iter().find(|attribute| attribute.name == active.name) {
+                Some(attribute) => attribute,
+                None => {
+                    mismatches.push(LayoutMismatch::Missing {
+                        name: active.name,
+                        gl_type: active.gl_type,
+                        location: active.location,
+                    });
+                    continue;
+                },
+            };
+
+            if attribute.location as GLint != active.location {
+                mismatches.push(LayoutMismatch::Location {
+                    name: active.name.clone(),
+                    program_location: active.location,
+                    layout_location: attribute.location,
+                });
+            }
+            if float_components(active.gl_type) != Some(attribute.components) {
+                mismatches.push(LayoutMismatch::Type {
+                    name: active.name,
+                    gl_type: active.gl_type,
+                    layout_components: attribute.components,
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+/// How many floats an attribute of the given type holds, or None for types that aren't filled
+/// from floats
+fn float_components(gl_type: GLenum) -> Option<usize> {
+    match gl_type {
+        gl::FLOAT => Some(1),
+        gl::FLOAT_VEC2 => Some(2),
+        gl::FLOAT_VEC3 => Some(3),
+        gl::FLOAT_VEC4 => Some(4),
+        _ => None,
+    }
+}
@@ -1,10 +1,11 @@
 #version 330 core
 
-uniform mat4 viewMatrix;
-uniform mat4 projectionMatrix;
+#include "camera_block.glsl"
 
 layout (location = 0) in vec3 aPos;
 
+uniform vec3 origin;
+
 void main() {
-    gl_Position = projectionMatrix * viewMatrix * vec4(aPos, 1.0);
+    gl_Position = projectionMatrix * viewMatrix * vec4(aPos + origin, 1.0);
 }
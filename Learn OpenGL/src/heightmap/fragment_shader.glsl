@@ -1,7 +1,30 @@
 #version 330 core
 
+in vec3 vertexPositionView;
+in vec3 normal;
+in vec2 texCoords;
+
+#include "lighting.glsl"
+#include "light_block.glsl"
+
+uniform Material material;
+
 out vec4 fragColor;
 
 void main() {
-	fragColor = vec4(0.0, 1.0, 0.0, 1.0);
+	vec3 normalizedNormal = normalize(normal);
+
+	vec3 color = vec3(0.0);
+	for(int i = 0; i < pointLightCount; i++) {
+		color += pointLightColor(pointLights[i], material, vertexPositionView, normalizedNormal);
+	}
+	color += directionalLightColor(directionalLight, material, vertexPositionView, normalizedNormal);
+	color += spotlightColor(spotlight, material, vertexPositionView, normalizedNormal);
+
+	// emission
+	if(material.emissionPresent) {
+		color += vec3(texture(material.emissionColor, texCoords));
+	}
+
+	fragColor = vec4(color, 1.0);
 }
@@ -1,16 +1,25 @@
 extern crate glfw;
-use self::glfw::Context;
+use self::glfw::{Context, Key};
 
 extern crate gl;
 
+use std::env;
 use std::ptr;
 use std::os::raw::c_void;
 use std::ffi::CStr;
 
 use cgmath::{Point3, Vector3, Vector4};
 
-use common::camera::{Camera, Pitch, Yaw};
+use common::asset_cache::AssetCache;
+use common::camera::{Camera, CameraMode, ClipPlanes, Pitch, Yaw};
+use common::camera_controller::{FpsController, WalkController};
+use common::camera_path::CameraPathController;
 use common::input::*;
+use common::heightmap::HeightField;
+use common::ray::Ray;
+use common::skybox::Skybox;
+use common::texture::{ColorSpace, FloatPrecision, TextureOptions};
+use common::load_error::LoadError;
 
 use super::coordinate_axes::CoordinateAxes;
 use super::scene_graph::SceneGraph;
@@ -23,6 +32,13 @@ const WINDOW_NAME: &'static str = "Heightmap";
 const SCREEN_WIDTH: u32 = 1024;
 const SCREEN_HEIGHT: u32 = 768;
 
+/// An equirectangular panorama to use as the sky instead of `common/sky.png`, e.g. a Radiance
+/// `.hdr` environment map, found like any other asset
+const SKY_VARIABLE: &'static str = "LEARN_OPENGL_SKY";
+
+/// How many texels wide each face of the sky's cubemap is
+const SKY_FACE_SIZE: u32 = 256;
+
 extern "system" fn gl_debug_output(source: gl::types::GLenum,
                                  type_: gl::types::GLenum,
                                  id: gl::types::GLuint,
@@ -76,6 +92,45 @@ extern "system" fn gl_debug_output(source: gl::types::GLenum,
     }
 }
 
+/// Returns the scene graph, the index of the terrain element and the terrain's surface
+fn build_scene_graph(
+    camera: Camera,
+    lights: Vec<Light>,
+    assets: &mut AssetCache,
+) -> Result<(SceneGraph, usize, HeightField), LoadError> {
+    let mut scene_graph = SceneGraph::new(
+        SCREEN_WIDTH, SCREEN_HEIGHT, SRC_FOLDER, camera, lights
+    );
+
+    scene_graph.add_element(Box::new(
+        CoordinateAxes::new(
+            SRC_FOLDER,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        )?
+    ));
+
+    let terrain = Terrain::new(SRC_FOLDER, Vector3::new(0.0, 0.0, 0.0), assets)?;
+    let ground = terrain.height_field().clone();
+    let terrain_element = scene_graph.add_element(Box::new(terrain));
+
+    let sky_path = match env::var(SKY_VARIABLE) {
+        Ok(ref path) if !path.is_empty() => path.clone(),
+        _ => "common/sky.png".to_string(),
+    };
+    let sky_options = if sky_path.to_lowercase().ends_with(".hdr") {
+        // the sun in an unclipped HDR panorama is often brighter than the largest half float
+        TextureOptions::new().float_precision(FloatPrecision::Full)
+    } else {
+        TextureOptions::new().color_space(ColorSpace::Srgb)
+    };
+    let sky = assets.cubemap_from_equirectangular(&sky_path, SKY_FACE_SIZE, &sky_options)?;
+    scene_graph.set_skybox(Skybox::new(sky)?);
+
+    Ok((scene_graph, terrain_element, ground))
+}
+
 pub fn main() {
 
     let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
@@ -86,10 +141,6 @@ pub fn main() {
     #[cfg(debug_assertions)]
     glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(true));
 
-    let mut first_mouse = true;
-    let mut last_x: f32 = SCREEN_WIDTH as f32 / 2.0;
-    let mut last_y: f32 = SCREEN_HEIGHT as f32 / 2.0;
-
     // timing
     let mut delta_time: f32; // time between current frame and last frame
     let mut last_frame: f32 = 0.0;
@@ -104,6 +155,7 @@ pub fn main() {
 
     window.make_current();
     // window.set_cursor_pos_polling(true);
+    window.set_key_polling(true);
     window.set_scroll_polling(true);
     window.set_framebuffer_size_polling(true);
 
@@ -113,6 +165,13 @@ pub fn main() {
     // initialize OpenGL function pointers
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
+    // color maps are sampled as sRGB and lit in linear space, so convert the result back to sRGB
+    unsafe {
+        gl::Enable(gl::FRAMEBUFFER_SRGB);
+        // filter across the edges between cubemap faces
+        gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+    }
+
     #[cfg(debug_assertions)]
     unsafe {
         let mut flags = 0;
@@ -138,26 +197,47 @@ pub fn main() {
         },
     ];
 
-    let camera = Camera::new(
+    let mut camera = Camera::new(
         Point3::new(00.0, 20.0, 0.0),
         Vector3::unit_y(),
         Yaw::new(45.0),
         Pitch::new(-10.0),
     );
-    let mut scene_graph = SceneGraph::new(
-        SCREEN_WIDTH, SCREEN_HEIGHT, SRC_FOLDER, camera, lights
+    camera.set_mode(CameraMode::FreeFlight);
+    // the terrain is much larger than the other lessons' scenes
+    camera.set_clip_planes(ClipPlanes::new(0.1, 1_000.0));
+    let mut assets = AssetCache::new();
+    let (scene_graph, terrain_element, ground) = match build_scene_graph(camera, lights, &mut assets) {
+        Ok(scene) => scene,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
+    };
+
+    // G switches between flying and walking on the terrain; F5 records a flythrough and F6 plays
+    // it back at a fixed 60 frames per second of path time
+    let mut camera_controller = CameraPathController::new(
+        WalkController::new(
+            FpsController::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+            ground,
+            1.7,
+        ),
+        &format!("{}/camera_path.txt", SRC_FOLDER),
+        0.1,
+        Some(1.0 / 60.0),
     );
 
-    scene_graph.add_element(Box::new(
-        CoordinateAxes::new(
-            SRC_FOLDER,
-            Vector3::new(1.0, 0.0, 0.0),
-            Vector3::new(0.0, 1.0, 0.0),
-            Vector3::new(0.0, 0.0, 1.0),
-        )
-    ));
+    // click the terrain to move the coordinate axes to the point under the cursor, and click the
+    // sky to move them back
+    let mut click_detector = ClickDetector::new();
+
+    // F3 prints how many elements and instances the last frame culled
+    let mut culling_key = KeyPressDetector::new(Key::F3);
 
-    scene_graph.add_element(Box::new(Terrain::new(SRC_FOLDER, Vector3::new(0.0, 0.0, 0.0))));
+    // F4 draws the terrain's normals as lines, to check the lighting
+    let mut normals_key = KeyPressDetector::new(Key::F4);
+    let mut show_normals = false;
 
     let mut scene_graph = scene_graph;
 
@@ -166,12 +246,45 @@ pub fn main() {
         delta_time = current_frame - last_frame;
         last_frame = current_frame;
 
-        process_events(&events, &mut first_mouse, &mut last_x, &mut last_y, &mut scene_graph.camera);
-        process_input(&mut window, delta_time, &mut scene_graph.camera);
+        process_events(&events, &mut camera_controller, &mut scene_graph.camera);
+        process_input(&mut window, delta_time, &mut camera_controller, &mut scene_graph.camera);
+
+        if let Some((cursor_x, cursor_y)) = click_detector.update(&window) {
+            let (window_width, window_height) = window.get_size();
+            let ray = Ray::from_cursor(
+                cursor_x,
+                cursor_y,
+                window_width as u32,
+                window_height as u32,
+                &scene_graph.camera.view_matrix(),
+                &scene_graph.camera.projection_matrix(),
+            );
+            let point = ray.and_then(|ray| match scene_graph.pick(&ray) {
+                Some(pick) if pick.element == terrain_element => Some(ray.point_at(pick.distance)),
+                _ => None,
+            });
+            scene_graph.mark_point(point);
+        }
+
+        if culling_key.update(&window) {
+            println!("culling: {}", scene_graph.culling_stats());
+        }
+
+        if normals_key.update(&window) {
+            show_normals = !show_normals;
+            scene_graph.show_normals(show_normals);
+        }
 
+        scene_graph.reload_shaders();
         scene_graph.render_frame(current_frame);
 
         window.swap_buffers();
         glfw.poll_events();
     }
+
+    // everything the scene loaded is released with it, so anything still alive has leaked
+    drop(scene_graph);
+    if cfg!(debug_assertions) && assets.live_assets() > 0 {
+        println!("{} after the scene was dropped", assets);
+    }
 }
@@ -4,6 +4,7 @@ pub mod light;
 pub mod material;
 pub mod scene_element;
 pub mod scene_graph;
+pub mod skybox;
 pub mod terrain;
 
 pub use self::main::main;
@@ -0,0 +1,47 @@
+#version 430 core
+
+// Works out the terrain's normals from its positions with central differences, falling back to
+// one-sided ones at the edges like common::heightmap, and writes them over the vertex buffer's
+
+layout (local_size_x = 16, local_size_y = 16) in;
+
+// interleaved like heightmap_data's vertices: position, normal, texture coordinates
+const uint VERTEX_FLOATS = 8u;
+
+layout (std430, binding = 0) buffer Vertices {
+	float vertices[];
+};
+
+uniform uint columns;
+uniform uint rows;
+
+uint vertexStart(uint column, uint row) {
+	return (row * columns + column) * VERTEX_FLOATS;
+}
+
+vec3 position(uint column, uint row) {
+	uint start = vertexStart(column, row);
+	return vec3(vertices[start], vertices[start + 1u], vertices[start + 2u]);
+}
+
+void main() {
+	uint column = gl_GlobalInvocationID.x;
+	uint row = gl_GlobalInvocationID.y;
+	// the grid is rarely a multiple of the work group size
+	if (column >= columns || row >= rows) {
+		return;
+	}
+
+	uint left = max(column, 1u) - 1u;
+	uint right = min(column + 1u, columns - 1u);
+	uint near = max(row, 1u) - 1u;
+	uint far = min(row + 1u, rows - 1u);
+	vec3 alongX = position(right, row) - position(left, row);
+	vec3 alongZ = position(column, far) - position(column, near);
+	vec3 normal = normalize(vec3(-alongX.y / alongX.x, 1.0, -alongZ.y / alongZ.z));
+
+	uint start = vertexStart(column, row);
+	vertices[start + 3u] = normal.x;
+	vertices[start + 4u] = normal.y;
+	vertices[start + 5u] = normal.z;
+}
@@ -0,0 +1,21 @@
+#version 330 core
+
+#include "camera_block.glsl"
+
+// turns each vertex into a line along its normal
+
+layout (points) in;
+layout (line_strip, max_vertices = 2) out;
+
+in vec3 worldNormal[];
+
+uniform float normalLength;
+
+void main() {
+	vec4 base = gl_in[0].gl_Position;
+	gl_Position = projectionMatrix * viewMatrix * base;
+	EmitVertex();
+	gl_Position = projectionMatrix * viewMatrix * vec4(base.xyz + normalize(worldNormal[0]) * normalLength, 1.0);
+	EmitVertex();
+	EndPrimitive();
+}
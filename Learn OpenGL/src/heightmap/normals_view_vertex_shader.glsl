@@ -0,0 +1,15 @@
+#version 330 core
+
+uniform mat4 modelMatrix;
+
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+
+out vec3 worldNormal;
+
+void main() {
+	// the geometry shader projects the normal's line once it knows both ends
+	gl_Position = modelMatrix * vec4(aPos, 1.0);
+	// the terrain is only ever translated, which leaves normals as they are
+	worldNormal = aNormal;
+}
@@ -1,4 +1,7 @@
-use cgmath::Matrix4;
+use cgmath::{Matrix4, Point3};
+
+use common::frustum::{BoundingVolume, CullingStats, Frustum};
+use common::ray::{Ray, RayHit};
 
 use super::light::Light;
 
@@ -9,5 +12,30 @@ pub trait SceneElement {
         lights: &Vec<Light>,
         view_matrix: &Matrix4<f32>,
         projection_matrix: &Matrix4<f32>,
+        frustum: &Frustum,
+        culling_stats: &mut CullingStats,
     );
+
+    /// World-space bounds used to skip the whole element when it's outside the view frustum.
+    /// Elements without bounds are always drawn.
+    fn bounds(&self) -> Option<BoundingVolume> {
+        None
+    }
+
+    /// The nearest point where the ray hits the element, for picking with the mouse. Elements that
+    /// can't be picked never report a hit.
+    fn intersect_ray(&self, _ray: &Ray) -> Option<RayHit> {
+        None
+    }
+
+    /// Show that the point was picked with the mouse, or that nothing is picked. Most elements
+    /// don't show it.
+    fn mark_point(&mut self, _point: Option<Point3<f32>>) {}
+
+    /// Draw each vertex's normal as a line, or stop drawing them. Most elements don't have a
+    /// normals view.
+    fn show_normals(&mut self, _show: bool) {}
+
+    /// Rebuild any shader programs whose source files have changed. Only called between frames.
+    fn reload_shaders(&mut self) {}
 }
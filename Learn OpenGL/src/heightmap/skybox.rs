@@ -0,0 +1,25 @@
+use cgmath::Matrix4;
+
+use common::frustum::{CullingStats, Frustum};
+use common::skybox::Skybox;
+
+use super::light::Light;
+use super::scene_element::SceneElement;
+
+impl SceneElement for Skybox {
+    fn render_frame(
+        &self,
+        _t: f32,
+        _lights: &Vec<Light>,
+        _view_matrix: &Matrix4<f32>,
+        _projection_matrix: &Matrix4<f32>,
+        _frustum: &Frustum,
+        _culling_stats: &mut CullingStats,
+    ) {
+        self.render();
+    }
+
+    fn reload_shaders(&mut self) {
+        self.reload_if_changed();
+    }
+}
@@ -3,94 +3,217 @@ extern crate glfw;
 extern crate gl;
 use self::gl::types::*;
 
+use std::env;
 use std::ptr;
 use std::mem;
 use std::os::raw::c_void;
 use std::ffi::CStr;
 
 use cgmath::prelude::*;
-use cgmath::{Matrix3, Matrix4, Vector3};
-
-use common::shader::Shader;
-use common::texture::load_texture;
+use cgmath::{Matrix3, Matrix4, Point3, Vector3};
+
+use common::asset_cache::AssetCache;
+use common::frustum::{BoundingVolume, CullingStats, Frustum};
+use common::load_error::LoadError;
+use common::reloadable_shader::ReloadableShader;
+use common::shader::{compute_shaders_supported, memory_barrier, MemoryBarrier, ShaderError, ShaderStage};
+use common::shader_builder::ShaderBuilder;
+use common::texture::{ColorSpace, TextureOptions};
+use common::uniform::TextureUnit;
+use common::vertex_layout::VertexLayout;
 use common::heightmap;
+use common::heightmap::{HeightEncoding, HeightField, HeightmapError, Indices};
+use common::ray::{Ray, RayHit};
 
 use super::scene_element::SceneElement;
 use super::light::Light;
 use super::material::Material;
 
+/// How much ground a heightmap image covers. Heights in metres are scaled by the same units per
+/// metre as the width, which keeps the terrain true to scale.
+const HEIGHTMAP_WIDTH_METRES: f32 = 5_000.0;
+
+/// How wide the heightmap's terrain is in world units
+const TERRAIN_WIDTH: f32 = 100.0;
+
+/// A heightmap image, SRTM `.hgt` tile or ESRI `.asc` grid to use instead of the lesson's own
+/// heightmap, found like any other asset
+const TERRAIN_VARIABLE: &'static str = "LEARN_OPENGL_TERRAIN";
+
+/// How `LEARN_OPENGL_TERRAIN`'s pixels hold heights, as parsed by `HeightEncoding::from_str`;
+/// Terrarium if it's unset
+const TERRAIN_ENCODING_VARIABLE: &'static str = "LEARN_OPENGL_TERRAIN_ENCODING";
+
+/// Grayscale heights go from 0 to 1, which is taken to span this many metres
+const GRAYSCALE_RELIEF_METRES: f32 = 500.0;
+
+/// Where the normals compute shader finds the vertex buffer
+const VERTICES_STORAGE_BINDING: GLuint = 0;
+
+/// How long the lines showing the normals are, in world units
+const NORMAL_VIEW_LENGTH: f32 = 0.5;
+
+/// The normals view draws every line in one color, which isn't worth a file of its own
+const NORMALS_VIEW_FRAGMENT_SHADER: &'static str = "#version 330 core
+
+out vec4 fragColor;
+
+void main() {
+	fragColor = vec4(1.0, 1.0, 0.0, 1.0);
+}
+";
+
+/// The lesson's ground. Its normals come from `heightmap_data`, and are worked out again by a
+/// compute shader when OpenGL 4.3 is available, which lets that shader be edited while the lesson
+/// runs.
 pub struct Terrain {
-    shader_program: Shader,
+    shader_program: ReloadableShader,
+    // None without compute shader support
+    normals_program: Option<ReloadableShader>,
+    normals_view_program: ReloadableShader,
+    show_normals: bool,
     vao: GLuint,
     vbo: GLuint,
     ebo: GLuint,
+    columns: usize,
+    rows: usize,
     num_elements_to_draw: i32,
+    index_type: GLenum,
     model_matrix: Matrix4<f32>,
+    bounds: BoundingVolume,
+    height_field: HeightField,
     material: Material,
 }
 
 impl Terrain {
-    pub fn new(src_folder: &str, position: Vector3<f32>) -> Self {
+    pub fn new(src_folder: &str, position: Vector3<f32>, assets: &mut AssetCache) -> Result<Self, LoadError> {
 
-        let (shader_program, vao, vbo, ebo, num_elements_to_draw) = Terrain::init_opengl(src_folder);
+        let (normals_program, normals_view_program) = Terrain::normals_programs(src_folder, assets)?;
+        let (shader_program, vao, vbo, ebo, indices, (columns, rows), model_bounds, height_field) =
+            Terrain::init_opengl(src_folder, assets)?;
+        let model_matrix = Matrix4::from_translation(position);
 
         let material = Material {
             ambient_color: Vector3::new(1.0, 0.5, 0.31),
-            diffuse_map_texture_id: load_texture(&format!("{}/terrain_diffuse_map.png", src_folder), true),
-            specular_map_texture_id: load_texture(&format!("{}/terrain_specular_map.png", src_folder), true),
-            emission_map_texture_id: None,
+            diffuse_map: assets.texture(&format!("{}/terrain_diffuse_map.png", src_folder), &TextureOptions::new().color_space(ColorSpace::Srgb).anisotropy(8.0))?,
+            specular_map: assets.texture(&format!("{}/terrain_specular_map.png", src_folder), &TextureOptions::new())?,
+            emission_map: None,
             shininess: 32.0,
         };
 
-        Terrain {
+        let terrain = Terrain {
             shader_program: shader_program,
+            normals_program: normals_program,
+            normals_view_program: normals_view_program,
+            show_normals: false,
             vao: vao,
             vbo: vbo,
             ebo: ebo,
-            num_elements_to_draw: num_elements_to_draw,
-            model_matrix: Matrix4::from_translation(position),
+            columns: columns,
+            rows: rows,
+            num_elements_to_draw: indices.len() as i32,
+            index_type: indices.gl_type(),
+            model_matrix: model_matrix,
+            bounds: model_bounds.transformed(&model_matrix),
+            height_field: height_field.translated(position),
             material: material,
+        };
+        if terrain.normals_program.is_some() {
+            println!("terrain normals are worked out by a compute shader");
+            terrain.generate_normals();
         }
+        Ok(terrain)
     }
 
-    fn init_opengl(src_folder: &str) -> (Shader, GLuint, GLuint, GLuint, i32) {
+    /// The terrain's surface in world space, for keeping things on the ground
+    pub fn height_field(&self) -> &HeightField {
+        &self.height_field
+    }
 
-        let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
-        let fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
-        let heightmap_path = &format!("{}/heightmap_1.png", src_folder);
+    /// The compute program that works out the normals, if the context can run it, and the
+    /// program that shows them as lines
+    fn normals_programs(
+        src_folder: &str,
+        assets: &mut AssetCache,
+    ) -> Result<(Option<ReloadableShader>, ReloadableShader), ShaderError> {
+        let normals_program = if unsafe { compute_shaders_supported() } {
+            let builder = ShaderBuilder::new().compute(&format!("{}/normals_compute_shader.glsl", src_folder));
+            Some(assets.shader(builder)?)
+        } else {
+            None
+        };
 
-        let (mut vertices, indices) = heightmap::heightmap_data(heightmap_path, true);
-        let num_elements_to_draw = indices.len() as i32;
-        let shader_program = Shader::new(vertex_shader_path, fragment_shader_path);
-        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+        let normals_view_program = assets.shader(ShaderBuilder::new()
+            .vertex(&format!("{}/normals_view_vertex_shader.glsl", src_folder))
+            .geometry(&format!("{}/normals_view_geometry_shader.glsl", src_folder))
+            .source(
+                ShaderStage::Fragment,
+                &format!("{}/normals_view_fragment_shader", src_folder),
+                NORMALS_VIEW_FRAGMENT_SHADER,
+            ))?;
+        let layout = VertexLayout::new(&[("aPos", 0, 3), ("aNormal", 1, 3)]);
+        layout.validate(&normals_view_program)?;
+
+        Ok((normals_program, normals_view_program))
+    }
 
-        // scale the terrain
-        let width = 100.0;
-        for (index, mut coordinate) in vertices.iter_mut().enumerate() {
-            match index % 3 {
-                0 => *coordinate *= width,
-                2 => *coordinate *= width,
-                1 => *coordinate /= 50.0,
-                _ => {},
-            }
+    /// Overwrite the normals in the vertex buffer with ones worked out by the compute program
+    fn generate_normals(&self) {
+        let normals_program = match self.normals_program {
+            Some(ref normals_program) => normals_program,
+            None => return,
+        };
+        unsafe {
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, VERTICES_STORAGE_BINDING, self.vbo);
+            normals_program.use_program();
+            normals_program.set("columns", &(self.columns as u32));
+            normals_program.set("rows", &(self.rows as u32));
+            normals_program.dispatch_invocations(self.columns as u32, self.rows as u32, 1);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, VERTICES_STORAGE_BINDING, 0);
+            // the terrain is drawn from the buffer the normals were written to
+            memory_barrier(MemoryBarrier::VERTEX_ATTRIBUTES);
         }
+    }
+
+    /// Returns the shader program, VAO, VBO, EBO, indices, columns and rows of vertices, and
+    /// bounds and height field in model space
+    fn init_opengl(
+        src_folder: &str,
+        assets: &mut AssetCache,
+    ) -> Result<(ReloadableShader, GLuint, GLuint, GLuint, Indices, (usize, usize), BoundingVolume, HeightField), LoadError> {
 
-        let min_y = vertices.iter().enumerate().fold(None::<f32>, |acc, (index, &y)| {
-            if index % 3 == 1 && (acc.is_none() || y < acc.unwrap()) {
-                Some(y)
+        let vertex_shader_path = &format!("{}/vertex_shader.glsl", src_folder);
+        let fragment_shader_path = &format!("{}/fragment_shader.glsl", src_folder);
+        let (vertices, indices, columns, rows) = terrain_data(src_folder, TERRAIN_WIDTH)?;
+        let shader_program = assets.shader(ShaderBuilder::new().vertex(vertex_shader_path).fragment(fragment_shader_path))?;
+        let layout = VertexLayout::new(&[("aPos", 0, 3), ("aNormal", 1, 3), ("aTexCoords", 2, 2)]);
+        layout.validate(&shader_program)?;
+        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+
+        let min_y = vertices.chunks(heightmap::VERTEX_FLOATS).fold(None::<f32>, |acc, vertex| {
+            if acc.is_none() || vertex[1] < acc.unwrap() {
+                Some(vertex[1])
             } else {
                 acc
             }
         }).expect("This heightmap's terrain has no vertices");
-        let max_y = vertices.iter().enumerate().fold(None::<f32>, |acc, (index, &y)| {
-            if index % 3 == 1 && (acc.is_none() || y > acc.unwrap()) {
-                Some(y)
+        let max_y = vertices.chunks(heightmap::VERTEX_FLOATS).fold(None::<f32>, |acc, vertex| {
+            if acc.is_none() || vertex[1] > acc.unwrap() {
+                Some(vertex[1])
             } else {
                 acc
             }
         }).expect("This heightmap's terrain has no vertices");
 
+        let height_field = HeightField::from_vertices(&vertices, columns, rows)?;
+
         println!("terrain elevation varies between {} and {}", min_y, max_y);
+        // the grid starts at the origin, so its last vertex is at the far corner
+        let far_corner = &vertices[vertices.len() - heightmap::VERTEX_FLOATS..];
+        let bounds = BoundingVolume::Aabb {
+            min: Point3::new(0.0, min_y, 0.0),
+            max: Point3::new(far_corner[0], max_y, far_corner[2]),
+        };
 
         unsafe {
             gl::GenVertexArrays(1, &mut vao);
@@ -108,25 +231,13 @@ impl Terrain {
                            &vertices[0] as *const f32 as *const c_void,
                            gl::STATIC_DRAW);
 
-            // TODO: uncomment when the heightmap loader can calculat surface normals
-            let stride = 0;
-            // let stride = 3 * mem::size_of::<GLfloat>() as GLsizei;
-
-            // positions
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
-
-            // TODO: uncomment when the heightmap loader can calculat surface normals
-            // surface normals
-            // let surface_normals_offset = (3 * mem::size_of::<GLfloat>()) as *const c_void;
-            // gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, surface_normals_offset);
-            // gl::EnableVertexAttribArray(1);
+            layout.apply();
 
             // index buffer
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
             gl::BufferData(gl::ELEMENT_ARRAY_BUFFER,
-                           (indices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
-                           &indices[0] as *const i32 as *const c_void,
+                           indices.size_in_bytes() as GLsizeiptr,
+                           indices.as_ptr(),
                            gl::STATIC_DRAW);
 
             gl::BindVertexArray(0);
@@ -134,8 +245,39 @@ impl Terrain {
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
         }
 
-        (shader_program, vao, vbo, ebo, num_elements_to_draw)
+        Ok((shader_program, vao, vbo, ebo, indices, (columns, rows), bounds, height_field))
+    }
+}
+
+/// Vertices, indices and grid size of the terrain named by `LEARN_OPENGL_TERRAIN`, or of the
+/// lesson's own heightmap. Heightmap images are `width` units across; elevation models keep their
+/// real size at the same units per metre.
+fn terrain_data(src_folder: &str, width: f32) -> Result<(Vec<f32>, Indices, usize, usize), HeightmapError> {
+    let units_per_metre = width / HEIGHTMAP_WIDTH_METRES;
+    let path = match env::var(TERRAIN_VARIABLE) {
+        Ok(ref path) if !path.is_empty() => path.clone(),
+        _ => return heightmap::heightmap_data(
+            &format!("{}/heightmap_1.png", src_folder),
+            true,
+            HeightEncoding::Terrarium,
+            Vector3::new(width, units_per_metre, width),
+        ),
+    };
+
+    let lowercase = path.to_lowercase();
+    if lowercase.ends_with(".hgt") || lowercase.ends_with(".asc") {
+        return heightmap::elevation_data(&path, Vector3::new(units_per_metre, units_per_metre, units_per_metre));
     }
+    let encoding = match env::var(TERRAIN_ENCODING_VARIABLE) {
+        Ok(ref name) if !name.is_empty() => name.parse()?,
+        _ => HeightEncoding::Terrarium,
+    };
+    let height_scale = if encoding.is_normalised() {
+        GRAYSCALE_RELIEF_METRES * units_per_metre
+    } else {
+        units_per_metre
+    };
+    heightmap::heightmap_data(&path, true, encoding, Vector3::new(width, height_scale, width))
 }
 
 impl SceneElement for Terrain {
@@ -145,14 +287,14 @@ impl SceneElement for Terrain {
         _t: f32,
         _lights: &Vec<Light>,
         view_matrix: &Matrix4<f32>,
-        projection_matrix: &Matrix4<f32>,
+        _projection_matrix: &Matrix4<f32>,
+        _frustum: &Frustum,
+        _culling_stats: &mut CullingStats,
     ) {
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
 
             self.shader_program.use_program();
-            self.shader_program.set_mat4fv(c_str!("viewMatrix"), view_matrix);
-            self.shader_program.set_mat4fv(c_str!("projectionMatrix"), projection_matrix);
 
             // material properties
             // TODO: accept these in the constructor, set them on the struct, and pass to the
@@ -160,19 +302,16 @@ impl SceneElement for Terrain {
             self.shader_program.set_3fv(c_str!("material.ambientColor"), &self.material.ambient_color);
             self.shader_program.set_float(c_str!("material.shininess"), self.material.shininess);
             // diffuse map
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, self.material.diffuse_map_texture_id);
+            self.material.diffuse_map.bind(TextureUnit(0));
             self.shader_program.set_int(c_str!("material.diffuseColor"), 0);
 
             // specular map
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, self.material.specular_map_texture_id);
+            self.material.specular_map.bind(TextureUnit(1));
             self.shader_program.set_int(c_str!("material.specularColor"), 1);
 
-            match self.material.emission_map_texture_id {
-                Some(emission_map_id) => {
-                    gl::ActiveTexture(gl::TEXTURE2);
-                    gl::BindTexture(gl::TEXTURE_2D, emission_map_id);
+            match self.material.emission_map {
+                Some(ref emission_map) => {
+                    emission_map.bind(TextureUnit(2));
                     self.shader_program.set_bool(c_str!("material.emissionPresent"), true);
                     self.shader_program.set_int(c_str!("material.emissionColor"), 2);
                 },
@@ -200,11 +339,42 @@ impl SceneElement for Terrain {
                 &normal_matrix_in_view_space
             );
 
-            gl::DrawElements(gl::LINES, self.num_elements_to_draw, gl::UNSIGNED_INT, ptr::null());
+            gl::DrawElements(gl::TRIANGLES, self.num_elements_to_draw, self.index_type, ptr::null());
+
+            if self.show_normals {
+                self.normals_view_program.use_program();
+                self.normals_view_program.set_mat4fv(c_str!("modelMatrix"), &self.model_matrix);
+                self.normals_view_program.set_float(c_str!("normalLength"), NORMAL_VIEW_LENGTH);
+                gl::DrawArrays(gl::POINTS, 0, (self.columns * self.rows) as GLsizei);
+            }
 
             gl::BindVertexArray(0);
         }
     }
+
+    fn bounds(&self) -> Option<BoundingVolume> {
+        Some(self.bounds)
+    }
+
+    fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        self.height_field.intersect_ray(ray).map(|distance| RayHit { distance: distance, instance: 0 })
+    }
+
+    fn show_normals(&mut self, show: bool) {
+        self.show_normals = show;
+    }
+
+    fn reload_shaders(&mut self) {
+        self.shader_program.reload_if_changed();
+        self.normals_view_program.reload_if_changed();
+        let normals_reloaded = match self.normals_program {
+            Some(ref mut normals_program) => normals_program.reload_if_changed(),
+            None => false,
+        };
+        if normals_reloaded {
+            self.generate_normals();
+        }
+    }
 }
 
 impl Drop for Terrain {
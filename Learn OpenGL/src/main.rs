@@ -1,5 +1,6 @@
 extern crate gl;
 extern crate image;
+extern crate png;
 extern crate cgmath;
 extern crate tobj;
 
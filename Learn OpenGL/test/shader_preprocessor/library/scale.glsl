@@ -0,0 +1,2 @@
+// included from an include folder rather than next to the shader
+const float LIBRARY_SCALE = 2.0;
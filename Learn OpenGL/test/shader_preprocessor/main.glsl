@@ -0,0 +1,8 @@
+#version 330 core
+#include "shade.glsl"
+
+out vec4 fragColor;
+
+void main() {
+    fragColor = shade();
+}
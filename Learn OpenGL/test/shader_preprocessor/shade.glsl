@@ -0,0 +1,3 @@
+vec4 shade() {
+    return vec4(1.0);
+}